@@ -0,0 +1,271 @@
+#[cfg(test)]
+mod tests;
+
+use std::path::PathBuf;
+use anyhow::Result;
+use akanec::{
+    arity,
+    data::{
+        ast::{
+            ModuleAst,
+            ExternFnAst,
+            FnDefAst,
+            IdentAst,
+        },
+        builtins::{
+            BUILTINS,
+            builtin_arity,
+        },
+        diagnostic::Diagnostic,
+        span::Span,
+        ty::FnTy,
+    },
+    lexer,
+    linter::{
+        self,
+        Levels,
+    },
+    loader,
+    parser,
+    printer,
+    resolver::{
+        self,
+        Def,
+        SymbolTable,
+    },
+    simplifier,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SymbolKind {
+    Fn,
+    ExternFn,
+    Arg,
+    Builtin,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Symbol {
+    pub name: String,
+    pub kind: SymbolKind,
+    /// The defining identifier, `None` for builtins.
+    pub span: Option<Span>,
+    /// The whole definition, `None` for builtins.
+    pub def_span: Option<Span>,
+    /// The file defining the symbol, `None` if it is the analyzed one.
+    pub path: Option<PathBuf>,
+    pub signature: String,
+    pub arity: usize,
+}
+
+impl Symbol {
+    fn fn_def(fn_def_ast: &FnDefAst, name: String, path: Option<PathBuf>) -> Self {
+        let arity = fn_def_ast.left_def.args.len();
+        let signature = match FnTy::from_ty_annot(fn_def_ast.ty_annot.as_ref(), arity) {
+            Ok(fn_ty) => format!("{} : {}", name, fn_ty),
+            Err(_) => name.clone(),
+        };
+        Self {
+            name,
+            kind: SymbolKind::Fn,
+            span: Some(fn_def_ast.left_def.ident.span),
+            def_span: Some(fn_def_ast.span),
+            path,
+            signature,
+            arity,
+        }
+    }
+
+    fn extern_fn(extern_fn_ast: &ExternFnAst) -> Self {
+        let name = extern_fn_ast.ident.name.clone();
+        Self {
+            signature: format!("extern {} {} : {}", printer::str_lit(&extern_fn_ast.abi.value), name, printer::ty(&extern_fn_ast.ty)),
+            arity: FnTy::from_ty_ast(&extern_fn_ast.ty).map_or(0, |fn_ty| fn_ty.args.len()),
+            name,
+            kind: SymbolKind::ExternFn,
+            span: Some(extern_fn_ast.ident.span),
+            def_span: Some(extern_fn_ast.span),
+            path: None,
+        }
+    }
+
+    fn arg(fn_def_ast: &FnDefAst, index: usize) -> Self {
+        let arg = &fn_def_ast.left_def.args[index];
+        let signature = match FnTy::from_ty_annot(fn_def_ast.ty_annot.as_ref(), fn_def_ast.left_def.args.len()) {
+            Ok(fn_ty) => format!("{} : {}", arg.name, fn_ty.args[index]),
+            Err(_) => arg.name.clone(),
+        };
+        Self {
+            name: arg.name.clone(),
+            kind: SymbolKind::Arg,
+            span: Some(arg.span),
+            def_span: Some(arg.span),
+            path: None,
+            signature,
+            arity: 0,
+        }
+    }
+
+    fn builtin(name: &str, arity: usize) -> Self {
+        Self {
+            name: name.to_owned(),
+            kind: SymbolKind::Builtin,
+            span: None,
+            def_span: None,
+            path: None,
+            signature: name.to_owned(),
+            arity,
+        }
+    }
+
+    /// Markdown shown on hover.
+    pub fn describe(&self) -> String {
+        let arity = arity::count(self.arity);
+        let note = match self.kind {
+            SymbolKind::Fn => format!("Function taking {}.", arity),
+            SymbolKind::ExternFn => format!("External function taking {}.", arity),
+            SymbolKind::Arg => "Parameter.".to_owned(),
+            SymbolKind::Builtin => format!("Builtin function taking {}.", arity),
+        };
+        format!("```akane\n{}\n```\n{}", self.signature, note)
+    }
+}
+
+/// What the server knows about one open document.
+#[derive(Clone, Debug)]
+pub struct Analysis {
+    pub diagnostics: Vec<Diagnostic>,
+    module_ast: Option<ModuleAst>,
+    /// Top-level functions visible in the document, imported ones under their qualified names.
+    globals: Vec<Symbol>,
+    symbol_table: SymbolTable,
+}
+
+impl Analysis {
+    /// `dirs` are searched for imported modules.
+    pub fn new(code: &str, dirs: &[PathBuf]) -> Self {
+        let parsed = lexer::lex(code.to_owned()).and_then(|tokens| Ok((parser::parse(tokens.clone())?, tokens)));
+        let (module_ast, tokens) = match parsed {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                let diagnostics = vec![to_diagnostic(e, Span::default())];
+                return Self { diagnostics, module_ast: None, globals: Vec::new(), symbol_table: SymbolTable::default() };
+            },
+        };
+        let mut diagnostics = Vec::new();
+        let mut globals = Vec::new();
+        globals.extend(module_ast.extern_fns.iter().map(Symbol::extern_fn));
+        globals.extend(module_ast.fn_defs.iter().map(|fn_def_ast| Symbol::fn_def(fn_def_ast, fn_def_ast.left_def.ident.name.clone(), None)));
+        for import_ast in &module_ast.imports {
+            let name = &import_ast.module.name;
+            match import(name, dirs) {
+                Ok((imported, path)) =>
+                    for fn_def_ast in imported.fn_defs.iter().filter(|fn_def_ast| fn_def_ast.is_pub) {
+                        let qualified = format!("{}.{}", name, fn_def_ast.left_def.ident.name);
+                        globals.push(Symbol::fn_def(fn_def_ast, qualified, Some(path.clone())));
+                    },
+                Err(e) => diagnostics.push(to_diagnostic(e, import_ast.span)),
+            }
+        }
+        let imported = globals.iter().filter(|symbol| symbol.path.is_some()).map(|symbol| symbol.name.clone()).collect::<Vec<_>>();
+        let symbol_table = resolver::resolve_with(&module_ast, &imported);
+        diagnostics.extend(symbol_table.diagnostics.iter().cloned());
+        diagnostics.extend(arity::diagnose(&module_ast, &symbol_table));
+        diagnostics.extend(simplifier::diagnose(&module_ast, &symbol_table));
+        diagnostics.extend(linter::lint(&tokens, &module_ast, &symbol_table, &Levels::default()));
+        Self { diagnostics, module_ast: Some(module_ast), globals, symbol_table }
+    }
+
+    pub fn is_parsed(&self) -> bool {
+        self.module_ast.is_some()
+    }
+
+    /// The symbol named by the identifier at `offset`.
+    pub fn definition(&self, offset: usize) -> Option<Symbol> {
+        self.ident_at(offset).and_then(|(ident, fn_def_ast)| self.symbol_of(ident, fn_def_ast))
+    }
+
+    /// The identifier at `offset` and the markdown describing it.
+    pub fn hover(&self, offset: usize) -> Option<(Span, String)> {
+        let (ident, fn_def_ast) = self.ident_at(offset)?;
+        let symbol = self.symbol_of(ident, fn_def_ast)?;
+        Some((ident.span, symbol.describe()))
+    }
+
+    /// The functions defined in the document, in source order.
+    pub fn fn_defs(&self) -> Vec<Symbol> {
+        self.globals.iter().filter(|symbol| symbol.kind == SymbolKind::Fn && symbol.path.is_none()).cloned().collect()
+    }
+
+    /// Every name usable at `offset`: the parameters of the enclosing definition,
+    /// the visible top-level functions and the builtins.
+    pub fn completions(&self, offset: usize) -> Vec<Symbol> {
+        let mut symbols = Vec::new();
+        if let Some(fn_def_ast) = self.fn_def_at(offset) {
+            symbols.extend((0..fn_def_ast.left_def.args.len()).map(|i| Symbol::arg(fn_def_ast, i)));
+        }
+        symbols.extend(self.globals.iter().cloned());
+        symbols.extend(BUILTINS.iter().map(|name| Symbol::builtin(name, builtin_arity(name).unwrap())));
+        symbols
+    }
+
+    fn fn_def_at(&self, offset: usize) -> Option<&FnDefAst> {
+        self.module_ast.as_ref()?.fn_defs.iter().find(|fn_def_ast| touches(fn_def_ast.span, offset))
+    }
+
+    fn ident_at(&self, offset: usize) -> Option<(&IdentAst, Option<&FnDefAst>)> {
+        let module_ast = self.module_ast.as_ref()?;
+        for extern_fn_ast in &module_ast.extern_fns {
+            if touches(extern_fn_ast.ident.span, offset) {
+                return Some((&extern_fn_ast.ident, None));
+            }
+        }
+        let fn_def_ast = self.fn_def_at(offset)?;
+        let left_def = &fn_def_ast.left_def;
+        let mut idents = [&left_def.ident].into_iter().chain(&left_def.args).collect::<Vec<_>>();
+        idents.extend(fn_def_ast.expr.idents());
+        idents.into_iter().find(|ident| touches(ident.span, offset)).map(|ident| (ident, Some(fn_def_ast)))
+    }
+
+    /// Definitions are described by themselves, uses by what the resolver found them to refer to.
+    fn symbol_of(&self, ident: &IdentAst, fn_def_ast: Option<&FnDefAst>) -> Option<Symbol> {
+        let Some(fn_def_ast) = fn_def_ast else {
+            return self.globals.iter().find(|symbol| symbol.name == ident.name).cloned();
+        };
+        let left_def = &fn_def_ast.left_def;
+        if left_def.ident.span.start == ident.span.start {
+            return Some(Symbol::fn_def(fn_def_ast, left_def.ident.name.clone(), None));
+        }
+        if let Some(i) = left_def.args.iter().position(|arg| arg.span.start == ident.span.start) {
+            return Some(Symbol::arg(fn_def_ast, i));
+        }
+        let module_ast = self.module_ast.as_ref()?;
+        match self.symbol_table.use_at(ident.span.start)?.def {
+            Def::Fn(i) => {
+                let fn_def_ast = &module_ast.fn_defs[i];
+                Some(Symbol::fn_def(fn_def_ast, fn_def_ast.left_def.ident.name.clone(), None))
+            },
+            Def::ExternFn(i) => Some(Symbol::extern_fn(&module_ast.extern_fns[i])),
+            Def::Param(f, i) => Some(Symbol::arg(&module_ast.fn_defs[f], i)),
+            Def::Imported(i) => self.globals.iter().filter(|symbol| symbol.path.is_some()).nth(i).cloned(),
+            Def::Builtin => builtin_arity(&ident.name).map(|arity| Symbol::builtin(&ident.name, arity)),
+        }
+    }
+}
+
+fn import(name: &str, dirs: &[PathBuf]) -> Result<(ModuleAst, PathBuf)> {
+    let path = loader::find_module(name, dirs)?;
+    Ok((loader::read_module(&path)?, path))
+}
+
+fn to_diagnostic(e: anyhow::Error, span: Span) -> Diagnostic {
+    match e.downcast_ref::<Diagnostic>() {
+        Some(diagnostic) => diagnostic.clone(),
+        None => Diagnostic::error(e.to_string(), span),
+    }
+}
+
+/// Like `Span::contains`, but also true just after the end, where the cursor sits after typing a name.
+fn touches(span: Span, offset: usize) -> bool {
+    span.start <= offset && offset <= span.end
+}
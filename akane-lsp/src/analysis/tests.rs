@@ -0,0 +1,116 @@
+use std::{
+    env,
+    fs,
+    path::PathBuf,
+    process,
+    slice,
+};
+use akanec::data::diagnostic::Severity;
+use super::{
+    Analysis,
+    SymbolKind,
+};
+
+const CODE: &str = "\
+import Math;
+extern \"C\" abs : i32 -> i32;
+pub scale : i32 -> i32 -> i32;
+scale k x = k * Math.double x;
+pub f x = scale 2 (abs x) + length [x];
+";
+
+fn write_math(test_name: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("akane_lsp_analysis_{}_{}", test_name, process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("Math.akane"), "module Math;\npub double x = x + x;\nhidden = 0;\n").unwrap();
+    dir
+}
+
+fn offset(code: &str, pattern: &str) -> usize {
+    code.find(pattern).unwrap()
+}
+
+#[test]
+fn analysis_diagnostics() {
+    assert!(Analysis::new(CODE, &[]).diagnostics.iter().any(|diagnostic| diagnostic.message == "Module not found: Math"));
+
+    let analysis = Analysis::new("pub f x = g x;\ng y = y + z;", &[]);
+    let messages = analysis.diagnostics.iter().map(|diagnostic| (diagnostic.message.as_str(), diagnostic.span.start, diagnostic.span.end)).collect::<Vec<_>>();
+    assert_eq!(messages, vec![("Unknown identifier: z", 25, 26)]);
+
+    let analysis = Analysis::new("double x = x + x;\npub f double = dobule double;", &[]);
+    let messages = analysis.diagnostics.iter().map(|diagnostic| (diagnostic.severity, diagnostic.message.as_str())).collect::<Vec<_>>();
+    assert_eq!(
+        messages,
+        vec![
+            (Severity::Error, "Undefined function: dobule (did you mean double?)"),
+            (Severity::Warning, "Unused function: double"),
+            (Severity::Warning, "Parameter double shadows a function."),
+        ]
+    );
+
+    let analysis = Analysis::new("add a b = a + b;\npub f x y = (add x);", &[]);
+    let messages = analysis.diagnostics.iter().map(|diagnostic| (diagnostic.message.as_str(), diagnostic.span.start, diagnostic.span.end)).collect::<Vec<_>>();
+    assert_eq!(messages, vec![("add takes 2 arguments but 1 was given.", 30, 35), ("Unused parameter: y", 25, 26), ("Redundant parentheses.", 29, 36)]);
+
+    let analysis = Analysis::new("f x = x +;", &[]);
+    assert!(!analysis.is_parsed());
+    assert_eq!(analysis.diagnostics.len(), 1);
+    assert_eq!(analysis.diagnostics[0].message, "Term required.");
+    assert_eq!((analysis.diagnostics[0].span.start, analysis.diagnostics[0].span.end), (9, 10));
+}
+
+#[test]
+fn analysis_definition() {
+    let dir = write_math("definition");
+    let analysis = Analysis::new(CODE, slice::from_ref(&dir));
+    assert_eq!(analysis.diagnostics, vec![]);
+
+    let symbol = analysis.definition(offset(CODE, "k * ")).unwrap();
+    assert_eq!((symbol.kind, symbol.span.unwrap().start), (SymbolKind::Arg, offset(CODE, "k x =")));
+
+    let symbol = analysis.definition(offset(CODE, "scale 2") + 5).unwrap();
+    assert_eq!((symbol.kind, symbol.span.unwrap().start), (SymbolKind::Fn, offset(CODE, "scale k")));
+    assert_eq!(symbol.def_span.unwrap().start, offset(CODE, "pub scale"));
+
+    let symbol = analysis.definition(offset(CODE, "abs x")).unwrap();
+    assert_eq!((symbol.kind, symbol.span.unwrap().start), (SymbolKind::ExternFn, offset(CODE, "abs :")));
+
+    let symbol = analysis.definition(offset(CODE, "Math.double")).unwrap();
+    assert_eq!(symbol.path, Some(dir.join("Math.akane")));
+    assert_eq!(symbol.span.unwrap().start, "module Math;\npub ".len());
+
+    assert_eq!(analysis.definition(offset(CODE, "length")).unwrap().kind, SymbolKind::Builtin);
+    assert_eq!(analysis.definition(offset(CODE, "* Math")), None);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn analysis_hover() {
+    let dir = write_math("hover");
+    let analysis = Analysis::new(CODE, slice::from_ref(&dir));
+    let (span, markdown) = analysis.hover(offset(CODE, "scale 2")).unwrap();
+    assert_eq!((span.start, span.end), (offset(CODE, "scale 2"), offset(CODE, "scale 2") + 5));
+    assert_eq!(markdown, "```akane\nscale : i32 -> i32 -> i32\n```\nFunction taking 2 arguments.");
+    assert_eq!(analysis.hover(offset(CODE, "k * ")).unwrap().1, "```akane\nk : i32\n```\nParameter.");
+    assert_eq!(analysis.hover(offset(CODE, "abs x")).unwrap().1, "```akane\nextern \"C\" abs : i32 -> i32\n```\nExternal function taking 1 argument.");
+    assert_eq!(analysis.hover(offset(CODE, "length")).unwrap().1, "```akane\nlength\n```\nBuiltin function taking 1 argument.");
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn analysis_symbols_and_completions() {
+    let dir = write_math("completions");
+    let analysis = Analysis::new(CODE, slice::from_ref(&dir));
+    assert_eq!(analysis.fn_defs().iter().map(|symbol| symbol.name.as_str()).collect::<Vec<_>>(), vec!["scale", "f"]);
+
+    let names = |offset| analysis.completions(offset).into_iter().map(|symbol| symbol.name).collect::<Vec<_>>();
+    let in_scale = names(offset(CODE, "k * "));
+    assert_eq!(&in_scale[..2], &["k".to_owned(), "x".to_owned()]);
+    assert!(in_scale.contains(&"Math.double".to_owned()));
+    assert!(!in_scale.contains(&"Math.hidden".to_owned()));
+    assert!(in_scale.contains(&"abs".to_owned()) && in_scale.contains(&"fold".to_owned()));
+    assert!(!names(0).contains(&"k".to_owned()));
+    fs::remove_dir_all(&dir).unwrap();
+}
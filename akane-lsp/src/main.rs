@@ -0,0 +1,10 @@
+use anyhow::Result;
+use lsp_server::Connection;
+use akane_lsp::server;
+
+fn main() -> Result<()> {
+    let (connection, io_threads) = Connection::stdio();
+    server::run(connection)?;
+    io_threads.join()?;
+    Ok(())
+}
@@ -0,0 +1,401 @@
+#[cfg(test)]
+mod tests;
+
+use std::{
+    collections::HashMap,
+    fs,
+    path::{
+        Path,
+        PathBuf,
+    },
+};
+use anyhow::{
+    bail,
+    Result,
+};
+use lsp_server::{
+    Connection,
+    ErrorCode,
+    Message,
+    Notification,
+    Request,
+    Response,
+};
+use lsp_types::{
+    notification::{
+        DidChangeTextDocument,
+        DidCloseTextDocument,
+        DidOpenTextDocument,
+        Notification as _,
+        PublishDiagnostics,
+    },
+    request::{
+        Completion,
+        DocumentSymbolRequest,
+        GotoDefinition,
+        HoverRequest,
+        Request as _,
+        SemanticTokensFullRequest,
+    },
+    CompletionItem,
+    CompletionItemKind,
+    CompletionOptions,
+    CompletionParams,
+    CompletionResponse,
+    DiagnosticSeverity,
+    DidChangeTextDocumentParams,
+    DidCloseTextDocumentParams,
+    DidOpenTextDocumentParams,
+    DocumentSymbol,
+    DocumentSymbolParams,
+    DocumentSymbolResponse,
+    GotoDefinitionParams,
+    GotoDefinitionResponse,
+    Hover,
+    HoverContents,
+    HoverParams,
+    HoverProviderCapability,
+    InitializeParams,
+    Location,
+    MarkupContent,
+    MarkupKind,
+    OneOf,
+    Position,
+    PublishDiagnosticsParams,
+    Range,
+    SemanticToken,
+    SemanticTokenModifier,
+    SemanticTokenType,
+    SemanticTokens,
+    SemanticTokensFullOptions,
+    SemanticTokensLegend,
+    SemanticTokensOptions,
+    SemanticTokensParams,
+    SemanticTokensResult,
+    ServerCapabilities,
+    SymbolKind,
+    TextDocumentSyncCapability,
+    TextDocumentSyncKind,
+    Url,
+};
+use serde_json::Value;
+use akanec::{
+    data::{
+        diagnostic::Severity,
+        span::Span,
+    },
+    highlighter::{
+        self,
+        Class,
+        Highlight,
+    },
+};
+use crate::analysis::{
+    self,
+    Analysis,
+};
+
+/// Maps byte offsets to LSP positions, whose characters count UTF-16 code units.
+#[derive(Clone, Debug)]
+pub struct LineIndex {
+    text: String,
+    line_starts: Vec<usize>,
+}
+
+impl LineIndex {
+    pub fn new(text: String) -> Self {
+        let line_starts = [0].into_iter().chain(text.match_indices('\n').map(|(i, _)| i + 1)).collect();
+        Self { text, line_starts }
+    }
+
+    pub fn position(&self, offset: usize) -> Position {
+        let offset = offset.min(self.text.len());
+        let line = self.line_starts.partition_point(|start| *start <= offset) - 1;
+        let character = self.text[self.line_starts[line]..offset].encode_utf16().count();
+        Position::new(line as u32, character as u32)
+    }
+
+    pub fn offset(&self, position: Position) -> usize {
+        let Some(start) = self.line_starts.get(position.line as usize) else {
+            return self.text.len();
+        };
+        let mut units = 0;
+        for (i, c) in self.text[*start..].char_indices() {
+            if units >= position.character as usize || c == '\n' {
+                return start + i;
+            }
+            units += c.len_utf16();
+        }
+        self.text.len()
+    }
+
+    pub fn range(&self, span: Span) -> Range {
+        Range::new(self.position(span.start), self.position(span.end))
+    }
+
+    /// Encodes `highlights` relative to each other, splitting those spanning lines,
+    /// since clients may not support multiline tokens.
+    pub fn semantic_tokens(&self, highlights: &[Highlight]) -> Vec<SemanticToken> {
+        let mut tokens = Vec::new();
+        let mut prev = Position::new(0, 0);
+        for highlight in highlights {
+            let token_type = TOKEN_TYPES.iter().position(|token_type| *token_type == semantic_token_type(highlight.class)).unwrap() as u32;
+            let token_modifiers_bitset = if highlight.class == Class::FnDef { 1 } else { 0 };
+            let mut start = highlight.span.start;
+            for line in self.text[highlight.span.start..highlight.span.end].split('\n') {
+                let position = self.position(start);
+                let length = line.encode_utf16().count() as u32;
+                start += line.len() + 1;
+                if length == 0 {
+                    continue;
+                }
+                let delta_line = position.line - prev.line;
+                let delta_start = if delta_line == 0 { position.character - prev.character } else { position.character };
+                tokens.push(SemanticToken { delta_line, delta_start, length, token_type, token_modifiers_bitset });
+                prev = position;
+            }
+        }
+        tokens
+    }
+}
+
+const TOKEN_TYPES: [SemanticTokenType; 9] = [
+    SemanticTokenType::FUNCTION,
+    SemanticTokenType::PARAMETER,
+    SemanticTokenType::TYPE,
+    SemanticTokenType::NAMESPACE,
+    SemanticTokenType::KEYWORD,
+    SemanticTokenType::OPERATOR,
+    SemanticTokenType::NUMBER,
+    SemanticTokenType::STRING,
+    SemanticTokenType::COMMENT,
+];
+
+fn semantic_token_type(class: Class) -> SemanticTokenType {
+    match class {
+        Class::FnDef | Class::Call => SemanticTokenType::FUNCTION,
+        Class::Param => SemanticTokenType::PARAMETER,
+        Class::Ty => SemanticTokenType::TYPE,
+        Class::Module => SemanticTokenType::NAMESPACE,
+        Class::Keyword => SemanticTokenType::KEYWORD,
+        Class::Op => SemanticTokenType::OPERATOR,
+        Class::Num => SemanticTokenType::NUMBER,
+        Class::Str | Class::Char => SemanticTokenType::STRING,
+        Class::Comment => SemanticTokenType::COMMENT,
+    }
+}
+
+/// Function definitions are the only tokens with a modifier, `declaration` in bit 0.
+pub fn semantic_tokens_legend() -> SemanticTokensLegend {
+    SemanticTokensLegend { token_types: TOKEN_TYPES.to_vec(), token_modifiers: vec![SemanticTokenModifier::DECLARATION] }
+}
+
+struct Document {
+    index: LineIndex,
+    analysis: Analysis,
+    /// The latest analysis of a version that parsed, so that completion keeps working while typing.
+    parsed: Option<Analysis>,
+}
+
+pub struct Server {
+    connection: Connection,
+    search_paths: Vec<PathBuf>,
+    documents: HashMap<Url, Document>,
+}
+
+pub fn capabilities() -> ServerCapabilities {
+    ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        definition_provider: Some(OneOf::Left(true)),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        document_symbol_provider: Some(OneOf::Left(true)),
+        completion_provider: Some(CompletionOptions { trigger_characters: Some(vec![".".to_owned()]), ..Default::default() }),
+        semantic_tokens_provider: Some(SemanticTokensOptions {
+            legend: semantic_tokens_legend(),
+            full: Some(SemanticTokensFullOptions::Bool(true)),
+            ..Default::default()
+        }.into()),
+        ..Default::default()
+    }
+}
+
+/// Serves `connection` until the client shuts the server down.
+/// The `include` initialization option lists more directories to search for imported modules.
+pub fn run(connection: Connection) -> Result<()> {
+    let params = connection.initialize(serde_json::to_value(capabilities())?)?;
+    let params = serde_json::from_value::<InitializeParams>(params)?;
+    let search_paths = params.initialization_options
+        .and_then(|options| options.get("include").cloned())
+        .and_then(|include| serde_json::from_value::<Vec<PathBuf>>(include).ok())
+        .unwrap_or_default();
+    let mut server = Server { connection, search_paths, documents: HashMap::new() };
+    server.main_loop()
+}
+
+impl Server {
+    fn main_loop(&mut self) -> Result<()> {
+        while let Ok(message) = self.connection.receiver.recv() {
+            match message {
+                Message::Request(request) => {
+                    if self.connection.handle_shutdown(&request)? {
+                        return Ok(());
+                    }
+                    let response = self.handle_request(request);
+                    self.connection.sender.send(Message::Response(response))?;
+                },
+                Message::Notification(notification) => self.handle_notification(notification)?,
+                Message::Response(_) => (),
+            }
+        }
+        Ok(())
+    }
+
+    fn handle_request(&self, request: Request) -> Response {
+        let result = match request.method.as_str() {
+            GotoDefinition::METHOD => self.definition(request.params),
+            HoverRequest::METHOD => self.hover(request.params),
+            DocumentSymbolRequest::METHOD => self.document_symbols(request.params),
+            Completion::METHOD => self.completion(request.params),
+            SemanticTokensFullRequest::METHOD => self.semantic_tokens(request.params),
+            method => return Response::new_err(request.id, ErrorCode::MethodNotFound as i32, format!("Unknown method: {}", method)),
+        };
+        match result {
+            Ok(value) => Response::new_ok(request.id, value),
+            Err(e) => Response::new_err(request.id, ErrorCode::InvalidParams as i32, e.to_string()),
+        }
+    }
+
+    fn handle_notification(&mut self, notification: Notification) -> Result<()> {
+        match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let params = serde_json::from_value::<DidOpenTextDocumentParams>(notification.params)?;
+                self.update(params.text_document.uri, params.text_document.text)
+            },
+            DidChangeTextDocument::METHOD => {
+                let params = serde_json::from_value::<DidChangeTextDocumentParams>(notification.params)?;
+                match params.content_changes.into_iter().last() {
+                    Some(change) => self.update(params.text_document.uri, change.text),
+                    None => Ok(()),
+                }
+            },
+            DidCloseTextDocument::METHOD => {
+                let params = serde_json::from_value::<DidCloseTextDocumentParams>(notification.params)?;
+                self.documents.remove(&params.text_document.uri);
+                self.publish_diagnostics(params.text_document.uri, Vec::new())
+            },
+            _ => Ok(()),
+        }
+    }
+
+    fn update(&mut self, uri: Url, text: String) -> Result<()> {
+        let mut dirs = Vec::new();
+        if let Some(dir) = uri.to_file_path().ok().and_then(|path| path.parent().map(Path::to_path_buf)) {
+            dirs.push(dir);
+        }
+        dirs.extend(self.search_paths.iter().cloned());
+        let analysis = Analysis::new(&text, &dirs);
+        let parsed = if analysis.is_parsed() { Some(analysis.clone()) } else { self.documents.remove(&uri).and_then(|document| document.parsed) };
+        let index = LineIndex::new(text);
+        let diagnostics = analysis.diagnostics.iter().map(|diagnostic| lsp_types::Diagnostic {
+            range: index.range(diagnostic.span),
+            severity: Some(match diagnostic.severity {
+                Severity::Error => DiagnosticSeverity::ERROR,
+                Severity::Warning => DiagnosticSeverity::WARNING,
+            }),
+            source: Some("akane".to_owned()),
+            message: diagnostic.message.clone(),
+            ..Default::default()
+        })
+        .collect();
+        self.documents.insert(uri.clone(), Document { index, analysis, parsed });
+        self.publish_diagnostics(uri, diagnostics)
+    }
+
+    fn publish_diagnostics(&self, uri: Url, diagnostics: Vec<lsp_types::Diagnostic>) -> Result<()> {
+        let params = PublishDiagnosticsParams { uri, diagnostics, version: None };
+        let notification = Notification::new(PublishDiagnostics::METHOD.to_owned(), params);
+        self.connection.sender.send(Message::Notification(notification))?;
+        Ok(())
+    }
+
+    fn document(&self, uri: &Url) -> Result<&Document> {
+        match self.documents.get(uri) {
+            Some(document) => Ok(document),
+            None => bail!("Document not open: {}", uri),
+        }
+    }
+
+    fn definition(&self, params: Value) -> Result<Value> {
+        let params = serde_json::from_value::<GotoDefinitionParams>(params)?;
+        let uri = params.text_document_position_params.text_document.uri;
+        let document = self.document(&uri)?;
+        let offset = document.index.offset(params.text_document_position_params.position);
+        let location = document.analysis.definition(offset).and_then(|symbol| {
+            let span = symbol.span?;
+            match &symbol.path {
+                None => Some(Location::new(uri.clone(), document.index.range(span))),
+                Some(path) => {
+                    let index = LineIndex::new(fs::read_to_string(path).ok()?);
+                    Some(Location::new(Url::from_file_path(path).ok()?, index.range(span)))
+                },
+            }
+        });
+        Ok(serde_json::to_value(location.map(GotoDefinitionResponse::Scalar))?)
+    }
+
+    fn hover(&self, params: Value) -> Result<Value> {
+        let params = serde_json::from_value::<HoverParams>(params)?;
+        let document = self.document(&params.text_document_position_params.text_document.uri)?;
+        let offset = document.index.offset(params.text_document_position_params.position);
+        let hover = document.analysis.hover(offset).map(|(span, value)| Hover {
+            contents: HoverContents::Markup(MarkupContent { kind: MarkupKind::Markdown, value }),
+            range: Some(document.index.range(span)),
+        });
+        Ok(serde_json::to_value(hover)?)
+    }
+
+    #[allow(deprecated)]
+    fn document_symbols(&self, params: Value) -> Result<Value> {
+        let params = serde_json::from_value::<DocumentSymbolParams>(params)?;
+        let document = self.document(&params.text_document.uri)?;
+        let symbols = document.analysis.fn_defs().into_iter().map(|symbol| DocumentSymbol {
+            name: symbol.name,
+            detail: Some(symbol.signature),
+            kind: SymbolKind::FUNCTION,
+            tags: None,
+            deprecated: None,
+            range: document.index.range(symbol.def_span.unwrap_or_default()),
+            selection_range: document.index.range(symbol.span.unwrap_or_default()),
+            children: None,
+        })
+        .collect();
+        Ok(serde_json::to_value(DocumentSymbolResponse::Nested(symbols))?)
+    }
+
+    fn completion(&self, params: Value) -> Result<Value> {
+        let params = serde_json::from_value::<CompletionParams>(params)?;
+        let document = self.document(&params.text_document_position.text_document.uri)?;
+        let offset = document.index.offset(params.text_document_position.position);
+        let analysis = document.parsed.as_ref().unwrap_or(&document.analysis);
+        let items = analysis.completions(offset).into_iter().map(|symbol| CompletionItem {
+            kind: Some(match symbol.kind {
+                analysis::SymbolKind::Arg => CompletionItemKind::VARIABLE,
+                _ => CompletionItemKind::FUNCTION,
+            }),
+            detail: Some(symbol.signature),
+            label: symbol.name,
+            ..Default::default()
+        })
+        .collect();
+        Ok(serde_json::to_value(CompletionResponse::Array(items))?)
+    }
+
+    /// A document that does not even lex has no tokens, its diagnostics tell why.
+    fn semantic_tokens(&self, params: Value) -> Result<Value> {
+        let params = serde_json::from_value::<SemanticTokensParams>(params)?;
+        let document = self.document(&params.text_document.uri)?;
+        let highlights = highlighter::highlight(&document.index.text).unwrap_or_default();
+        let tokens = SemanticTokens { result_id: None, data: document.index.semantic_tokens(&highlights) };
+        Ok(serde_json::to_value(SemanticTokensResult::Tokens(tokens))?)
+    }
+}
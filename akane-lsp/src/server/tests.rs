@@ -0,0 +1,174 @@
+use std::thread;
+use lsp_server::{
+    Connection,
+    Message,
+    Notification,
+    Request,
+    RequestId,
+};
+use lsp_types::Position;
+use serde_json::{
+    json,
+    Value,
+};
+use akanec::data::span::Span;
+use super::LineIndex;
+
+const URI: &str = "file:///tmp/akane_lsp_server/main.akane";
+
+/// Plays the client side of a session, collecting the diagnostics the server publishes.
+struct Client {
+    connection: Connection,
+    next_id: i32,
+    diagnostics: Vec<Value>,
+}
+
+impl Client {
+    fn start() -> (Self, thread::JoinHandle<()>) {
+        let (server, client) = Connection::memory();
+        let handle = thread::spawn(move || super::run(server).unwrap());
+        let mut client = Self { connection: client, next_id: 0, diagnostics: Vec::new() };
+        let result = client.request("initialize", json!({ "capabilities": {} }));
+        assert_eq!(result["capabilities"]["definitionProvider"], json!(true));
+        client.notify("initialized", json!({}));
+        (client, handle)
+    }
+
+    fn request(&mut self, method: &str, params: Value) -> Value {
+        self.next_id += 1;
+        let id = RequestId::from(self.next_id);
+        self.connection.sender.send(Message::Request(Request::new(id.clone(), method.to_owned(), params))).unwrap();
+        loop {
+            match self.connection.receiver.recv().unwrap() {
+                Message::Response(response) if response.id == id => {
+                    if let Some(error) = response.error {
+                        panic!("{}: {}", method, error.message);
+                    }
+                    return response.result.unwrap_or(Value::Null);
+                },
+                Message::Notification(notification) => self.receive(notification),
+                message => panic!("Unexpected message: {:?}", message),
+            }
+        }
+    }
+
+    fn notify(&mut self, method: &str, params: Value) {
+        self.connection.sender.send(Message::Notification(Notification::new(method.to_owned(), params))).unwrap();
+    }
+
+    fn receive(&mut self, notification: Notification) {
+        assert_eq!(notification.method, "textDocument/publishDiagnostics");
+        self.diagnostics.push(notification.params["diagnostics"].clone());
+    }
+
+    /// Waits for the diagnostics published after a change.
+    fn next_diagnostics(&mut self) -> Value {
+        if self.diagnostics.is_empty() {
+            match self.connection.receiver.recv().unwrap() {
+                Message::Notification(notification) => self.receive(notification),
+                message => panic!("Unexpected message: {:?}", message),
+            }
+        }
+        self.diagnostics.remove(0)
+    }
+
+    fn open(&mut self, text: &str) {
+        self.notify("textDocument/didOpen", json!({
+            "textDocument": { "uri": URI, "languageId": "akane", "version": 1, "text": text },
+        }));
+    }
+
+    fn change(&mut self, version: i32, text: &str) {
+        self.notify("textDocument/didChange", json!({
+            "textDocument": { "uri": URI, "version": version },
+            "contentChanges": [{ "text": text }],
+        }));
+    }
+
+    fn at(&mut self, method: &str, line: u32, character: u32) -> Value {
+        self.request(method, json!({
+            "textDocument": { "uri": URI },
+            "position": { "line": line, "character": character },
+        }))
+    }
+
+    fn shutdown(mut self, handle: thread::JoinHandle<()>) {
+        assert_eq!(self.request("shutdown", Value::Null), Value::Null);
+        self.notify("exit", Value::Null);
+        handle.join().unwrap();
+    }
+}
+
+fn range(start: (u32, u32), end: (u32, u32)) -> Value {
+    json!({ "start": { "line": start.0, "character": start.1 }, "end": { "line": end.0, "character": end.1 } })
+}
+
+#[test]
+fn line_index() {
+    let index = LineIndex::new("a = \"あ𝄞\";\nb = 1;".to_owned());
+    assert_eq!(index.position(0), Position::new(0, 0));
+    assert_eq!(index.position(8), Position::new(0, 6));
+    assert_eq!(index.position(12), Position::new(0, 8));
+    assert_eq!(index.position(16), Position::new(1, 1));
+    assert_eq!(index.position(100), Position::new(1, 6));
+    assert_eq!(index.offset(Position::new(0, 8)), 12);
+    assert_eq!(index.offset(Position::new(1, 1)), 16);
+    assert_eq!(index.offset(Position::new(0, 50)), 14);
+    assert_eq!(index.offset(Position::new(5, 0)), 21);
+    assert_eq!(index.range(Span::new(15, 16)), lsp_types::Range::new(Position::new(1, 0), Position::new(1, 1)));
+}
+
+#[test]
+fn semantic_tokens() {
+    let index = LineIndex::new("f = \"あ\n\"; // c\n".to_owned());
+    let highlights = akanec::highlighter::highlight(&index.text).unwrap();
+    let data = index.semantic_tokens(&highlights).into_iter()
+        .map(|token| (token.delta_line, token.delta_start, token.length, token.token_type, token.token_modifiers_bitset))
+        .collect::<Vec<_>>();
+    assert_eq!(data, vec![(0, 0, 1, 0, 1), (0, 2, 1, 5, 0), (0, 2, 2, 7, 0), (1, 0, 1, 7, 0), (0, 3, 4, 8, 0)]);
+}
+
+#[test]
+fn scripted_session() {
+    let (mut client, handle) = Client::start();
+
+    client.open("double x = x + x;\npub f y = double y + z;\n");
+    let diagnostics = client.next_diagnostics();
+    assert_eq!(diagnostics.as_array().unwrap().len(), 1);
+    assert_eq!(diagnostics[0]["message"], json!("Unknown identifier: z"));
+    assert_eq!(diagnostics[0]["range"], range((1, 21), (1, 22)));
+
+    client.change(2, "double x = x + x;\nf y = double y +;\n");
+    let diagnostics = client.next_diagnostics();
+    assert_eq!(diagnostics[0]["message"], json!("Term required."));
+    assert_eq!(diagnostics[0]["range"], range((1, 16), (1, 17)));
+
+    let completion = client.at("textDocument/completion", 1, 16);
+    let labels = completion.as_array().unwrap().iter().map(|item| item["label"].as_str().unwrap()).collect::<Vec<_>>();
+    assert!(labels.contains(&"double") && labels.contains(&"map"));
+
+    client.change(3, "double : i32 -> i32;\ndouble x = x + x;\npub f y = double y;\n");
+    assert_eq!(client.next_diagnostics(), json!([]));
+
+    let definition = client.at("textDocument/definition", 2, 12);
+    assert_eq!(definition, json!({ "uri": URI, "range": range((1, 0), (1, 6)) }));
+    assert_eq!(client.at("textDocument/definition", 2, 9), Value::Null);
+
+    let hover = client.at("textDocument/hover", 2, 17);
+    assert_eq!(hover["contents"]["value"], json!("```akane\ny : i32\n```\nParameter."));
+    assert_eq!(hover["range"], range((2, 17), (2, 18)));
+
+    let symbols = client.request("textDocument/documentSymbol", json!({ "textDocument": { "uri": URI } }));
+    let symbols = symbols.as_array().unwrap();
+    assert_eq!(symbols.len(), 2);
+    assert_eq!((&symbols[0]["name"], &symbols[0]["detail"]), (&json!("double"), &json!("double : i32 -> i32")));
+    assert_eq!(symbols[0]["range"], range((0, 0), (1, 17)));
+    assert_eq!(symbols[1]["selectionRange"], range((2, 4), (2, 5)));
+
+    let tokens = client.request("textDocument/semanticTokens/full", json!({ "textDocument": { "uri": URI } }));
+    assert_eq!(&tokens["data"].as_array().unwrap()[..10], &[0, 0, 6, 0, 1, 0, 7, 1, 5, 0].map(|n| json!(n)));
+
+    client.notify("textDocument/didClose", json!({ "textDocument": { "uri": URI } }));
+    assert_eq!(client.next_diagnostics(), json!([]));
+    client.shutdown(handle);
+}
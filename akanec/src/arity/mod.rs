@@ -0,0 +1,165 @@
+#[cfg(test)]
+mod tests;
+
+use anyhow::Result;
+use crate::{
+    data::{
+        ast::{
+            ModuleAst,
+            FnDefAst,
+            ExprAst,
+            IdentAst,
+        },
+        builtins::builtin_arity,
+        diagnostic::{
+            Diagnostic,
+            Diagnostics,
+        },
+        span::Span,
+        ty::{
+            Ty,
+            FnTy,
+        },
+    },
+    resolver::{
+        Def,
+        SymbolTable,
+    },
+};
+
+/// How many arguments a function takes, and what it returns once applied to them,
+/// `None` if the annotation is invalid and codegen will reject it anyway.
+struct Signature {
+    arity: usize,
+    ret: Option<Ty>,
+}
+
+impl Signature {
+    fn of(fn_ty: FnTy) -> Self {
+        Self { arity: fn_ty.args.len(), ret: Some(fn_ty.ret.as_ref().clone()) }
+    }
+}
+
+/// Fails with every wrong count of arguments if there are any.
+pub fn check(module_ast: &ModuleAst, symbol_table: &SymbolTable) -> Result<()> {
+    Diagnostics::check(&diagnose(module_ast, symbol_table))
+}
+
+/// Reports applications to too few or too many arguments, since functions are applied in full.
+/// Calls to undefined functions are left to the resolver, which reports them along with the other unbound names.
+pub fn diagnose(module_ast: &ModuleAst, symbol_table: &SymbolTable) -> Vec<Diagnostic> {
+    let mut checker = Checker { module_ast, symbol_table, diagnostics: Vec::new() };
+    for (i, fn_def_ast) in module_ast.fn_defs.iter().enumerate() {
+        checker.check_expr(i, fn_def_ast, &fn_def_ast.expr);
+    }
+    checker.diagnostics
+}
+
+struct Checker<'a> {
+    module_ast: &'a ModuleAst,
+    symbol_table: &'a SymbolTable,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Checker<'_> {
+    fn check_expr(&mut self, index: usize, fn_def_ast: &FnDefAst, expr_ast: &ExprAst) {
+        match expr_ast {
+            ExprAst::Fn(fn_ast) => {
+                let mut arg_exprs = vec![fn_ast.arg_expr.as_ref()];
+                let mut fn_expr = fn_ast.fn_expr.as_ref();
+                while let ExprAst::Fn(fn_ast) = fn_expr {
+                    arg_exprs.push(fn_ast.arg_expr.as_ref());
+                    fn_expr = fn_ast.fn_expr.as_ref();
+                }
+                match fn_expr {
+                    ExprAst::Ident(ident_ast) => self.check_call(index, fn_def_ast, ident_ast, arg_exprs.len(), fn_ast.span),
+                    fn_expr => self.check_expr(index, fn_def_ast, fn_expr),
+                }
+                for arg_expr in arg_exprs.into_iter().rev() {
+                    self.check_expr(index, fn_def_ast, arg_expr);
+                }
+            },
+            ExprAst::PrefixOp(prefix_op_ast) =>
+                self.check_expr(index, fn_def_ast, &prefix_op_ast.rhs),
+            ExprAst::InfixOp(infix_op_ast) => {
+                self.check_expr(index, fn_def_ast, &infix_op_ast.lhs);
+                self.check_expr(index, fn_def_ast, &infix_op_ast.rhs);
+            },
+            ExprAst::List(list_ast) =>
+                for elem in &list_ast.elems {
+                    self.check_expr(index, fn_def_ast, elem);
+                },
+            ExprAst::Ident(_) | ExprAst::Num(_) | ExprAst::Str(_) | ExprAst::Char(_) => (),
+        }
+    }
+
+    /// A function returning a function can be applied to the arguments of both at once.
+    fn check_call(&mut self, index: usize, fn_def_ast: &FnDefAst, ident_ast: &IdentAst, arg_count: usize, span: Span) {
+        let Some(mut signature) = self.signature(index, fn_def_ast, &ident_ast.name) else {
+            return;
+        };
+        let mut expected = 0;
+        loop {
+            expected += signature.arity;
+            if arg_count < expected {
+                break;
+            }
+            match signature.ret {
+                _ if arg_count == expected => return,
+                Some(Ty::Fn(fn_ty)) => signature = Signature::of(fn_ty),
+                Some(_) => break,
+                None => return,
+            }
+        }
+        let message = format!("{} takes {} but {} given.", ident_ast.name, count(expected), were(arg_count));
+        self.diagnostics.push(Diagnostic::error(message, span));
+    }
+
+    fn signature(&self, index: usize, fn_def_ast: &FnDefAst, name: &str) -> Option<Signature> {
+        let signature = match self.symbol_table.lookup(index, fn_def_ast, name)? {
+            Def::Fn(i) => {
+                let fn_def_ast = &self.module_ast.fn_defs[i];
+                let arity = fn_def_ast.left_def.args.len();
+                match FnTy::from_ty_annot(fn_def_ast.ty_annot.as_ref(), arity) {
+                    Ok(fn_ty) => Signature::of(fn_ty),
+                    Err(_) => Signature { arity, ret: None },
+                }
+            },
+            Def::ExternFn(i) => Signature::of(FnTy::from_ty_ast(&self.module_ast.extern_fns[i].ty).ok()?),
+            Def::Param(_, i) => {
+                let fn_ty = FnTy::from_ty_annot(fn_def_ast.ty_annot.as_ref(), fn_def_ast.left_def.args.len()).ok()?;
+                match &fn_ty.args[i] {
+                    Ty::Fn(fn_ty) => Signature::of(fn_ty.clone()),
+                    ty => Signature { arity: 0, ret: Some(ty.clone()) },
+                }
+            },
+            Def::Imported(_) => return None,
+            Def::Builtin => Signature { arity: builtin_arity(name)?, ret: Some(builtin_ret(name)) },
+        };
+        Some(signature)
+    }
+}
+
+fn builtin_ret(name: &str) -> Ty {
+    match name {
+        "map" | "filter" => Ty::List,
+        "c_str" => Ty::Ptr,
+        _ => Ty::I32,
+    }
+}
+
+/// How many arguments `n` are, as messages and hovers word it.
+pub fn count(n: usize) -> String {
+    match n {
+        0 => "no arguments".to_owned(),
+        1 => "1 argument".to_owned(),
+        n => format!("{} arguments", n),
+    }
+}
+
+fn were(n: usize) -> String {
+    match n {
+        1 => "1 was".to_owned(),
+        n => format!("{} were", n),
+    }
+}
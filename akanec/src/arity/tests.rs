@@ -0,0 +1,71 @@
+use crate::{
+    data::diagnostic::Severity,
+    lexer,
+    parser,
+    resolver,
+};
+
+fn diagnostics(code: &str) -> Vec<(Severity, String, usize, usize)> {
+    let module_ast = parser::parse(lexer::lex(code.to_owned()).unwrap()).unwrap();
+    let symbol_table = resolver::resolve(&module_ast);
+    super::diagnose(&module_ast, &symbol_table).into_iter()
+        .map(|diagnostic| (diagnostic.severity, diagnostic.message, diagnostic.span.start, diagnostic.span.end))
+        .collect()
+}
+
+fn messages(code: &str) -> Vec<String> {
+    diagnostics(code).into_iter().map(|(_, message, _, _)| message).collect::<Vec<_>>()
+}
+
+#[test]
+fn arity_calls() {
+    assert_eq!(
+        diagnostics("add a b = a + b;\nf x = add x + add x x x;"),
+        vec![
+            (Severity::Error, "add takes 2 arguments but 1 was given.".to_owned(), 23, 28),
+            (Severity::Error, "add takes 2 arguments but 3 were given.".to_owned(), 31, 40),
+        ]
+    );
+    assert_eq!(messages("add a b = a + b;\nf x = add x (add x);"), vec!["add takes 2 arguments but 1 was given."]);
+    assert_eq!(messages("g = 1;\nf x = g x;"), vec!["g takes no arguments but 1 was given."]);
+    assert_eq!(messages("add a b = a + b;\nf xs = map add xs + length xs xs;"), vec!["length takes 1 argument but 2 were given."]);
+    assert_eq!(messages("extern \"C\" abs : i32 -> i32;\nf = abs;\ng x = abs x x;"), vec!["abs takes 1 argument but 2 were given."]);
+    assert_eq!(messages("add a b = a + b;\nf x = add x 1;\ng x = (add x) 1;"), Vec::<String>::new());
+}
+
+#[test]
+fn arity_fn_values() {
+    let code = "\
+adder : i32 -> i32 -> i32 -> i32;
+adder n = add n;
+add a b c = a + b + c;
+apply : (i32 -> i32) -> i32 -> i32;
+apply g x = g x;
+negate x = -x;
+f x = adder 1 2 3 + adder 1 2 + apply negate x + x 1;
+";
+    assert_eq!(
+        messages(code),
+        vec![
+            "add takes 3 arguments but 1 was given.",
+            "adder takes 3 arguments but 2 were given.",
+            "x takes no arguments but 1 was given.",
+        ]
+    );
+}
+
+#[test]
+fn arity_undefined() {
+    assert_eq!(messages("f x = g x 1;"), Vec::<String>::new());
+    let module_ast = parser::parse(lexer::lex("f x = g x 1;".to_owned()).unwrap()).unwrap();
+    assert_eq!(resolver::check(&module_ast).unwrap_err().to_string(), "Undefined function: g");
+}
+
+#[test]
+fn check_errors() {
+    let module_ast = parser::parse(lexer::lex("add a b = a + b;\nf x y = add x;".to_owned()).unwrap()).unwrap();
+    let symbol_table = resolver::check(&module_ast).unwrap();
+    assert_eq!(super::check(&module_ast, &symbol_table).unwrap_err().to_string(), "add takes 2 arguments but 1 was given.");
+    let module_ast = parser::parse(lexer::lex("f x y = x;".to_owned()).unwrap()).unwrap();
+    assert!(super::check(&module_ast, &resolver::resolve(&module_ast)).is_ok());
+}
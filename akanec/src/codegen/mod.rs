@@ -1,57 +1,210 @@
+mod runtime;
+
+use std::rc::Rc;
 use anyhow::{
     bail,
     Result,
 };
-use llvm_sys::prelude::LLVMValueRef;
+use llvm_sys::{
+    prelude::{
+        LLVMTypeRef,
+        LLVMValueRef,
+    },
+    LLVMIntPredicate,
+    LLVMLinkage,
+};
 use crate::{
-    data::ast::{
-        FnDefAst,
-        LeftDefAst,
-        ExprAst,
-        FnAst,
-        PrefixOpAst,
-        InfixOpAst,
-        IdentAst,
-        NumAst,
+    data::{
+        span::Span,
+        ast::{
+            ModuleAst,
+            ExternFnAst,
+            FnDefAst,
+            LeftDefAst,
+            ExprAst,
+            FnAst,
+            PrefixOpAst,
+            InfixOpAst,
+            IdentAst,
+            NumAst,
+            StrAst,
+            CharAst,
+            ListAst,
+            TyAst,
+        },
+        ty::{
+            Ty,
+            FnTy,
+        },
+        value::{
+            num_value,
+            neg_num_value,
+        },
     },
     llvm::LLVM,
 };
 
-pub fn compile(llvm: &mut LLVM, fn_def_asts: &Vec<FnDefAst>) -> Result<()> {
-    for fn_def_ast in fn_def_asts {
+const ENTRY: &str = "main";
+
+pub fn compile(llvm: &mut LLVM, module_ast: &ModuleAst) -> Result<()> {
+    for extern_fn_ast in &module_ast.extern_fns {
+        gen_extern_fn(llvm, extern_fn_ast)?;
+    }
+    for fn_def_ast in &module_ast.fn_defs {
+        gen_left_def(llvm, fn_def_ast.ty_annot.as_ref(), &fn_def_ast.left_def)?;
+    }
+    for fn_def_ast in &module_ast.fn_defs {
         gen_fn_def(llvm, fn_def_ast)?;
     }
+    if module_ast.fn_defs.iter().any(|fn_def_ast| fn_def_ast.left_def.ident.name == ENTRY) {
+        gen_entry(llvm)?;
+    }
+    Ok(())
+}
+
+/// Declares every function `llvm` has the type of in its current module,
+/// for a module calling the functions compiled into the ones before it.
+pub fn declare_fns(llvm: &mut LLVM) -> Result<()> {
+    let mut fn_tys = llvm.fn_tys().iter().map(|(name, fn_ty)| (name.clone(), fn_ty.clone())).collect::<Vec<_>>();
+    fn_tys.sort_by(|(lhs, _), (rhs, _)| lhs.cmp(rhs));
+    for (name, fn_ty) in fn_tys {
+        let f_ty = gen_fn_ty(llvm, &fn_ty)?;
+        llvm.add_function(symbol_name(&name), f_ty)?;
+    }
+    Ok(())
+}
+
+pub fn infer_ty(llvm: &mut LLVM, expr_ast: &ExprAst) -> Result<Ty> {
+    infer_ty_in(llvm, &[], &[], expr_ast)
+}
+
+/// Infers the type of an expression appearing in the body of a function already compiled into `llvm`.
+pub fn infer_fn_expr_ty(llvm: &mut LLVM, fn_def_ast: &FnDefAst, expr_ast: &ExprAst) -> Result<Ty> {
+    let fn_ty = llvm.get_fn_ty(&fn_def_ast.left_def.ident.name)?;
+    infer_ty_in(llvm, &fn_def_ast.left_def.args, &fn_ty.args, expr_ast)
+}
+
+/// Generates the expression into a temporary function, then deletes it
+/// along with the helpers and globals generating it added, leaving the module as it was.
+fn infer_ty_in(llvm: &mut LLVM, args: &[IdentAst], arg_tys: &[Ty], expr_ast: &ExprAst) -> Result<Ty> {
+    let functions = llvm.functions();
+    let globals = llvm.globals();
+    let void_ty = llvm.void_type()?;
+    let mut param_tys = Vec::new();
+    for arg_ty in arg_tys {
+        param_tys.push(gen_ty(llvm, arg_ty)?);
+    }
+    let f_ty = llvm.function_type(void_ty, param_tys)?;
+    let f = llvm.add_function("akane.infer", f_ty)?;
+    let block = llvm.append_basic_block(f, "entry")?;
+    llvm.position_builder_at_end(block);
+    llvm.clear_named_value();
+    let result = bind_args(llvm, f, args, arg_tys).and_then(|_| gen_expr(llvm, expr_ast));
+    delete_added(llvm, &functions, &globals)?;
+    result.map(|(_, ty)| ty)
+}
+
+/// Deletes the functions and globals not among `functions` and `globals`.
+/// Their uses are replaced first, since they may refer to each other.
+fn delete_added(llvm: &LLVM, functions: &[LLVMValueRef], globals: &[LLVMValueRef]) -> Result<()> {
+    let added_functions = llvm.functions().into_iter().filter(|f| !functions.contains(f)).collect::<Vec<_>>();
+    let added_globals = llvm.globals().into_iter().filter(|global| !globals.contains(global)).collect::<Vec<_>>();
+    for value in added_functions.iter().chain(&added_globals) {
+        let undef = LLVM::get_undef(LLVM::type_of(*value)?)?;
+        LLVM::replace_all_uses_with(*value, undef);
+    }
+    for f in added_functions {
+        LLVM::delete_function(f);
+    }
+    for global in added_globals {
+        LLVM::delete_global(global);
+    }
+    Ok(())
+}
+
+fn bind_args(llvm: &mut LLVM, f: LLVMValueRef, args: &[IdentAst], arg_tys: &[Ty]) -> Result<()> {
+    for (i, (arg, arg_ty)) in args.iter().zip(arg_tys).enumerate() {
+        let param = LLVM::get_param(f, i)?;
+        llvm.insert_named_value(arg.name.clone(), param, arg_ty.clone())?;
+    }
     Ok(())
 }
 
+pub fn compile_expr(llvm: &mut LLVM, name: &str, expr_ast: &ExprAst) -> Result<Ty> {
+    let ty = infer_ty(llvm, expr_ast)?;
+    let fn_ty = FnTy { args: Vec::new(), ret: Rc::new(ty.clone()) };
+    let f_ty = gen_fn_ty(llvm, &fn_ty)?;
+    llvm.insert_fn_ty(name.to_owned(), fn_ty)?;
+    llvm.add_function(symbol_name(name), f_ty)?;
+    let fn_def_ast = FnDefAst {
+        attrs: Vec::new(),
+        is_pub: true,
+        ty_annot: None,
+        left_def: LeftDefAst { ident: IdentAst { name: name.to_owned(), span: Span::default() }, args: Vec::new(), span: Span::default() },
+        expr: expr_ast.clone(),
+        span: expr_ast.span(),
+    };
+    gen_fn_def(llvm, &fn_def_ast)?;
+    Ok(ty)
+}
+
+fn gen_extern_fn(llvm: &mut LLVM, extern_fn_ast: &ExternFnAst) -> Result<LLVMValueRef> {
+    let name = &extern_fn_ast.ident.name;
+    let fn_ty = FnTy::from_ty_ast(&extern_fn_ast.ty)?;
+    if let Ok(defined) = llvm.get_fn_ty(name) {
+        let f = llvm.get_named_function(name)?;
+        if defined != fn_ty || LLVM::count_basic_blocks(f) != 0 {
+            bail!("Function cannot be redefined.");
+        }
+        return Ok(f);
+    }
+    let f_ty = gen_fn_ty(llvm, &fn_ty)?;
+    llvm.insert_fn_ty(name.clone(), fn_ty)?;
+    llvm.add_function(name, f_ty)
+}
+
 fn gen_fn_def(llvm: &mut LLVM, fn_def_ast: &FnDefAst) -> Result<LLVMValueRef> {
-    let f = match llvm.get_named_function(&fn_def_ast.left_def.ident.name) {
+    let f = match llvm.get_named_function(symbol_name(&fn_def_ast.left_def.ident.name)) {
         Ok(f) => f,
-        Err(_) => gen_left_def(llvm, &fn_def_ast.left_def)?,
+        Err(_) => gen_left_def(llvm, fn_def_ast.ty_annot.as_ref(), &fn_def_ast.left_def)?,
     };
     let block_count = LLVM::count_basic_blocks(f);
     if block_count != 0 {
         bail!("Function cannot be redefined.");
     }
+    let fn_ty = llvm.get_fn_ty(&fn_def_ast.left_def.ident.name)?;
     let block = llvm.append_basic_block(f, "entry")?;
     llvm.position_builder_at_end(block);
+    if !fn_def_ast.is_pub || fn_def_ast.left_def.ident.name == ENTRY {
+        LLVM::set_linkage(f, LLVMLinkage::LLVMInternalLinkage);
+    }
     llvm.clear_named_value();
     let arg_count = fn_def_ast.left_def.args.len();
     for i in 0..arg_count {
         let arg = LLVM::get_param(f, i)?;
         llvm.set_value_name(arg, &fn_def_ast.left_def.args[i].name);
-        llvm.insert_named_value(fn_def_ast.left_def.args[i].name.clone(), arg)?;
+        llvm.insert_named_value(fn_def_ast.left_def.args[i].name.clone(), arg, fn_ty.args[i].clone())?;
     }
     match gen_expr(llvm, &fn_def_ast.expr) {
-        Ok(body) => {
-            llvm.build_ret(body)?;
-            if LLVM::verify_function(f) {
-                Ok(f)
+        Ok((body, ty)) => {
+            if ty != *fn_ty.ret {
+                LLVM::delete_function(f);
+                bail!("Type mismatch.");
             }
-            else {
+            llvm.build_ret(body)?;
+            if !LLVM::verify_function(f) {
+                let name = &fn_def_ast.left_def.ident.name;
+                let message = match llvm.verify_module() {
+                    Err(e) => format!("Invalid function {}: {}", name, e),
+                    Ok(()) => format!("Invalid function {}.", name),
+                };
                 LLVM::delete_function(f);
-                bail!("Invalid function.");
+                bail!(message);
             }
+            if llvm.verify_each() {
+                llvm.verify_module()?;
+            }
+            Ok(f)
         },
         Err(e) => {
             LLVM::delete_function(f);
@@ -60,19 +213,51 @@ fn gen_fn_def(llvm: &mut LLVM, fn_def_ast: &FnDefAst) -> Result<LLVMValueRef> {
     }
 }
 
-fn gen_left_def(llvm: &mut LLVM, left_def_ast: &LeftDefAst) -> Result<LLVMValueRef> {
-    let arg_count = left_def_ast.args.len();
-    let int_ty = llvm.int32_type()?;
-    let arg_tys = vec![int_ty; arg_count];
-    let f_ty = llvm.function_type(int_ty, arg_tys)?;
-    let f = llvm.add_function(&left_def_ast.ident.name, f_ty)?;
+fn gen_entry(llvm: &mut LLVM) -> Result<LLVMValueRef> {
+    let fn_ty = llvm.get_fn_ty(ENTRY)?;
+    if !fn_ty.args.is_empty() || *fn_ty.ret != Ty::I32 {
+        bail!("main must have type i32.");
+    }
+    let f = llvm.get_named_function(symbol_name(ENTRY))?;
+    let stub = runtime::gen_entry_stub(llvm, f)?;
+    if llvm.verify_each() {
+        llvm.verify_module()?;
+    }
+    Ok(stub)
+}
+
+fn gen_left_def(llvm: &mut LLVM, ty_annot: Option<&TyAst>, left_def_ast: &LeftDefAst) -> Result<LLVMValueRef> {
+    let fn_ty = FnTy::from_ty_annot(ty_annot, left_def_ast.args.len())?;
+    let f_ty = gen_fn_ty(llvm, &fn_ty)?;
+    llvm.insert_fn_ty(left_def_ast.ident.name.clone(), fn_ty)?;
+    let f = llvm.add_function(symbol_name(&left_def_ast.ident.name), f_ty)?;
     if f.is_null() {
         bail!("Cannot create function.");
     }
     Ok(f)
 }
 
-fn gen_expr(llvm: &mut LLVM, expr_ast: &ExprAst) -> Result<LLVMValueRef> {
+fn gen_ty(llvm: &mut LLVM, ty: &Ty) -> Result<LLVMTypeRef> {
+    match ty {
+        Ty::I32 =>
+            llvm.int32_type(),
+        Ty::Ptr | Ty::Str | Ty::List =>
+            LLVM::pointer_type(llvm.int8_type()?),
+        Ty::Fn(fn_ty) =>
+            LLVM::pointer_type(gen_fn_ty(llvm, fn_ty)?),
+    }
+}
+
+fn gen_fn_ty(llvm: &mut LLVM, fn_ty: &FnTy) -> Result<LLVMTypeRef> {
+    let ret_ty = gen_ty(llvm, &fn_ty.ret)?;
+    let mut arg_tys = Vec::new();
+    for arg in &fn_ty.args {
+        arg_tys.push(gen_ty(llvm, arg)?);
+    }
+    llvm.function_type(ret_ty, arg_tys)
+}
+
+fn gen_expr(llvm: &mut LLVM, expr_ast: &ExprAst) -> Result<(LLVMValueRef, Ty)> {
     match expr_ast {
         ExprAst::Fn(fn_ast) =>
             gen_fn(llvm, fn_ast),
@@ -84,48 +269,212 @@ fn gen_expr(llvm: &mut LLVM, expr_ast: &ExprAst) -> Result<LLVMValueRef> {
             gen_ident(llvm, ident_ast),
         ExprAst::Num(num_ast) =>
             gen_num(llvm, num_ast),
+        ExprAst::Str(str_ast) =>
+            gen_str(llvm, str_ast),
+        ExprAst::Char(char_ast) =>
+            gen_char(llvm, char_ast),
+        ExprAst::List(list_ast) =>
+            gen_list(llvm, list_ast),
     }
 }
 
-fn gen_fn(llvm: &mut LLVM, fn_ast: &FnAst) -> Result<LLVMValueRef> {
-    match fn_ast.fn_expr.as_ref() {
-        ExprAst::Ident(IdentAst { name }) => {
-            let f = llvm.get_named_function(name)?;
-            if f.is_null() {
-                bail!("Unknown function");
-            }
-            let arg_count = LLVM::count_params(f);
-            if arg_count != 1 {
-                bail!("Invalid count of arguments.");
+fn gen_fn(llvm: &mut LLVM, fn_ast: &FnAst) -> Result<(LLVMValueRef, Ty)> {
+    let mut arg_exprs = vec![fn_ast.arg_expr.as_ref()];
+    let mut fn_expr = fn_ast.fn_expr.as_ref();
+    while let ExprAst::Fn(fn_ast) = fn_expr {
+        arg_exprs.push(fn_ast.arg_expr.as_ref());
+        fn_expr = fn_ast.fn_expr.as_ref();
+    }
+    arg_exprs.reverse();
+    let mut args = Vec::new();
+    for arg_expr in arg_exprs {
+        args.push(gen_expr(llvm, arg_expr)?);
+    }
+    if let ExprAst::Ident(IdentAst { name, .. }) = fn_expr {
+        if !is_defined(llvm, name) && runtime::is_builtin(name) {
+            return runtime::gen_builtin(llvm, name, args);
+        }
+    }
+    let (mut f, mut ty) = gen_expr(llvm, fn_expr)?;
+    let mut args = args.into_iter();
+    loop {
+        let fn_ty = match ty {
+            Ty::Fn(fn_ty) => fn_ty,
+            _ => bail!("Not a function."),
+        };
+        let arg_count = fn_ty.args.len();
+        if arg_count == 0 || args.len() < arg_count {
+            bail!("Invalid count of arguments.");
+        }
+        let mut arg_values = Vec::new();
+        for arg_ty in &fn_ty.args {
+            let (arg, ty) = args.next().unwrap();
+            if ty != *arg_ty {
+                bail!("Type mismatch.");
             }
-            let args = vec![gen_expr(llvm, fn_ast.arg_expr.as_ref())?];
-            let ty = LLVM::get_called_function_type(f)?;
-            Ok(llvm.build_call(ty, f, args, "calltmp")?)
-        },
-        _ => panic!("Not implemented."),
+            arg_values.push(arg);
+        }
+        let f_ty = gen_fn_ty(llvm, &fn_ty)?;
+        f = llvm.build_call(f_ty, f, arg_values, "calltmp")?;
+        ty = fn_ty.ret.as_ref().clone();
+        if args.len() == 0 {
+            return Ok((f, ty));
+        }
     }
 }
 
-fn gen_prefix_op(_llvm: &mut LLVM, _prefix_op_ast: &PrefixOpAst) -> Result<LLVMValueRef> {
-    panic!("Not implemented.")
+fn gen_prefix_op(llvm: &mut LLVM, prefix_op_ast: &PrefixOpAst) -> Result<(LLVMValueRef, Ty)> {
+    if let ("-", ExprAst::Num(num_ast)) = (prefix_op_ast.op_code.as_str(), prefix_op_ast.rhs.as_ref()) {
+        let value = neg_num_value(num_ast)?;
+        return Ok((llvm.const_int(value as u32 as u64, 0)?, Ty::I32));
+    }
+    let (rhs, rhs_ty) = gen_expr(llvm, prefix_op_ast.rhs.as_ref())?;
+    match (prefix_op_ast.op_code.as_str(), rhs_ty) {
+        ("-", Ty::I32) =>
+            Ok((llvm.build_neg(rhs, "negtmp")?, Ty::I32)),
+        ("-", _) =>
+            bail!("Type mismatch."),
+        _ => bail!("Invalid operator."),
+    }
 }
 
-fn gen_infix_op(llvm: &mut LLVM, infix_op_ast: &InfixOpAst) -> Result<LLVMValueRef> {
-    match infix_op_ast.op_code.as_str() {
-        "+" => {
-            let lhs = gen_expr(llvm, infix_op_ast.lhs.as_ref())?;
-            let rhs = gen_expr(llvm, infix_op_ast.rhs.as_ref())?;
-            Ok(llvm.build_add(lhs, rhs, "addtmp")?)
+fn gen_infix_op(llvm: &mut LLVM, infix_op_ast: &InfixOpAst) -> Result<(LLVMValueRef, Ty)> {
+    if let "&&" | "||" = infix_op_ast.op_code.as_str() {
+        return gen_logical_op(llvm, infix_op_ast);
+    }
+    let (lhs, lhs_ty) = gen_expr(llvm, infix_op_ast.lhs.as_ref())?;
+    let (rhs, rhs_ty) = gen_expr(llvm, infix_op_ast.rhs.as_ref())?;
+    match (infix_op_ast.op_code.as_str(), lhs_ty, rhs_ty) {
+        ("+", Ty::I32, Ty::I32) =>
+            Ok((llvm.build_add(lhs, rhs, "addtmp")?, Ty::I32)),
+        ("-", Ty::I32, Ty::I32) =>
+            Ok((llvm.build_sub(lhs, rhs, "subtmp")?, Ty::I32)),
+        ("*", Ty::I32, Ty::I32) =>
+            Ok((llvm.build_mul(lhs, rhs, "multmp")?, Ty::I32)),
+        ("/", Ty::I32, Ty::I32) =>
+            Ok((llvm.build_sdiv(lhs, rhs, "divtmp")?, Ty::I32)),
+        ("%", Ty::I32, Ty::I32) =>
+            Ok((llvm.build_srem(lhs, rhs, "remtmp")?, Ty::I32)),
+        ("==", Ty::I32, Ty::I32) =>
+            gen_icmp(llvm, LLVMIntPredicate::LLVMIntEQ, lhs, rhs),
+        ("!=", Ty::I32, Ty::I32) =>
+            gen_icmp(llvm, LLVMIntPredicate::LLVMIntNE, lhs, rhs),
+        ("<", Ty::I32, Ty::I32) =>
+            gen_icmp(llvm, LLVMIntPredicate::LLVMIntSLT, lhs, rhs),
+        ("<=", Ty::I32, Ty::I32) =>
+            gen_icmp(llvm, LLVMIntPredicate::LLVMIntSLE, lhs, rhs),
+        (">", Ty::I32, Ty::I32) =>
+            gen_icmp(llvm, LLVMIntPredicate::LLVMIntSGT, lhs, rhs),
+        (">=", Ty::I32, Ty::I32) =>
+            gen_icmp(llvm, LLVMIntPredicate::LLVMIntSGE, lhs, rhs),
+        ("==", Ty::Str, Ty::Str) => {
+            let eq = runtime::gen_str_eq(llvm, lhs, rhs)?;
+            Ok((eq, Ty::I32))
+        },
+        ("!=", Ty::Str, Ty::Str) => {
+            let eq = runtime::gen_str_eq(llvm, lhs, rhs)?;
+            let zero = llvm.const_int(0, 0)?;
+            gen_icmp(llvm, LLVMIntPredicate::LLVMIntEQ, eq, zero)
         },
+        ("++", Ty::Str, Ty::Str) =>
+            Ok((runtime::gen_str_concat(llvm, lhs, rhs)?, Ty::Str)),
+        ("++", Ty::List, Ty::List) =>
+            Ok((runtime::gen_list_concat(llvm, lhs, rhs)?, Ty::List)),
+        ("::", Ty::I32, Ty::List) =>
+            Ok((runtime::gen_list_cons(llvm, lhs, rhs)?, Ty::List)),
+        ("!!", Ty::List, Ty::I32) =>
+            Ok((runtime::gen_list_index(llvm, lhs, rhs)?, Ty::I32)),
+        ("+" | "-" | "*" | "/" | "%" | "==" | "!=" | "<" | "<=" | ">" | ">=" | "++" | "::" | "!!", _, _) =>
+            bail!("Type mismatch."),
         _ => bail!("Invalid operator."),
     }
 }
 
-fn gen_ident(llvm: &mut LLVM, ident_ast: &IdentAst) -> Result<LLVMValueRef> {
-    llvm.get_named_value(&ident_ast.name)
+fn gen_logical_op(llvm: &mut LLVM, infix_op_ast: &InfixOpAst) -> Result<(LLVMValueRef, Ty)> {
+    let (lhs, lhs_ty) = gen_expr(llvm, infix_op_ast.lhs.as_ref())?;
+    if lhs_ty != Ty::I32 {
+        bail!("Type mismatch.");
+    }
+    let zero = llvm.const_int(0, 0)?;
+    let (lhs, _) = gen_icmp(llvm, LLVMIntPredicate::LLVMIntNE, lhs, zero)?;
+    let lhs_block = llvm.get_insert_block().unwrap();
+    let f = LLVM::get_basic_block_parent(lhs_block)?;
+    let rhs_block = llvm.append_basic_block(f, "rhs")?;
+    let end_block = llvm.append_basic_block(f, "end")?;
+    let cond = llvm.build_icmp(LLVMIntPredicate::LLVMIntNE, lhs, zero, "cond")?;
+    if infix_op_ast.op_code == "&&" {
+        llvm.build_cond_br(cond, rhs_block, end_block)?;
+    }
+    else {
+        llvm.build_cond_br(cond, end_block, rhs_block)?;
+    }
+    llvm.position_builder_at_end(rhs_block);
+    let (rhs, rhs_ty) = gen_expr(llvm, infix_op_ast.rhs.as_ref())?;
+    if rhs_ty != Ty::I32 {
+        bail!("Type mismatch.");
+    }
+    let (rhs, _) = gen_icmp(llvm, LLVMIntPredicate::LLVMIntNE, rhs, zero)?;
+    let rhs_block = llvm.get_insert_block().unwrap();
+    llvm.build_br(end_block)?;
+    llvm.position_builder_at_end(end_block);
+    let int_ty = llvm.int32_type()?;
+    Ok((llvm.build_phi(int_ty, vec![(lhs, lhs_block), (rhs, rhs_block)], "logictmp")?, Ty::I32))
+}
+
+fn gen_icmp(llvm: &mut LLVM, op: LLVMIntPredicate, lhs: LLVMValueRef, rhs: LLVMValueRef) -> Result<(LLVMValueRef, Ty)> {
+    let cmp = llvm.build_icmp(op, lhs, rhs, "cmptmp")?;
+    let int_ty = llvm.int32_type()?;
+    Ok((llvm.build_zext(cmp, int_ty, "booltmp")?, Ty::I32))
+}
+
+fn gen_ident(llvm: &mut LLVM, ident_ast: &IdentAst) -> Result<(LLVMValueRef, Ty)> {
+    if let Ok(value) = llvm.get_named_value(&ident_ast.name) {
+        return Ok(value);
+    }
+    let fn_ty = llvm.get_fn_ty(&ident_ast.name)?;
+    let f = llvm.get_named_function(symbol_name(&ident_ast.name))?;
+    if fn_ty.args.is_empty() {
+        let f_ty = gen_fn_ty(llvm, &fn_ty)?;
+        Ok((llvm.build_call(f_ty, f, Vec::new(), "calltmp")?, fn_ty.ret.as_ref().clone()))
+    }
+    else {
+        Ok((f, Ty::Fn(fn_ty)))
+    }
+}
+
+fn gen_num(llvm: &mut LLVM, num_ast: &NumAst) -> Result<(LLVMValueRef, Ty)> {
+    let value = num_value(num_ast)?;
+    Ok((llvm.const_int(value as u64, 0)?, Ty::I32))
 }
 
-fn gen_num(llvm: &mut LLVM, num_ast: &NumAst) -> Result<LLVMValueRef> {
-    let value = num_ast.value.parse().unwrap();
-    Ok(llvm.const_int(value, 0)?)
+fn gen_str(llvm: &mut LLVM, str_ast: &StrAst) -> Result<(LLVMValueRef, Ty)> {
+    Ok((runtime::gen_str_literal(llvm, &str_ast.value)?, Ty::Str))
+}
+
+fn gen_char(llvm: &mut LLVM, char_ast: &CharAst) -> Result<(LLVMValueRef, Ty)> {
+    Ok((llvm.const_int(char_ast.value as u64, 0)?, Ty::I32))
+}
+
+fn gen_list(llvm: &mut LLVM, list_ast: &ListAst) -> Result<(LLVMValueRef, Ty)> {
+    let mut elems = Vec::new();
+    for elem in &list_ast.elems {
+        match gen_expr(llvm, elem)? {
+            (elem, Ty::I32) => elems.push(elem),
+            _ => bail!("Only lists of i32 are supported."),
+        }
+    }
+    Ok((runtime::gen_list_literal(llvm, elems)?, Ty::List))
+}
+
+fn is_defined(llvm: &LLVM, name: &str) -> bool {
+    llvm.get_named_value(name).is_ok() || llvm.get_fn_ty(name).is_ok()
+}
+
+pub fn symbol_name(name: &str) -> &str {
+    if name == ENTRY {
+        runtime::ENTRY_SYMBOL
+    }
+    else {
+        name
+    }
 }
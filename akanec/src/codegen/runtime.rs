@@ -0,0 +1,480 @@
+use std::rc::Rc;
+use anyhow::{
+    bail,
+    Result,
+};
+use llvm_sys::{
+    prelude::{
+        LLVMTypeRef,
+        LLVMValueRef,
+    },
+    LLVMIntPredicate,
+    LLVMLinkage,
+    LLVMUnnamedAddr,
+};
+use crate::{
+    data::ty::{
+        Ty,
+        FnTy,
+    },
+    llvm::LLVM,
+};
+
+// Strings and lists are pointers to `{ i64 len, [len x elem] data }`.
+// String data is UTF-8 and always followed by a NUL so that it can be handed to C as it is.
+// List elements are i32.
+const HEADER_SIZE: u64 = 8;
+const LIST_ELEM_SIZE: u64 = 4;
+
+// The Akane `main` is renamed so that the C `main` can be a stub calling it.
+pub const ENTRY_SYMBOL: &str = "akane.main";
+
+pub fn is_builtin(name: &str) -> bool {
+    ["length", "map", "filter", "fold", "c_str"].contains(&name)
+}
+
+pub fn gen_builtin(llvm: &mut LLVM, name: &str, args: Vec<(LLVMValueRef, Ty)>) -> Result<(LLVMValueRef, Ty)> {
+    let unary = Ty::Fn(FnTy { args: vec![Ty::I32], ret: Rc::new(Ty::I32) });
+    let binary = Ty::Fn(FnTy { args: vec![Ty::I32, Ty::I32], ret: Rc::new(Ty::I32) });
+    match (name, args.as_slice()) {
+        ("length", [(s, Ty::Str)]) =>
+            Ok((gen_length(llvm, *s)?, Ty::I32)),
+        ("length", [(xs, Ty::List)]) =>
+            Ok((gen_length(llvm, *xs)?, Ty::I32)),
+        ("map", [(f, f_ty), (xs, Ty::List)]) if *f_ty == unary =>
+            Ok((gen_list_map(llvm, *f, *xs)?, Ty::List)),
+        ("filter", [(f, f_ty), (xs, Ty::List)]) if *f_ty == unary =>
+            Ok((gen_list_filter(llvm, *f, *xs)?, Ty::List)),
+        ("fold", [(f, f_ty), (init, Ty::I32), (xs, Ty::List)]) if *f_ty == binary =>
+            Ok((gen_list_fold(llvm, *f, *init, *xs)?, Ty::I32)),
+        ("c_str", [(s, Ty::Str)]) =>
+            Ok((gen_data(llvm, *s)?, Ty::Ptr)),
+        ("length", [_]) | ("map", [_, _]) | ("filter", [_, _]) | ("fold", [_, _, _]) | ("c_str", [_]) =>
+            bail!("Type mismatch."),
+        _ => bail!("Invalid count of arguments."),
+    }
+}
+
+pub fn gen_entry_stub(llvm: &mut LLVM, entry: LLVMValueRef) -> Result<LLVMValueRef> {
+    let int_ty = llvm.int32_type()?;
+    let argv_ty = LLVM::pointer_type(ptr_ty(llvm)?)?;
+    let f_ty = llvm.function_type(int_ty, vec![int_ty, argv_ty])?;
+    let f = llvm.add_function("main", f_ty)?;
+    let block = llvm.append_basic_block(f, "entry")?;
+    llvm.position_builder_at_end(block);
+    let entry_ty = LLVM::global_get_value_type(entry)?;
+    let code = llvm.build_call(entry_ty, entry, Vec::new(), "exitcode")?;
+    llvm.build_ret(code)?;
+    Ok(f)
+}
+
+pub fn gen_str_literal(llvm: &mut LLVM, value: &str) -> Result<LLVMValueRef> {
+    let len = llvm.const_int64(value.len() as u64)?;
+    let bytes = llvm.const_string(value.as_bytes())?;
+    let init = llvm.const_struct(vec![len, bytes])?;
+    let global = llvm.add_global(LLVM::type_of(init)?, ".str")?;
+    LLVM::set_initializer(global, init);
+    LLVM::set_global_constant(global, true);
+    LLVM::set_linkage(global, LLVMLinkage::LLVMPrivateLinkage);
+    LLVM::set_unnamed_address(global, LLVMUnnamedAddr::LLVMGlobalUnnamedAddr);
+    LLVM::const_pointer_cast(global, ptr_ty(llvm)?)
+}
+
+pub fn gen_str_concat(llvm: &mut LLVM, lhs: LLVMValueRef, rhs: LLVMValueRef) -> Result<LLVMValueRef> {
+    gen_helper_call(llvm, "akane.str.concat", gen_str_concat_helper, vec![lhs, rhs])
+}
+
+pub fn gen_str_eq(llvm: &mut LLVM, lhs: LLVMValueRef, rhs: LLVMValueRef) -> Result<LLVMValueRef> {
+    gen_helper_call(llvm, "akane.str.eq", gen_str_eq_helper, vec![lhs, rhs])
+}
+
+pub fn gen_list_literal(llvm: &mut LLVM, elems: Vec<LLVMValueRef>) -> Result<LLVMValueRef> {
+    let len = llvm.const_int64(elems.len() as u64)?;
+    let xs = gen_list_alloc(llvm, len)?;
+    for (i, elem) in elems.into_iter().enumerate() {
+        let index = llvm.const_int64(i as u64)?;
+        let elem_ptr = gen_list_elem_ptr(llvm, xs, index)?;
+        llvm.build_store(elem, elem_ptr)?;
+    }
+    Ok(xs)
+}
+
+pub fn gen_list_cons(llvm: &mut LLVM, x: LLVMValueRef, xs: LLVMValueRef) -> Result<LLVMValueRef> {
+    gen_helper_call(llvm, "akane.list.cons", gen_list_cons_helper, vec![x, xs])
+}
+
+pub fn gen_list_concat(llvm: &mut LLVM, lhs: LLVMValueRef, rhs: LLVMValueRef) -> Result<LLVMValueRef> {
+    gen_helper_call(llvm, "akane.list.concat", gen_list_concat_helper, vec![lhs, rhs])
+}
+
+pub fn gen_list_index(llvm: &mut LLVM, xs: LLVMValueRef, index: LLVMValueRef) -> Result<LLVMValueRef> {
+    gen_helper_call(llvm, "akane.list.index", gen_list_index_helper, vec![xs, index])
+}
+
+pub fn gen_list_map(llvm: &mut LLVM, f: LLVMValueRef, xs: LLVMValueRef) -> Result<LLVMValueRef> {
+    gen_helper_call(llvm, "akane.list.map", gen_list_map_helper, vec![f, xs])
+}
+
+pub fn gen_list_filter(llvm: &mut LLVM, f: LLVMValueRef, xs: LLVMValueRef) -> Result<LLVMValueRef> {
+    gen_helper_call(llvm, "akane.list.filter", gen_list_filter_helper, vec![f, xs])
+}
+
+pub fn gen_list_fold(llvm: &mut LLVM, f: LLVMValueRef, init: LLVMValueRef, xs: LLVMValueRef) -> Result<LLVMValueRef> {
+    gen_helper_call(llvm, "akane.list.fold", gen_list_fold_helper, vec![f, init, xs])
+}
+
+fn gen_length(llvm: &mut LLVM, s: LLVMValueRef) -> Result<LLVMValueRef> {
+    let len = gen_len(llvm, s)?;
+    let int_ty = llvm.int32_type()?;
+    llvm.build_trunc(len, int_ty, "lentmp")
+}
+
+fn gen_str_concat_helper(llvm: &mut LLVM, name: &str) -> Result<LLVMValueRef> {
+    let ptr_ty = ptr_ty(llvm)?;
+    let f = add_helper(llvm, name, ptr_ty, vec![ptr_ty, ptr_ty])?;
+    let lhs = LLVM::get_param(f, 0)?;
+    let rhs = LLVM::get_param(f, 1)?;
+    let lhs_len = gen_len(llvm, lhs)?;
+    let rhs_len = gen_len(llvm, rhs)?;
+    let len = llvm.build_add(lhs_len, rhs_len, "len")?;
+    let extra = llvm.const_int64(HEADER_SIZE + 1)?;
+    let size = llvm.build_add(len, extra, "size")?;
+    let s = gen_malloc(llvm, size)?;
+    gen_set_len(llvm, s, len)?;
+    let data = gen_data(llvm, s)?;
+    let lhs_data = gen_data(llvm, lhs)?;
+    let rhs_data = gen_data(llvm, rhs)?;
+    gen_memcpy(llvm, data, lhs_data, lhs_len)?;
+    let rest = gen_byte_offset(llvm, data, lhs_len)?;
+    gen_memcpy(llvm, rest, rhs_data, rhs_len)?;
+    let end = gen_byte_offset(llvm, data, len)?;
+    let nul = llvm.const_int8(0)?;
+    llvm.build_store(nul, end)?;
+    llvm.build_ret(s)?;
+    Ok(f)
+}
+
+fn gen_str_eq_helper(llvm: &mut LLVM, name: &str) -> Result<LLVMValueRef> {
+    let ptr_ty = ptr_ty(llvm)?;
+    let int_ty = llvm.int32_type()?;
+    let f = add_helper(llvm, name, int_ty, vec![ptr_ty, ptr_ty])?;
+    let lhs = LLVM::get_param(f, 0)?;
+    let rhs = LLVM::get_param(f, 1)?;
+    let entry = llvm.get_insert_block().unwrap();
+    let cmp_block = llvm.append_basic_block(f, "cmp")?;
+    let end_block = llvm.append_basic_block(f, "end")?;
+    let lhs_len = gen_len(llvm, lhs)?;
+    let rhs_len = gen_len(llvm, rhs)?;
+    let len_eq = llvm.build_icmp(LLVMIntPredicate::LLVMIntEQ, lhs_len, rhs_len, "leneq")?;
+    llvm.build_cond_br(len_eq, cmp_block, end_block)?;
+    llvm.position_builder_at_end(cmp_block);
+    let lhs_data = gen_data(llvm, lhs)?;
+    let rhs_data = gen_data(llvm, rhs)?;
+    let order = gen_memcmp(llvm, lhs_data, rhs_data, lhs_len)?;
+    let zero = llvm.const_int(0, 0)?;
+    let data_eq = llvm.build_icmp(LLVMIntPredicate::LLVMIntEQ, order, zero, "dataeq")?;
+    let data_eq = llvm.build_zext(data_eq, int_ty, "dataeq")?;
+    llvm.build_br(end_block)?;
+    llvm.position_builder_at_end(end_block);
+    let eq = llvm.build_phi(int_ty, vec![(zero, entry), (data_eq, cmp_block)], "eq")?;
+    llvm.build_ret(eq)?;
+    Ok(f)
+}
+
+fn gen_list_cons_helper(llvm: &mut LLVM, name: &str) -> Result<LLVMValueRef> {
+    let ptr_ty = ptr_ty(llvm)?;
+    let int_ty = llvm.int32_type()?;
+    let f = add_helper(llvm, name, ptr_ty, vec![int_ty, ptr_ty])?;
+    let x = LLVM::get_param(f, 0)?;
+    let xs = LLVM::get_param(f, 1)?;
+    let xs_len = gen_len(llvm, xs)?;
+    let one = llvm.const_int64(1)?;
+    let len = llvm.build_add(xs_len, one, "len")?;
+    let ys = gen_list_alloc(llvm, len)?;
+    let zero = llvm.const_int64(0)?;
+    let head = gen_list_elem_ptr(llvm, ys, zero)?;
+    llvm.build_store(x, head)?;
+    let tail = gen_list_elem_ptr(llvm, ys, one)?;
+    let xs_data = gen_list_elem_ptr(llvm, xs, zero)?;
+    gen_list_memcpy(llvm, tail, xs_data, xs_len)?;
+    llvm.build_ret(ys)?;
+    Ok(f)
+}
+
+fn gen_list_concat_helper(llvm: &mut LLVM, name: &str) -> Result<LLVMValueRef> {
+    let ptr_ty = ptr_ty(llvm)?;
+    let f = add_helper(llvm, name, ptr_ty, vec![ptr_ty, ptr_ty])?;
+    let lhs = LLVM::get_param(f, 0)?;
+    let rhs = LLVM::get_param(f, 1)?;
+    let lhs_len = gen_len(llvm, lhs)?;
+    let rhs_len = gen_len(llvm, rhs)?;
+    let len = llvm.build_add(lhs_len, rhs_len, "len")?;
+    let xs = gen_list_alloc(llvm, len)?;
+    let zero = llvm.const_int64(0)?;
+    let data = gen_list_elem_ptr(llvm, xs, zero)?;
+    let lhs_data = gen_list_elem_ptr(llvm, lhs, zero)?;
+    let rhs_data = gen_list_elem_ptr(llvm, rhs, zero)?;
+    gen_list_memcpy(llvm, data, lhs_data, lhs_len)?;
+    let rest = gen_list_elem_ptr(llvm, xs, lhs_len)?;
+    gen_list_memcpy(llvm, rest, rhs_data, rhs_len)?;
+    llvm.build_ret(xs)?;
+    Ok(f)
+}
+
+fn gen_list_index_helper(llvm: &mut LLVM, name: &str) -> Result<LLVMValueRef> {
+    let ptr_ty = ptr_ty(llvm)?;
+    let int_ty = llvm.int32_type()?;
+    let int64_ty = llvm.int64_type()?;
+    let f = add_helper(llvm, name, int_ty, vec![ptr_ty, int_ty])?;
+    let xs = LLVM::get_param(f, 0)?;
+    let index = LLVM::get_param(f, 1)?;
+    let in_bounds_block = llvm.append_basic_block(f, "inbounds")?;
+    let out_of_bounds_block = llvm.append_basic_block(f, "outofbounds")?;
+    let len = gen_len(llvm, xs)?;
+    let index = llvm.build_sext(index, int64_ty, "index")?;
+    let in_bounds = llvm.build_icmp(LLVMIntPredicate::LLVMIntULT, index, len, "inbounds")?;
+    llvm.build_cond_br(in_bounds, in_bounds_block, out_of_bounds_block)?;
+    llvm.position_builder_at_end(in_bounds_block);
+    let elem_ptr = gen_list_elem_ptr(llvm, xs, index)?;
+    let elem = llvm.build_load(int_ty, elem_ptr, "elem")?;
+    llvm.build_ret(elem)?;
+    llvm.position_builder_at_end(out_of_bounds_block);
+    gen_abort(llvm)?;
+    Ok(f)
+}
+
+fn gen_list_map_helper(llvm: &mut LLVM, name: &str) -> Result<LLVMValueRef> {
+    let ptr_ty = ptr_ty(llvm)?;
+    let int_ty = llvm.int32_type()?;
+    let int64_ty = llvm.int64_type()?;
+    let g_ty = llvm.function_type(int_ty, vec![int_ty])?;
+    let f = add_helper(llvm, name, ptr_ty, vec![LLVM::pointer_type(g_ty)?, ptr_ty])?;
+    let g = LLVM::get_param(f, 0)?;
+    let xs = LLVM::get_param(f, 1)?;
+    let entry = llvm.get_insert_block().unwrap();
+    let cond_block = llvm.append_basic_block(f, "cond")?;
+    let body_block = llvm.append_basic_block(f, "body")?;
+    let end_block = llvm.append_basic_block(f, "end")?;
+    let len = gen_len(llvm, xs)?;
+    let ys = gen_list_alloc(llvm, len)?;
+    let zero = llvm.const_int64(0)?;
+    let one = llvm.const_int64(1)?;
+    llvm.build_br(cond_block)?;
+    llvm.position_builder_at_end(cond_block);
+    let i = llvm.build_phi(int64_ty, vec![(zero, entry)], "i")?;
+    let cond = llvm.build_icmp(LLVMIntPredicate::LLVMIntSLT, i, len, "cond")?;
+    llvm.build_cond_br(cond, body_block, end_block)?;
+    llvm.position_builder_at_end(body_block);
+    let x_ptr = gen_list_elem_ptr(llvm, xs, i)?;
+    let x = llvm.build_load(int_ty, x_ptr, "x")?;
+    let y = llvm.build_call(g_ty, g, vec![x], "y")?;
+    let y_ptr = gen_list_elem_ptr(llvm, ys, i)?;
+    llvm.build_store(y, y_ptr)?;
+    let next = llvm.build_add(i, one, "next")?;
+    llvm.add_incoming(i, next, body_block);
+    llvm.build_br(cond_block)?;
+    llvm.position_builder_at_end(end_block);
+    llvm.build_ret(ys)?;
+    Ok(f)
+}
+
+fn gen_list_filter_helper(llvm: &mut LLVM, name: &str) -> Result<LLVMValueRef> {
+    let ptr_ty = ptr_ty(llvm)?;
+    let int_ty = llvm.int32_type()?;
+    let int64_ty = llvm.int64_type()?;
+    let g_ty = llvm.function_type(int_ty, vec![int_ty])?;
+    let f = add_helper(llvm, name, ptr_ty, vec![LLVM::pointer_type(g_ty)?, ptr_ty])?;
+    let g = LLVM::get_param(f, 0)?;
+    let xs = LLVM::get_param(f, 1)?;
+    let entry = llvm.get_insert_block().unwrap();
+    let cond_block = llvm.append_basic_block(f, "cond")?;
+    let body_block = llvm.append_basic_block(f, "body")?;
+    let push_block = llvm.append_basic_block(f, "push")?;
+    let next_block = llvm.append_basic_block(f, "next")?;
+    let end_block = llvm.append_basic_block(f, "end")?;
+    let len = gen_len(llvm, xs)?;
+    let ys = gen_list_alloc(llvm, len)?;
+    let zero = llvm.const_int64(0)?;
+    let one = llvm.const_int64(1)?;
+    llvm.build_br(cond_block)?;
+    llvm.position_builder_at_end(cond_block);
+    let i = llvm.build_phi(int64_ty, vec![(zero, entry)], "i")?;
+    let j = llvm.build_phi(int64_ty, vec![(zero, entry)], "j")?;
+    let cond = llvm.build_icmp(LLVMIntPredicate::LLVMIntSLT, i, len, "cond")?;
+    llvm.build_cond_br(cond, body_block, end_block)?;
+    llvm.position_builder_at_end(body_block);
+    let x_ptr = gen_list_elem_ptr(llvm, xs, i)?;
+    let x = llvm.build_load(int_ty, x_ptr, "x")?;
+    let keep = llvm.build_call(g_ty, g, vec![x], "keep")?;
+    let false_value = llvm.const_int(0, 0)?;
+    let keep = llvm.build_icmp(LLVMIntPredicate::LLVMIntNE, keep, false_value, "keep")?;
+    llvm.build_cond_br(keep, push_block, next_block)?;
+    llvm.position_builder_at_end(push_block);
+    let y_ptr = gen_list_elem_ptr(llvm, ys, j)?;
+    llvm.build_store(x, y_ptr)?;
+    let pushed = llvm.build_add(j, one, "pushed")?;
+    llvm.build_br(next_block)?;
+    llvm.position_builder_at_end(next_block);
+    let next_j = llvm.build_phi(int64_ty, vec![(pushed, push_block), (j, body_block)], "nextj")?;
+    let next_i = llvm.build_add(i, one, "nexti")?;
+    llvm.add_incoming(i, next_i, next_block);
+    llvm.add_incoming(j, next_j, next_block);
+    llvm.build_br(cond_block)?;
+    llvm.position_builder_at_end(end_block);
+    gen_set_len(llvm, ys, j)?;
+    llvm.build_ret(ys)?;
+    Ok(f)
+}
+
+fn gen_list_fold_helper(llvm: &mut LLVM, name: &str) -> Result<LLVMValueRef> {
+    let ptr_ty = ptr_ty(llvm)?;
+    let int_ty = llvm.int32_type()?;
+    let int64_ty = llvm.int64_type()?;
+    let g_ty = llvm.function_type(int_ty, vec![int_ty, int_ty])?;
+    let f = add_helper(llvm, name, int_ty, vec![LLVM::pointer_type(g_ty)?, int_ty, ptr_ty])?;
+    let g = LLVM::get_param(f, 0)?;
+    let init = LLVM::get_param(f, 1)?;
+    let xs = LLVM::get_param(f, 2)?;
+    let entry = llvm.get_insert_block().unwrap();
+    let cond_block = llvm.append_basic_block(f, "cond")?;
+    let body_block = llvm.append_basic_block(f, "body")?;
+    let end_block = llvm.append_basic_block(f, "end")?;
+    let len = gen_len(llvm, xs)?;
+    let zero = llvm.const_int64(0)?;
+    let one = llvm.const_int64(1)?;
+    llvm.build_br(cond_block)?;
+    llvm.position_builder_at_end(cond_block);
+    let i = llvm.build_phi(int64_ty, vec![(zero, entry)], "i")?;
+    let acc = llvm.build_phi(int_ty, vec![(init, entry)], "acc")?;
+    let cond = llvm.build_icmp(LLVMIntPredicate::LLVMIntSLT, i, len, "cond")?;
+    llvm.build_cond_br(cond, body_block, end_block)?;
+    llvm.position_builder_at_end(body_block);
+    let x_ptr = gen_list_elem_ptr(llvm, xs, i)?;
+    let x = llvm.build_load(int_ty, x_ptr, "x")?;
+    let next_acc = llvm.build_call(g_ty, g, vec![acc, x], "nextacc")?;
+    let next_i = llvm.build_add(i, one, "nexti")?;
+    llvm.add_incoming(i, next_i, body_block);
+    llvm.add_incoming(acc, next_acc, body_block);
+    llvm.build_br(cond_block)?;
+    llvm.position_builder_at_end(end_block);
+    llvm.build_ret(acc)?;
+    Ok(f)
+}
+
+fn ptr_ty(llvm: &mut LLVM) -> Result<LLVMTypeRef> {
+    LLVM::pointer_type(llvm.int8_type()?)
+}
+
+fn gen_len(llvm: &mut LLVM, s: LLVMValueRef) -> Result<LLVMValueRef> {
+    let int64_ty = llvm.int64_type()?;
+    let len_ptr = llvm.build_pointer_cast(s, LLVM::pointer_type(int64_ty)?, "lenptr")?;
+    llvm.build_load(int64_ty, len_ptr, "len")
+}
+
+fn gen_set_len(llvm: &mut LLVM, s: LLVMValueRef, len: LLVMValueRef) -> Result<LLVMValueRef> {
+    let int64_ty = llvm.int64_type()?;
+    let len_ptr = llvm.build_pointer_cast(s, LLVM::pointer_type(int64_ty)?, "lenptr")?;
+    llvm.build_store(len, len_ptr)
+}
+
+fn gen_data(llvm: &mut LLVM, s: LLVMValueRef) -> Result<LLVMValueRef> {
+    let offset = llvm.const_int64(HEADER_SIZE)?;
+    gen_byte_offset(llvm, s, offset)
+}
+
+fn gen_byte_offset(llvm: &mut LLVM, ptr: LLVMValueRef, offset: LLVMValueRef) -> Result<LLVMValueRef> {
+    let int8_ty = llvm.int8_type()?;
+    llvm.build_in_bounds_gep(int8_ty, ptr, vec![offset], "offsetptr")
+}
+
+fn gen_list_alloc(llvm: &mut LLVM, len: LLVMValueRef) -> Result<LLVMValueRef> {
+    let elem_size = llvm.const_int64(LIST_ELEM_SIZE)?;
+    let data_size = llvm.build_mul(len, elem_size, "datasize")?;
+    let header_size = llvm.const_int64(HEADER_SIZE)?;
+    let size = llvm.build_add(data_size, header_size, "size")?;
+    let xs = gen_malloc(llvm, size)?;
+    gen_set_len(llvm, xs, len)?;
+    Ok(xs)
+}
+
+fn gen_list_elem_ptr(llvm: &mut LLVM, xs: LLVMValueRef, index: LLVMValueRef) -> Result<LLVMValueRef> {
+    let int_ty = llvm.int32_type()?;
+    let data = gen_data(llvm, xs)?;
+    let data = llvm.build_pointer_cast(data, LLVM::pointer_type(int_ty)?, "dataptr")?;
+    llvm.build_in_bounds_gep(int_ty, data, vec![index], "elemptr")
+}
+
+fn gen_list_memcpy(llvm: &mut LLVM, dst: LLVMValueRef, src: LLVMValueRef, len: LLVMValueRef) -> Result<LLVMValueRef> {
+    let ptr_ty = ptr_ty(llvm)?;
+    let elem_size = llvm.const_int64(LIST_ELEM_SIZE)?;
+    let size = llvm.build_mul(len, elem_size, "size")?;
+    let dst = llvm.build_pointer_cast(dst, ptr_ty, "dst")?;
+    let src = llvm.build_pointer_cast(src, ptr_ty, "src")?;
+    gen_memcpy(llvm, dst, src, size)
+}
+
+fn gen_malloc(llvm: &mut LLVM, size: LLVMValueRef) -> Result<LLVMValueRef> {
+    let ptr_ty = ptr_ty(llvm)?;
+    let size_ty = llvm.int64_type()?;
+    let f = get_or_add_extern(llvm, "malloc", ptr_ty, vec![size_ty])?;
+    let f_ty = LLVM::global_get_value_type(f)?;
+    llvm.build_call(f_ty, f, vec![size], "malloctmp")
+}
+
+fn gen_memcpy(llvm: &mut LLVM, dst: LLVMValueRef, src: LLVMValueRef, size: LLVMValueRef) -> Result<LLVMValueRef> {
+    let ptr_ty = ptr_ty(llvm)?;
+    let size_ty = llvm.int64_type()?;
+    let f = get_or_add_extern(llvm, "memcpy", ptr_ty, vec![ptr_ty, ptr_ty, size_ty])?;
+    let f_ty = LLVM::global_get_value_type(f)?;
+    llvm.build_call(f_ty, f, vec![dst, src, size], "memcpytmp")
+}
+
+fn gen_memcmp(llvm: &mut LLVM, lhs: LLVMValueRef, rhs: LLVMValueRef, size: LLVMValueRef) -> Result<LLVMValueRef> {
+    let ptr_ty = ptr_ty(llvm)?;
+    let int_ty = llvm.int32_type()?;
+    let size_ty = llvm.int64_type()?;
+    let f = get_or_add_extern(llvm, "memcmp", int_ty, vec![ptr_ty, ptr_ty, size_ty])?;
+    let f_ty = LLVM::global_get_value_type(f)?;
+    llvm.build_call(f_ty, f, vec![lhs, rhs, size], "memcmptmp")
+}
+
+fn gen_abort(llvm: &mut LLVM) -> Result<LLVMValueRef> {
+    let void_ty = llvm.void_type()?;
+    let f = get_or_add_extern(llvm, "abort", void_ty, Vec::new())?;
+    let f_ty = LLVM::global_get_value_type(f)?;
+    llvm.build_call(f_ty, f, Vec::new(), "")?;
+    llvm.build_unreachable()
+}
+
+fn get_or_add_extern(llvm: &mut LLVM, name: &str, ret_ty: LLVMTypeRef, arg_tys: Vec<LLVMTypeRef>) -> Result<LLVMValueRef> {
+    if let Ok(f) = llvm.get_named_function(name) {
+        return Ok(f);
+    }
+    let f_ty = llvm.function_type(ret_ty, arg_tys)?;
+    llvm.add_function(name, f_ty)
+}
+
+fn gen_helper_call(llvm: &mut LLVM, name: &str, gen: fn(&mut LLVM, &str) -> Result<LLVMValueRef>, args: Vec<LLVMValueRef>) -> Result<LLVMValueRef> {
+    let f = match llvm.get_named_function(name) {
+        Ok(f) => f,
+        Err(_) => {
+            let block = llvm.get_insert_block();
+            let f = gen(llvm, name);
+            if let Some(block) = block {
+                llvm.position_builder_at_end(block);
+            }
+            f?
+        },
+    };
+    let f_ty = LLVM::global_get_value_type(f)?;
+    llvm.build_call(f_ty, f, args, "helpertmp")
+}
+
+fn add_helper(llvm: &mut LLVM, name: &str, ret_ty: LLVMTypeRef, arg_tys: Vec<LLVMTypeRef>) -> Result<LLVMValueRef> {
+    let f_ty = llvm.function_type(ret_ty, arg_tys)?;
+    let f = llvm.add_function(name, f_ty)?;
+    LLVM::set_linkage(f, LLVMLinkage::LLVMInternalLinkage);
+    let block = llvm.append_basic_block(f, "entry")?;
+    llvm.position_builder_at_end(block);
+    Ok(f)
+}
@@ -1,18 +1,302 @@
-use std::fs;
-use anyhow::Result;
+#[cfg(test)]
+mod tests;
+
+use std::{
+    env,
+    fs,
+    path::Path,
+    process::Command,
+};
+use anyhow::{
+    bail,
+    Result,
+};
+use clap::ValueEnum;
+use llvm_sys::target_machine::LLVMCodeGenFileType;
 use crate::{
+    data::{
+        ast::{
+            ModuleAst,
+            ExprAst,
+        },
+        diagnostic::{
+            Diagnostic,
+            Diagnostics,
+        },
+    },
+    dump,
+    highlighter,
     lexer,
-    parser,
+    loader,
+    resolver,
+    arity,
+    linter::{
+        self,
+        Levels,
+    },
+    simplifier,
     llvm::LLVM,
     codegen,
+    jit::{
+        self,
+        Jit,
+    },
 };
 
-pub fn compile(in_path: &str, out_path: &str) -> Result<()> {
-    let code = fs::read_to_string(in_path)?;
+pub const STDOUT: &str = "-";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
+pub enum Emit {
+    Obj,
+    Asm,
+    #[default]
+    LlvmIr,
+    Bitcode,
+    Tokens,
+    TokensJson,
+    Ast,
+    AstJson,
+    TypedAst,
+    Html,
+}
+
+impl Emit {
+    pub fn default_output(&self) -> &'static str {
+        match self {
+            Emit::Obj => "./a.o",
+            Emit::Asm => "./a.s",
+            Emit::LlvmIr => "./a.ll",
+            Emit::Bitcode => "./a.bc",
+            Emit::Tokens | Emit::TokensJson | Emit::Ast | Emit::AstJson | Emit::TypedAst | Emit::Html => STDOUT,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
+pub enum OptLevel {
+    #[default]
+    #[value(name = "0")]
+    O0,
+    #[value(name = "1")]
+    O1,
+    #[value(name = "2")]
+    O2,
+    #[value(name = "3")]
+    O3,
+    #[value(name = "s")]
+    Os,
+}
+
+impl OptLevel {
+    pub fn pipeline(&self) -> Option<&'static str> {
+        match self {
+            OptLevel::O0 => None,
+            OptLevel::O1 => Some("default<O1>"),
+            OptLevel::O2 => Some("default<O2>"),
+            OptLevel::O3 => Some("default<O3>"),
+            OptLevel::Os => Some("default<Os>"),
+        }
+    }
+
+    pub fn codegen_level(&self) -> u32 {
+        match self {
+            OptLevel::O0 => 0,
+            OptLevel::O1 => 1,
+            OptLevel::O2 | OptLevel::Os => 2,
+            OptLevel::O3 => 3,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Options {
+    pub search_paths: Vec<String>,
+    pub emit: Emit,
+    pub opt_level: OptLevel,
+    pub passes: Option<String>,
+    pub verify_each: bool,
+    pub lints: Levels,
+}
+
+pub fn compile(in_path: &str, out_path: &str) -> Result<Vec<Diagnostic>> {
+    compile_with_options(in_path, out_path, &Options::default())
+}
+
+/// Returns the warnings found in the input.
+pub fn compile_with_options(in_path: &str, out_path: &str, options: &Options) -> Result<Vec<Diagnostic>> {
+    if let Emit::Tokens | Emit::TokensJson | Emit::Ast | Emit::AstJson | Emit::Html = options.emit {
+        dump_source(in_path, out_path, options.emit)?;
+        return Ok(Vec::new());
+    }
+    let (module_ast, warnings) = load(in_path, options)?;
+    compile_module(in_path, &module_ast, out_path, options)?;
+    Ok(warnings)
+}
+
+/// Loads the input along with its imports and lints the input as written,
+/// returning the warnings and failing on denied lints.
+fn load(in_path: &str, options: &Options) -> Result<(ModuleAst, Vec<Diagnostic>)> {
+    let (module_ast, source) = loader::load_with_source(in_path, &options.search_paths)?;
+    let Some(source) = source else {
+        return Ok((module_ast, Vec::new()));
+    };
+    let imported = module_ast.fn_defs.iter().filter(|fn_def_ast| fn_def_ast.is_pub).map(|fn_def_ast| &fn_def_ast.left_def.ident.name)
+        .chain(module_ast.extern_fns.iter().map(|extern_fn_ast| &extern_fn_ast.ident.name))
+        .cloned()
+        .collect::<Vec<_>>();
+    let symbol_table = resolver::resolve_with(&source.module_ast, &imported);
+    Diagnostics::check(&symbol_table.diagnostics)?;
+    let warnings = linter::check(&source.tokens, &source.module_ast, &symbol_table, &options.lints)?;
+    Ok((module_ast, warnings))
+}
+
+/// The 1-based line and column of `offset`.
+pub fn line_col(code: &str, offset: usize) -> (usize, usize) {
+    let before = &code[..offset];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    (before.matches('\n').count() + 1, before[line_start..].chars().count() + 1)
+}
+
+pub fn compile_module(module_id: &str, module_ast: &ModuleAst, out_path: &str, options: &Options) -> Result<()> {
+    match options.emit {
+        Emit::Tokens | Emit::TokensJson | Emit::Html => bail!("Tokens can only be dumped from a source file."),
+        Emit::Ast => return write_output(out_path, &dump::ast(module_ast)),
+        Emit::AstJson => return write_output(out_path, &dump::ast_json(module_ast)?),
+        Emit::TypedAst => {
+            let module_ast = check_module(module_ast)?;
+            let mut llvm = gen_checked_module(module_id, &module_ast, options)?;
+            let typed_ast = dump::typed_ast(&module_ast, &mut |fn_def_ast, expr_ast| codegen::infer_fn_expr_ty(&mut llvm, fn_def_ast, expr_ast));
+            return write_output(out_path, &typed_ast);
+        },
+        _ => (),
+    }
+    let mut llvm = gen_module(module_id, module_ast, options)?;
+    optimize(&mut llvm, options)?;
+    match options.emit {
+        Emit::Obj => llvm.emit_to_file(out_path, LLVMCodeGenFileType::LLVMObjectFile, options.opt_level.codegen_level())?,
+        Emit::Asm => llvm.emit_to_file(out_path, LLVMCodeGenFileType::LLVMAssemblyFile, options.opt_level.codegen_level())?,
+        Emit::LlvmIr if out_path == STDOUT => write_output(out_path, &llvm.print_module_to_string())?,
+        Emit::LlvmIr => llvm.print_module_to_file(out_path)?,
+        Emit::Bitcode => llvm.write_bitcode_to_file(out_path)?,
+        Emit::Tokens | Emit::TokensJson | Emit::Ast | Emit::AstJson | Emit::TypedAst | Emit::Html => unreachable!(),
+    }
+    Ok(())
+}
+
+/// Tokens, ASTs and highlighted HTML are dumped as written, before imports are resolved.
+fn dump_source(in_path: &str, out_path: &str, emit: Emit) -> Result<()> {
+    if let Emit::Ast | Emit::AstJson = emit {
+        let module_ast = loader::read_module(Path::new(in_path))?;
+        return compile_module(in_path, &module_ast, out_path, &Options { emit, ..Default::default() });
+    }
+    let code = match fs::read_to_string(in_path) {
+        Ok(code) => code,
+        Err(e) => bail!("Cannot read {}: {}", in_path, e),
+    };
+    if emit == Emit::Html {
+        return write_output(out_path, &highlighter::html(&code)?);
+    }
     let tokens = lexer::lex(code)?;
-    let asts = parser::parse(tokens)?;
-    let mut llvm = LLVM::new(in_path);
-    codegen::compile(&mut llvm, &asts)?;
-    llvm.print_module_to_file(out_path)?;
+    match emit {
+        Emit::TokensJson => write_output(out_path, &dump::tokens_json(&tokens)?),
+        _ => write_output(out_path, &dump::tokens(&tokens)),
+    }
+}
+
+fn write_output(out_path: &str, output: &str) -> Result<()> {
+    if out_path == STDOUT {
+        print!("{}", output);
+    }
+    else if let Err(e) = fs::write(out_path, output) {
+        bail!("Cannot write {}: {}", out_path, e);
+    }
+    Ok(())
+}
+
+/// Returns the warnings found in the input along with the compiled program.
+pub fn jit(in_path: &str, options: &Options) -> Result<(Jit, Vec<Diagnostic>)> {
+    let (module_ast, warnings) = load(in_path, options)?;
+    Ok((jit_module(in_path, &module_ast, options)?, warnings))
+}
+
+pub fn jit_module(module_id: &str, module_ast: &ModuleAst, options: &Options) -> Result<Jit> {
+    let llvm = gen_module(module_id, module_ast, options)?;
+    jit_llvm(llvm, options)
+}
+
+/// Checks definitions calling the functions already compiled into `jit` and adds them to it.
+pub fn jit_add_module(jit: &mut Jit, module_id: &str, module_ast: &ModuleAst, options: &Options) -> Result<()> {
+    let imported = jit.fn_tys().keys().cloned().collect::<Vec<_>>();
+    let symbol_table = resolver::resolve_with(module_ast, &imported);
+    Diagnostics::check(&symbol_table.diagnostics)?;
+    arity::check(module_ast, &symbol_table)?;
+    let module_ast = simplifier::simplify(module_ast, &symbol_table)?;
+    let defined = module_ast.fn_defs.iter().map(|fn_def_ast| fn_def_ast.left_def.ident.name.clone()).collect::<Vec<_>>();
+    jit.add_module(module_id, &defined, options, |llvm| codegen::compile(llvm, &module_ast))
+}
+
+/// Adds a function `name` evaluating `expr_ast` to `jit`.
+pub fn jit_add_expr(jit: &mut Jit, module_id: &str, name: &str, expr_ast: &ExprAst, options: &Options) -> Result<()> {
+    jit.add_module(module_id, &[name.to_owned()], options, |llvm| codegen::compile_expr(llvm, name, expr_ast).map(|_| ()))
+}
+
+pub fn jit_llvm(mut llvm: LLVM, options: &Options) -> Result<Jit> {
+    jit::gen_trampolines(&mut llvm)?;
+    optimize(&mut llvm, options)?;
+    Jit::new(llvm, options.opt_level.codegen_level())
+}
+
+pub fn gen_module(module_id: &str, module_ast: &ModuleAst, options: &Options) -> Result<LLVM> {
+    let module_ast = check_module(module_ast)?;
+    gen_checked_module(module_id, &module_ast, options)
+}
+
+/// Checks the module and simplifies it into the AST code is generated from.
+fn check_module(module_ast: &ModuleAst) -> Result<ModuleAst> {
+    let symbol_table = resolver::check(module_ast)?;
+    arity::check(module_ast, &symbol_table)?;
+    simplifier::simplify(module_ast, &symbol_table)
+}
+
+fn gen_checked_module(module_id: &str, module_ast: &ModuleAst, options: &Options) -> Result<LLVM> {
+    let mut llvm = LLVM::new(module_id);
+    llvm.set_verify_each(options.verify_each);
+    codegen::compile(&mut llvm, module_ast)?;
+    Ok(llvm)
+}
+
+pub fn gen_expr(module_id: &str, module_ast: &ModuleAst, name: &str, expr_ast: &ExprAst, options: &Options) -> Result<LLVM> {
+    let mut llvm = gen_module(module_id, module_ast, options)?;
+    codegen::compile_expr(&mut llvm, name, expr_ast)?;
+    Ok(llvm)
+}
+
+pub fn optimize(llvm: &mut LLVM, options: &Options) -> Result<()> {
+    llvm.verify_module()?;
+    if let Some(passes) = options.passes.as_deref().or(options.opt_level.pipeline()) {
+        llvm.run_passes(passes)?;
+        llvm.verify_module()?;
+    }
+    Ok(())
+}
+
+pub fn build(in_path: &str, out_path: &str, options: &Options) -> Result<Vec<Diagnostic>> {
+    let obj_path = format!("{}.o", out_path);
+    let options = Options { emit: Emit::Obj, ..options.clone() };
+    let warnings = compile_with_options(in_path, &obj_path, &options)?;
+    let result = link(&obj_path, out_path);
+    let _ = fs::remove_file(&obj_path);
+    result.map(|_| warnings)
+}
+
+fn link(obj_path: &str, out_path: &str) -> Result<()> {
+    let linker = env::var("CC").unwrap_or_else(|_| "cc".to_owned());
+    let status = match Command::new(&linker).args(["-o", out_path, obj_path]).status() {
+        Ok(status) => status,
+        Err(e) => bail!("Cannot run linker {}: {}", linker, e),
+    };
+    if !status.success() {
+        bail!("Linker failed: {}", status);
+    }
     Ok(())
 }
@@ -0,0 +1,208 @@
+use std::{
+    env,
+    fs,
+    path::PathBuf,
+    process::{
+        self,
+        Command,
+    },
+};
+use crate::{
+    codegen,
+    data::{
+        diagnostic::Diagnostic,
+        span::Span,
+        ty::Ty,
+    },
+    lexer,
+    linter::{
+        Level,
+        Levels,
+        Lint,
+    },
+    parser,
+};
+use super::{
+    Emit,
+    OptLevel,
+    Options,
+};
+
+fn write_input(test_name: &str, code: &str) -> (PathBuf, PathBuf) {
+    let dir = env::temp_dir().join(format!("akanec_compiler_{}_{}", test_name, process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let in_path = dir.join("main.akane");
+    fs::write(&in_path, code).unwrap();
+    (dir, in_path)
+}
+
+fn compile_code(test_name: &str, code: &str, options: &Options) -> anyhow::Result<Vec<u8>> {
+    let (dir, in_path) = write_input(test_name, code);
+    let out_path = dir.join("main.out");
+    let result = super::compile_with_options(in_path.to_str().unwrap(), out_path.to_str().unwrap(), options);
+    let output = result.map(|_| fs::read(&out_path).unwrap());
+    fs::remove_dir_all(&dir).unwrap();
+    output
+}
+
+fn compile(test_name: &str, emit: Emit) -> Vec<u8> {
+    compile_code(test_name, "pub f x = x + 1;", &Options { emit, ..Default::default() }).unwrap()
+}
+
+#[test]
+fn emit_llvm_ir() {
+    let output = String::from_utf8(compile("llvm_ir", Emit::LlvmIr)).unwrap();
+    assert!(output.contains("define i32 @f(i32 %x)"));
+}
+
+#[test]
+fn emit_bitcode() {
+    assert!(compile("bitcode", Emit::Bitcode).starts_with(b"BC\xC0\xDE"));
+}
+
+#[test]
+fn emit_asm() {
+    let output = String::from_utf8(compile("asm", Emit::Asm)).unwrap();
+    assert!(output.contains("f:"));
+}
+
+#[test]
+fn emit_obj() {
+    let output = compile("obj", Emit::Obj);
+    let magics: [&[u8]; 4] = [b"\x7FELF", b"\xCF\xFA\xED\xFE", b"\x64\x86", b"\x64\xAA"];
+    assert!(magics.iter().any(|magic| output.starts_with(magic)));
+}
+
+#[test]
+fn opt_level() {
+    let code = "add_one x = x + 1; pub add_two x = add_one (add_one x);";
+    let output = compile_code("o0", code, &Options::default()).unwrap();
+    assert!(String::from_utf8(output).unwrap().contains("@add_one("));
+    for opt_level in [OptLevel::O1, OptLevel::O2, OptLevel::O3, OptLevel::Os] {
+        let options = Options { opt_level, ..Default::default() };
+        let output = String::from_utf8(compile_code("o2", code, &options).unwrap()).unwrap();
+        assert!(!output.contains("@add_one("));
+        assert!(output.contains("add i32 %x, 2"));
+    }
+}
+
+#[test]
+fn codegen_level() {
+    let code = "pub f x y = x * y + x / y - x % y;";
+    let asm = |opt_level| {
+        let options = Options { emit: Emit::Asm, opt_level, passes: Some("verify".to_owned()), ..Default::default() };
+        compile_code("codegen_level", code, &options).unwrap()
+    };
+    assert_ne!(asm(OptLevel::O0), asm(OptLevel::O2));
+}
+
+#[test]
+fn custom_passes() {
+    let code = "add_one x = x + 1; pub add_two x = add_one (add_one x);";
+    let options = Options { passes: Some("always-inline,globaldce".to_owned()), ..Default::default() };
+    let output = String::from_utf8(compile_code("passes", code, &options).unwrap()).unwrap();
+    assert!(output.contains("@add_one("));
+    let options = Options { passes: Some("cgscc(inline),globaldce".to_owned()), ..Default::default() };
+    let output = String::from_utf8(compile_code("passes", code, &options).unwrap()).unwrap();
+    assert!(!output.contains("@add_one("));
+    let options = Options { passes: Some("no-such-pass".to_owned()), ..Default::default() };
+    assert!(compile_code("passes", code, &options).unwrap_err().to_string().starts_with("Cannot run passes:"));
+}
+
+#[test]
+fn verify_each() {
+    let code = r#"
+        is_odd x = x != 0;
+        pub f : String -> [i32] -> i32;
+        f s xs = (s == "abc") + fold add 0 (filter is_odd (1 :: xs ++ [2]));
+        add x y = x + y;
+    "#;
+    for opt_level in [OptLevel::O0, OptLevel::O2] {
+        let options = Options { opt_level, verify_each: true, ..Default::default() };
+        assert!(compile_code("verify_each", code, &options).is_ok());
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn build_executable() {
+    let (dir, in_path) = write_input("build", r#"
+        extern "C" puts : ptr -> i32;
+        discard x = 0;
+        main = discard (puts (c_str "Hello, world!")) + length "abc";
+    "#);
+    let out_path = dir.join("main");
+    super::build(in_path.to_str().unwrap(), out_path.to_str().unwrap(), &Options::default()).unwrap();
+    let output = Command::new(&out_path).output().unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(output.stdout, b"Hello, world!\n");
+    assert_eq!(output.status.code(), Some(3));
+}
+
+#[test]
+fn build_invalid_main() {
+    let (dir, in_path) = write_input("invalid_main", "main x = x;");
+    let out_path = dir.join("main");
+    let result = super::build(in_path.to_str().unwrap(), out_path.to_str().unwrap(), &Options::default());
+    fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(result.unwrap_err().to_string(), "main must have type i32.");
+}
+
+#[test]
+fn lint_levels() {
+    let code = "pub f x = (x);\n#[allow(unused_fn)]\ng = 0;";
+    let (dir, in_path) = write_input("lint_warn", code);
+    let in_path = in_path.to_str().unwrap();
+    let warnings = super::compile_with_options(in_path, dir.join("main.out").to_str().unwrap(), &Options::default()).unwrap();
+    let (_, jit_warnings) = super::jit(in_path, &Options::default()).unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(warnings, vec![Diagnostic::warning("Redundant parentheses.", Span::new(10, 13))]);
+    assert_eq!(jit_warnings, warnings);
+    let mut lints = Levels::default();
+    lints.set(Lint::RedundantParens, Level::Deny);
+    let e = compile_code("lint_deny", code, &Options { lints: lints.clone(), ..Default::default() }).unwrap_err();
+    assert_eq!(e.to_string(), "Redundant parentheses.");
+    lints.set(Lint::UnusedFn, Level::Deny);
+    let e = compile_code("lint_deny_attr", code, &Options { lints, ..Default::default() }).unwrap_err();
+    assert_eq!(e.to_string(), "Redundant parentheses.");
+    let e = compile_code("lint_unresolved", "pub f = (g);", &Options::default()).unwrap_err();
+    assert_eq!(e.to_string(), "Unknown identifier: g");
+    assert_eq!(super::line_col("f = 0;\ng = あ x;", 15), (2, 7));
+}
+
+#[test]
+fn infer_leaves_module() {
+    let module_ast = parser::parse(lexer::lex("pub f = 1;".to_owned()).unwrap()).unwrap();
+    let mut llvm = super::gen_module("infer_leaves_module", &module_ast, &Options::default()).unwrap();
+    let ir = llvm.print_module_to_string();
+    let expr_ast = parser::parse_expr(lexer::lex(r#"("a" ++ "b") == "ab""#.to_owned()).unwrap()).unwrap();
+    assert_eq!(codegen::infer_ty(&mut llvm, &expr_ast).unwrap(), Ty::I32);
+    assert_eq!(llvm.print_module_to_string(), ir);
+    for code in ["99999999999999999999", "2147483648", "2147483648 + 1"] {
+        let expr_ast = parser::parse_expr(lexer::lex(code.to_owned()).unwrap()).unwrap();
+        assert_eq!(codegen::infer_ty(&mut llvm, &expr_ast).unwrap_err().to_string(), "Integer literal out of range.");
+    }
+    let expr_ast = parser::parse_expr(lexer::lex("-2147483648".to_owned()).unwrap()).unwrap();
+    assert_eq!(codegen::infer_ty(&mut llvm, &expr_ast).unwrap(), Ty::I32);
+}
+
+#[test]
+fn emit_dumps() {
+    let code = "double x = x * 2; pub f x = double x + 1;";
+    let dump = |test_name: &str, emit: Emit| String::from_utf8(compile_code(test_name, code, &Options { emit, ..Default::default() }).unwrap()).unwrap();
+    assert!(dump("tokens", Emit::Tokens).starts_with("0..6 Ident(\"double\")\n7..8 Ident(\"x\")\n9..10 Equal\n"));
+    assert!(dump("ast", Emit::Ast).contains("  FnDef pub f\n    Arg x\n    InfixOp +\n"));
+    assert!(dump("html", Emit::Html).starts_with("<pre class=\"akane\"><code><span class=\"fn-def\">double</span> <span class=\"param\">x</span>"));
+    assert!(dump("typed_ast", Emit::TypedAst).contains("  FnDef pub f : i32 -> i32\n    Arg x : i32\n    InfixOp + : i32\n      Fn : i32\n"));
+    let typed_ast = compile_code("typed_ast_simplified", "pub f x = x + 2 * 3;", &Options { emit: Emit::TypedAst, ..Default::default() }).unwrap();
+    assert!(String::from_utf8(typed_ast).unwrap().contains("    InfixOp + : i32\n      Ident x : i32\n      Num 6 : i32\n"));
+    if cfg!(feature = "serde") {
+        assert!(dump("ast_json", Emit::AstJson).contains("\"op_code\": \"+\""));
+        assert!(dump("tokens_json", Emit::TokensJson).contains("\"kind\": \"Ident\",\n    \"value\": \"double\""));
+    }
+    else {
+        let ast_json = compile_code("ast_json", code, &Options { emit: Emit::AstJson, ..Default::default() });
+        assert_eq!(ast_json.unwrap_err().to_string(), "JSON output requires the serde feature.");
+    }
+}
@@ -1,51 +1,317 @@
 use std::rc::Rc;
+use crate::data::span::Span;
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ModuleAst {
+    pub name: Option<IdentAst>,
+    pub imports: Vec<ImportAst>,
+    pub extern_fns: Vec<ExternFnAst>,
+    pub fn_defs: Vec<FnDefAst>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ImportAst {
+    pub module: IdentAst,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExternFnAst {
+    pub abi: StrAst,
+    pub ident: IdentAst,
+    pub ty: TyAst,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FnDefAst {
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub attrs: Vec<AttrAst>,
+    pub is_pub: bool,
+    pub ty_annot: Option<TyAst>,
     pub left_def: LeftDefAst,
     pub expr: ExprAst,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub span: Span,
 }
 
+/// `#[name(arg, ...)]` before a function definition.
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AttrAst {
+    pub name: IdentAst,
+    pub args: Vec<IdentAst>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LeftDefAst {
     pub ident: IdentAst,
     pub args: Vec<IdentAst>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub span: Span,
 }
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "kind"))]
 pub enum ExprAst {
     Fn(FnAst),
     PrefixOp(PrefixOpAst),
     InfixOp(InfixOpAst),
     Ident(IdentAst),
     Num(NumAst),
+    Str(StrAst),
+    Char(CharAst),
+    List(ListAst),
 }
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FnAst {
     pub fn_expr: Rc<ExprAst>,
     pub arg_expr: Rc<ExprAst>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub span: Span,
 }
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PrefixOpAst {
     pub op_code: String,
     pub rhs: Rc<ExprAst>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub span: Span,
 }
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct InfixOpAst {
     pub op_code: String,
     pub lhs: Rc<ExprAst>,
     pub rhs: Rc<ExprAst>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub span: Span,
 }
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct IdentAst {
     pub name: String,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub span: Span,
 }
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NumAst {
     pub value: String,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StrAst {
+    pub value: String,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CharAst {
+    pub value: char,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ListAst {
+    pub elems: Vec<ExprAst>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "kind"))]
+pub enum TyAst {
+    Fn(FnTyAst),
+    List(ListTyAst),
+    Ident(IdentAst),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FnTyAst {
+    pub lhs: Rc<TyAst>,
+    pub rhs: Rc<TyAst>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ListTyAst {
+    pub elem: Rc<TyAst>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub span: Span,
+}
+
+impl ExprAst {
+    pub fn span(&self) -> Span {
+        match self {
+            ExprAst::Fn(fn_ast) => fn_ast.span,
+            ExprAst::PrefixOp(prefix_op_ast) => prefix_op_ast.span,
+            ExprAst::InfixOp(infix_op_ast) => infix_op_ast.span,
+            ExprAst::Ident(ident_ast) => ident_ast.span,
+            ExprAst::Num(num_ast) => num_ast.span,
+            ExprAst::Str(str_ast) => str_ast.span,
+            ExprAst::Char(char_ast) => char_ast.span,
+            ExprAst::List(list_ast) => list_ast.span,
+        }
+    }
+
+    /// The identifiers the expression uses, in source order.
+    pub fn idents(&self) -> Vec<&IdentAst> {
+        let mut idents = Vec::new();
+        self.collect_idents(&mut idents);
+        idents
+    }
+
+    fn collect_idents<'a>(&'a self, idents: &mut Vec<&'a IdentAst>) {
+        match self {
+            ExprAst::Fn(fn_ast) => {
+                fn_ast.fn_expr.collect_idents(idents);
+                fn_ast.arg_expr.collect_idents(idents);
+            },
+            ExprAst::PrefixOp(prefix_op_ast) =>
+                prefix_op_ast.rhs.collect_idents(idents),
+            ExprAst::InfixOp(infix_op_ast) => {
+                infix_op_ast.lhs.collect_idents(idents);
+                infix_op_ast.rhs.collect_idents(idents);
+            },
+            ExprAst::Ident(ident_ast) => idents.push(ident_ast),
+            ExprAst::List(list_ast) =>
+                for elem in &list_ast.elems {
+                    elem.collect_idents(idents);
+                },
+            ExprAst::Num(_) | ExprAst::Str(_) | ExprAst::Char(_) => (),
+        }
+    }
+}
+
+impl TyAst {
+    pub fn span(&self) -> Span {
+        match self {
+            TyAst::Fn(fn_ty_ast) => fn_ty_ast.span,
+            TyAst::List(list_ty_ast) => list_ty_ast.span,
+            TyAst::Ident(ident_ast) => ident_ast.span,
+        }
+    }
+}
+
+impl ModuleAst {
+    /// Copies with every span left at its default, so that trees can be compared
+    /// whatever source, if any, they were parsed from.
+    pub fn without_spans(&self) -> Self {
+        ModuleAst {
+            name: self.name.as_ref().map(IdentAst::without_spans),
+            imports: self.imports.iter().map(ImportAst::without_spans).collect(),
+            extern_fns: self.extern_fns.iter().map(ExternFnAst::without_spans).collect(),
+            fn_defs: self.fn_defs.iter().map(FnDefAst::without_spans).collect(),
+        }
+    }
+}
+
+impl ImportAst {
+    pub fn without_spans(&self) -> Self {
+        ImportAst { module: self.module.without_spans(), span: Span::default() }
+    }
+}
+
+impl ExternFnAst {
+    pub fn without_spans(&self) -> Self {
+        ExternFnAst { abi: self.abi.without_spans(), ident: self.ident.without_spans(), ty: self.ty.without_spans(), span: Span::default() }
+    }
+}
+
+impl FnDefAst {
+    pub fn without_spans(&self) -> Self {
+        FnDefAst {
+            attrs: self.attrs.iter().map(AttrAst::without_spans).collect(),
+            is_pub: self.is_pub,
+            ty_annot: self.ty_annot.as_ref().map(TyAst::without_spans),
+            left_def: self.left_def.without_spans(),
+            expr: self.expr.without_spans(),
+            span: Span::default(),
+        }
+    }
+}
+
+impl AttrAst {
+    pub fn without_spans(&self) -> Self {
+        AttrAst { name: self.name.without_spans(), args: self.args.iter().map(IdentAst::without_spans).collect(), span: Span::default() }
+    }
+}
+
+impl LeftDefAst {
+    pub fn without_spans(&self) -> Self {
+        LeftDefAst { ident: self.ident.without_spans(), args: self.args.iter().map(IdentAst::without_spans).collect(), span: Span::default() }
+    }
+}
+
+impl ExprAst {
+    pub fn without_spans(&self) -> Self {
+        let span = Span::default();
+        match self {
+            ExprAst::Fn(fn_ast) =>
+                ExprAst::Fn(FnAst { fn_expr: Rc::new(fn_ast.fn_expr.without_spans()), arg_expr: Rc::new(fn_ast.arg_expr.without_spans()), span }),
+            ExprAst::PrefixOp(prefix_op_ast) =>
+                ExprAst::PrefixOp(PrefixOpAst { op_code: prefix_op_ast.op_code.clone(), rhs: Rc::new(prefix_op_ast.rhs.without_spans()), span }),
+            ExprAst::InfixOp(infix_op_ast) => {
+                let lhs = Rc::new(infix_op_ast.lhs.without_spans());
+                let rhs = Rc::new(infix_op_ast.rhs.without_spans());
+                ExprAst::InfixOp(InfixOpAst { op_code: infix_op_ast.op_code.clone(), lhs, rhs, span })
+            },
+            ExprAst::Ident(ident_ast) => ExprAst::Ident(ident_ast.without_spans()),
+            ExprAst::Num(num_ast) => ExprAst::Num(NumAst { value: num_ast.value.clone(), span }),
+            ExprAst::Str(str_ast) => ExprAst::Str(str_ast.without_spans()),
+            ExprAst::Char(char_ast) => ExprAst::Char(CharAst { value: char_ast.value, span }),
+            ExprAst::List(list_ast) => ExprAst::List(ListAst { elems: list_ast.elems.iter().map(ExprAst::without_spans).collect(), span }),
+        }
+    }
+}
+
+impl IdentAst {
+    pub fn without_spans(&self) -> Self {
+        IdentAst { name: self.name.clone(), span: Span::default() }
+    }
+}
+
+impl StrAst {
+    pub fn without_spans(&self) -> Self {
+        StrAst { value: self.value.clone(), span: Span::default() }
+    }
+}
+
+impl TyAst {
+    pub fn without_spans(&self) -> Self {
+        let span = Span::default();
+        match self {
+            TyAst::Fn(fn_ty_ast) => TyAst::Fn(FnTyAst { lhs: Rc::new(fn_ty_ast.lhs.without_spans()), rhs: Rc::new(fn_ty_ast.rhs.without_spans()), span }),
+            TyAst::List(list_ty_ast) => TyAst::List(ListTyAst { elem: Rc::new(list_ty_ast.elem.without_spans()), span }),
+            TyAst::Ident(ident_ast) => TyAst::Ident(ident_ast.without_spans()),
+        }
+    }
 }
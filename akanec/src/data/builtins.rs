@@ -0,0 +1,11 @@
+pub const BUILTINS: [&str; 5] = ["length", "map", "filter", "fold", "c_str"];
+
+/// The arity of a builtin function, or `None` if `name` is not one.
+pub fn builtin_arity(name: &str) -> Option<usize> {
+    match name {
+        "length" | "c_str" => Some(1),
+        "map" | "filter" => Some(2),
+        "fold" => Some(3),
+        _ => None,
+    }
+}
@@ -0,0 +1,71 @@
+use std::{
+    error::Error,
+    fmt::{
+        self,
+        Display,
+        Formatter,
+    },
+};
+use anyhow::{
+    bail,
+    Result,
+};
+use crate::data::span::Span;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// A message about a range of the source.
+/// As an error it displays only the message, so the span can be recovered by downcasting
+/// without changing what is printed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    pub span: Span,
+}
+
+impl Diagnostic {
+    pub fn error(message: impl Into<String>, span: Span) -> Self {
+        Self { severity: Severity::Error, message: message.into(), span }
+    }
+
+    pub fn warning(message: impl Into<String>, span: Span) -> Self {
+        Self { severity: Severity::Warning, message: message.into(), span }
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl Error for Diagnostic {}
+
+/// Several errors reported at once, one message per line.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diagnostics(pub Vec<Diagnostic>);
+
+impl Diagnostics {
+    /// Fails with the errors among `diagnostics`, if there are any.
+    pub fn check(diagnostics: &[Diagnostic]) -> Result<()> {
+        let errors = diagnostics.iter().filter(|diagnostic| diagnostic.severity == Severity::Error).cloned().collect::<Vec<_>>();
+        if !errors.is_empty() {
+            bail!(Self(errors));
+        }
+        Ok(())
+    }
+}
+
+impl Display for Diagnostics {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let messages = self.0.iter().map(|diagnostic| diagnostic.message.as_str()).collect::<Vec<_>>();
+        write!(f, "{}", messages.join("\n"))
+    }
+}
+
+impl Error for Diagnostics {}
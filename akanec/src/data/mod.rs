@@ -1,2 +1,7 @@
+pub mod span;
+pub mod diagnostic;
 pub mod token;
 pub mod ast;
+pub mod ty;
+pub mod value;
+pub mod builtins;
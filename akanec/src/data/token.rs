@@ -1,12 +1,41 @@
+use crate::data::span::Span;
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "kind", content = "value"))]
 pub enum Token {
     Eof,
     Semicolon,
+    Module,
+    Import,
+    Pub,
+    Extern,
     Ident(String),
     Num(String),
+    Str(String),
+    Char(char),
     OpCode(String),
     Equal,
+    Colon,
     LParen,
     RParen,
+    LBracket,
+    RBracket,
+    Comma,
+    Whitespace(String),
+    Comment(String),
+}
+
+impl Token {
+    pub fn is_trivia(&self) -> bool {
+        matches!(self, Token::Whitespace(_) | Token::Comment(_))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SpannedToken {
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    pub token: Token,
+    pub span: Span,
 }
@@ -0,0 +1,112 @@
+use std::{
+    fmt::{
+        self,
+        Display,
+        Formatter,
+    },
+    rc::Rc,
+};
+use anyhow::{
+    bail,
+    Result,
+};
+use crate::data::ast::{
+    TyAst,
+    IdentAst,
+};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Ty {
+    I32,
+    Ptr,
+    Str,
+    List,
+    Fn(FnTy),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FnTy {
+    pub args: Vec<Ty>,
+    pub ret: Rc<Ty>,
+}
+
+impl Ty {
+    pub fn from_ty_ast(ty_ast: &TyAst) -> Result<Self> {
+        match ty_ast {
+            TyAst::Fn(fn_ty_ast) => {
+                let mut args = vec![Self::from_ty_ast(&fn_ty_ast.lhs)?];
+                let mut rhs = fn_ty_ast.rhs.as_ref();
+                while let TyAst::Fn(fn_ty_ast) = rhs {
+                    args.push(Self::from_ty_ast(&fn_ty_ast.lhs)?);
+                    rhs = fn_ty_ast.rhs.as_ref();
+                }
+                Ok(Ty::Fn(FnTy { args, ret: Rc::new(Self::from_ty_ast(rhs)?) }))
+            },
+            TyAst::List(list_ty_ast) =>
+                match Self::from_ty_ast(&list_ty_ast.elem)? {
+                    Ty::I32 => Ok(Ty::List),
+                    _ => bail!("Only lists of i32 are supported."),
+                },
+            TyAst::Ident(IdentAst { name, .. }) =>
+                match name.as_str() {
+                    "i32" => Ok(Ty::I32),
+                    "ptr" => Ok(Ty::Ptr),
+                    "String" => Ok(Ty::Str),
+                    _ => bail!("Unknown type."),
+                },
+        }
+    }
+}
+
+impl FnTy {
+    pub fn from_ty_ast(ty_ast: &TyAst) -> Result<Self> {
+        match Ty::from_ty_ast(ty_ast)? {
+            Ty::Fn(fn_ty) => Ok(fn_ty),
+            ty => Ok(Self { args: Vec::new(), ret: Rc::new(ty) }),
+        }
+    }
+
+    pub fn from_ty_annot(ty_annot: Option<&TyAst>, arg_count: usize) -> Result<Self> {
+        let ty = match ty_annot {
+            Some(ty_ast) => Ty::from_ty_ast(ty_ast)?,
+            None => return Ok(Self { args: vec![Ty::I32; arg_count], ret: Rc::new(Ty::I32) }),
+        };
+        match ty {
+            Ty::Fn(fn_ty) if fn_ty.args.len() >= arg_count => {
+                let mut args = fn_ty.args;
+                let rest = args.split_off(arg_count);
+                let ret =
+                    if rest.is_empty() {
+                        fn_ty.ret
+                    }
+                    else {
+                        Rc::new(Ty::Fn(FnTy { args: rest, ret: fn_ty.ret }))
+                    };
+                Ok(Self { args, ret })
+            },
+            ty if arg_count == 0 => Ok(Self { args: Vec::new(), ret: Rc::new(ty) }),
+            _ => bail!("Type annotation does not match the count of arguments."),
+        }
+    }
+}
+
+impl Display for Ty {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Ty::I32 => write!(f, "i32"),
+            Ty::Ptr => write!(f, "ptr"),
+            Ty::Str => write!(f, "String"),
+            Ty::List => write!(f, "[i32]"),
+            Ty::Fn(fn_ty) => write!(f, "({})", fn_ty),
+        }
+    }
+}
+
+impl Display for FnTy {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        for arg in &self.args {
+            write!(f, "{} -> ", arg)?;
+        }
+        write!(f, "{}", self.ret)
+    }
+}
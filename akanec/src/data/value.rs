@@ -0,0 +1,96 @@
+use std::fmt::{
+    self,
+    Display,
+    Formatter,
+};
+use anyhow::{
+    bail,
+    Result,
+};
+use crate::data::{
+    ast::NumAst,
+    diagnostic::Diagnostic,
+    ty::Ty,
+};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    I32(i32),
+    Ptr(usize),
+    Str(String),
+    List(Vec<i32>),
+}
+
+impl Value {
+    pub fn ty(&self) -> Ty {
+        match self {
+            Value::I32(_) => Ty::I32,
+            Value::Ptr(_) => Ty::Ptr,
+            Value::Str(_) => Ty::Str,
+            Value::List(_) => Ty::List,
+        }
+    }
+
+    pub fn parse(s: &str, ty: &Ty) -> Result<Self> {
+        match ty {
+            Ty::I32 =>
+                match s.parse() {
+                    Ok(n) => Ok(Value::I32(n)),
+                    Err(_) => bail!("Invalid i32: {}", s),
+                },
+            Ty::Str =>
+                Ok(Value::Str(s.to_owned())),
+            Ty::List => {
+                let elems = s.trim().trim_start_matches('[').trim_end_matches(']');
+                let mut xs = Vec::new();
+                for elem in elems.split(',').map(str::trim).filter(|elem| !elem.is_empty()) {
+                    match elem.parse() {
+                        Ok(n) => xs.push(n),
+                        Err(_) => bail!("Invalid [i32]: {}", s),
+                    }
+                }
+                Ok(Value::List(xs))
+            },
+            _ => bail!("Unsupported argument type: {}", ty),
+        }
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Value::I32(n) => write!(f, "{}", n),
+            Value::Ptr(p) => write!(f, "{:#x}", p),
+            Value::Str(s) => write!(f, "{:?}", s),
+            Value::List(xs) =>
+                write!(f, "[{}]", xs.iter().map(|x| x.to_string()).collect::<Vec<_>>().join(", ")),
+        }
+    }
+}
+
+/// The value of a literal, which is unsigned, so `2147483647` is the largest.
+pub fn num_value(num_ast: &NumAst) -> Result<i32> {
+    match num_ast.value.parse::<i32>() {
+        Ok(n) => Ok(n),
+        Err(_) => bail!(Diagnostic::error("Integer literal out of range.", num_ast.span)),
+    }
+}
+
+/// The value of a literal negated by a prefix `-`, which reaches one further for `-2147483648`.
+pub fn neg_num_value(num_ast: &NumAst) -> Result<i32> {
+    match num_ast.value.parse::<u32>() {
+        Ok(n) if n <= i32::MIN.unsigned_abs() => Ok((n as i32).wrapping_neg()),
+        _ => bail!(Diagnostic::error("Integer literal out of range.", num_ast.span)),
+    }
+}
+
+/// Divides with `op`, failing where codegen's `sdiv` and `srem` would trap.
+pub fn checked_div(lhs: i32, rhs: i32, op: fn(i32, i32) -> Option<i32>) -> Result<i32> {
+    if rhs == 0 {
+        bail!("Division by zero.");
+    }
+    match op(lhs, rhs) {
+        Some(n) => Ok(n),
+        None => bail!("Integer overflow."),
+    }
+}
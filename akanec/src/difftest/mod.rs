@@ -0,0 +1,265 @@
+#[cfg(test)]
+mod tests;
+
+use std::{
+    fmt::{
+        self,
+        Display,
+        Formatter,
+    },
+    rc::Rc,
+};
+use anyhow::Result;
+use crate::{
+    compiler::{
+        self,
+        Options,
+    },
+    data::{
+        ast::{
+            ModuleAst,
+            ExprAst,
+            FnAst,
+            PrefixOpAst,
+            InfixOpAst,
+            NumAst,
+            ListAst,
+        },
+        ty::{
+            Ty,
+            FnTy,
+        },
+        value::Value,
+    },
+    interp::Interp,
+    jit::Jit,
+    loader,
+    printer,
+};
+
+pub const I32_INPUTS: [i32; 9] = [0, 1, -1, 2, -7, 13, 100, i32::MAX, i32::MIN];
+pub const STR_INPUTS: [&str; 4] = ["", "a", "akane", "あかね"];
+pub const LIST_INPUTS: [&[i32]; 4] = [&[], &[0], &[1, -2, 3], &[5, 5, 5, 5]];
+pub const MAX_CASES: usize = 64;
+/// How deep the interpreter recurses, far past its default so as not to give up on programs the compiled code runs.
+/// At several kilobytes per call, `check` needs a thread with a stack of 64 MiB, as akanectest gives it.
+pub const MAX_DEPTH: usize = 2_000;
+
+pub type Runner<'a> = dyn FnMut(&ModuleAst, &str, &[Value]) -> Result<Value> + 'a;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Mismatch {
+    pub name: String,
+    pub args: Vec<Value>,
+    pub expected: Value,
+    pub actual: Result<Value, String>,
+    pub module_ast: ModuleAst,
+}
+
+impl Display for Mismatch {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let args = self.args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>().join(" ");
+        writeln!(f, "Mismatch in {} {}", self.name, args)?;
+        writeln!(f, "  interpreter: {}", self.expected)?;
+        match &self.actual {
+            Ok(value) => writeln!(f, "  compiled:    {}", value)?,
+            Err(e) => writeln!(f, "  compiled:    error: {}", e)?,
+        }
+        writeln!(f, "Minimal program:")?;
+        write!(f, "{}", printer::module(&self.module_ast))
+    }
+}
+
+pub fn check_file(in_path: &str, options: &Options) -> Result<Option<Mismatch>> {
+    let module_ast = loader::load(in_path, &options.search_paths)?;
+    check(&module_ast, &mut jit_runner(options))
+}
+
+pub fn jit_runner(options: &Options) -> impl FnMut(&ModuleAst, &str, &[Value]) -> Result<Value> + '_ {
+    let mut cache: Option<(ModuleAst, Jit)> = None;
+    move |module_ast, name, args| {
+        if !matches!(&cache, Some((cached, _)) if cached == module_ast) {
+            cache = None;
+            let jit = compiler::jit_module("difftest", module_ast, options)?;
+            cache = Some((module_ast.clone(), jit));
+        }
+        cache.as_mut().unwrap().1.call(name, args)
+    }
+}
+
+pub fn check(module_ast: &ModuleAst, run: &mut Runner) -> Result<Option<Mismatch>> {
+    let interp = Interp::new(module_ast)?;
+    for fn_def_ast in &module_ast.fn_defs {
+        let name = &fn_def_ast.left_def.ident.name;
+        let fn_ty = interp.fn_ty(name)?;
+        if !is_testable(&fn_ty) {
+            continue;
+        }
+        for args in inputs(&fn_ty.args) {
+            if let Some(mismatch) = compare(module_ast, name, &args, run) {
+                return Ok(Some(minimize(mismatch, run)));
+            }
+        }
+    }
+    Ok(None)
+}
+
+fn is_testable(fn_ty: &FnTy) -> bool {
+    fn_ty.args.iter().chain([fn_ty.ret.as_ref()]).all(|ty| matches!(ty, Ty::I32 | Ty::Str | Ty::List))
+}
+
+fn inputs(tys: &[Ty]) -> Vec<Vec<Value>> {
+    let mut cases = vec![Vec::new()];
+    for ty in tys {
+        let values = match ty {
+            Ty::I32 => I32_INPUTS.iter().map(|n| Value::I32(*n)).collect::<Vec<_>>(),
+            Ty::Str => STR_INPUTS.iter().map(|s| Value::Str(s.to_string())).collect(),
+            _ => LIST_INPUTS.iter().map(|xs| Value::List(xs.to_vec())).collect(),
+        };
+        cases = cases.into_iter()
+            .flat_map(|case| values.iter().map(move |value| [case.clone(), vec![value.clone()]].concat()))
+            .take(MAX_CASES)
+            .collect();
+    }
+    cases
+}
+
+/// A call the interpreter fails, recursing past `MAX_DEPTH` included, leaves nothing to compare against.
+fn compare(module_ast: &ModuleAst, name: &str, args: &[Value], run: &mut Runner) -> Option<Mismatch> {
+    let expected = Interp::new(module_ast).and_then(|mut interp| {
+        interp.set_max_depth(MAX_DEPTH);
+        interp.call(name, args)
+    });
+    let expected = expected.ok()?;
+    let actual = match run(module_ast, name, args) {
+        Ok(value) if value == expected => return None,
+        Ok(value) => Ok(value),
+        Err(e) => Err(e.to_string()),
+    };
+    Some(Mismatch { name: name.to_owned(), args: args.to_vec(), expected, actual, module_ast: module_ast.clone() })
+}
+
+fn minimize(mismatch: Mismatch, run: &mut Runner) -> Mismatch {
+    let mut mismatch = mismatch;
+    'reduce: loop {
+        for module_ast in reduce_module(&mismatch.module_ast, &mismatch.name) {
+            if let Some(reduced) = compare(&module_ast, &mismatch.name, &mismatch.args, run) {
+                if is_same_failure(&mismatch, &reduced) {
+                    mismatch = reduced;
+                    continue 'reduce;
+                }
+            }
+        }
+        for args in reduce_args(&mismatch.args) {
+            if let Some(reduced) = compare(&mismatch.module_ast, &mismatch.name, &args, run) {
+                if is_same_failure(&mismatch, &reduced) {
+                    mismatch = reduced;
+                    continue 'reduce;
+                }
+            }
+        }
+        return mismatch;
+    }
+}
+
+fn is_same_failure(lhs: &Mismatch, rhs: &Mismatch) -> bool {
+    match (&lhs.actual, &rhs.actual) {
+        (Ok(_), Ok(_)) => true,
+        (Err(lhs), Err(rhs)) => lhs == rhs,
+        _ => false,
+    }
+}
+
+fn reduce_module(module_ast: &ModuleAst, name: &str) -> Vec<ModuleAst> {
+    let mut reduced = Vec::new();
+    for i in 0..module_ast.extern_fns.len() {
+        let mut module_ast = module_ast.clone();
+        module_ast.extern_fns.remove(i);
+        reduced.push(module_ast);
+    }
+    for i in 0..module_ast.fn_defs.len() {
+        if module_ast.fn_defs[i].left_def.ident.name != name {
+            let mut module_ast = module_ast.clone();
+            module_ast.fn_defs.remove(i);
+            reduced.push(module_ast);
+        }
+    }
+    for i in 0..module_ast.fn_defs.len() {
+        for expr in reduce_expr(&module_ast.fn_defs[i].expr) {
+            let mut module_ast = module_ast.clone();
+            module_ast.fn_defs[i].expr = expr;
+            reduced.push(module_ast);
+        }
+    }
+    reduced
+}
+
+fn reduce_expr(expr_ast: &ExprAst) -> Vec<ExprAst> {
+    let zero = ExprAst::Num(NumAst { value: "0".to_owned(), span: expr_ast.span() });
+    let mut reduced = Vec::new();
+    if *expr_ast != zero {
+        reduced.push(zero);
+    }
+    match expr_ast {
+        ExprAst::Fn(fn_ast) => {
+            reduced.push(fn_ast.fn_expr.as_ref().clone());
+            reduced.push(fn_ast.arg_expr.as_ref().clone());
+            for fn_expr in reduce_expr(&fn_ast.fn_expr) {
+                reduced.push(ExprAst::Fn(FnAst { fn_expr: Rc::new(fn_expr), arg_expr: fn_ast.arg_expr.clone(), span: fn_ast.span }));
+            }
+            for arg_expr in reduce_expr(&fn_ast.arg_expr) {
+                reduced.push(ExprAst::Fn(FnAst { fn_expr: fn_ast.fn_expr.clone(), arg_expr: Rc::new(arg_expr), span: fn_ast.span }));
+            }
+        },
+        ExprAst::PrefixOp(prefix_op_ast) => {
+            reduced.push(prefix_op_ast.rhs.as_ref().clone());
+            for rhs in reduce_expr(&prefix_op_ast.rhs) {
+                reduced.push(ExprAst::PrefixOp(PrefixOpAst { op_code: prefix_op_ast.op_code.clone(), rhs: Rc::new(rhs), span: prefix_op_ast.span }));
+            }
+        },
+        ExprAst::InfixOp(infix_op_ast) => {
+            reduced.push(infix_op_ast.lhs.as_ref().clone());
+            reduced.push(infix_op_ast.rhs.as_ref().clone());
+            for lhs in reduce_expr(&infix_op_ast.lhs) {
+                reduced.push(ExprAst::InfixOp(InfixOpAst { op_code: infix_op_ast.op_code.clone(), lhs: Rc::new(lhs), rhs: infix_op_ast.rhs.clone(), span: infix_op_ast.span }));
+            }
+            for rhs in reduce_expr(&infix_op_ast.rhs) {
+                reduced.push(ExprAst::InfixOp(InfixOpAst { op_code: infix_op_ast.op_code.clone(), lhs: infix_op_ast.lhs.clone(), rhs: Rc::new(rhs), span: infix_op_ast.span }));
+            }
+        },
+        ExprAst::List(list_ast) => {
+            for i in 0..list_ast.elems.len() {
+                let mut elems = list_ast.elems.clone();
+                elems.remove(i);
+                reduced.push(ExprAst::List(ListAst { elems, span: list_ast.span }));
+            }
+            for i in 0..list_ast.elems.len() {
+                for elem in reduce_expr(&list_ast.elems[i]) {
+                    let mut elems = list_ast.elems.clone();
+                    elems[i] = elem;
+                    reduced.push(ExprAst::List(ListAst { elems, span: list_ast.span }));
+                }
+            }
+        },
+        ExprAst::Ident(_) | ExprAst::Num(_) | ExprAst::Str(_) | ExprAst::Char(_) => (),
+    }
+    reduced
+}
+
+fn reduce_args(args: &[Value]) -> Vec<Vec<Value>> {
+    let mut reduced = Vec::new();
+    for i in 0..args.len() {
+        let smaller = match &args[i] {
+            Value::I32(n) => [0, n / 2].into_iter().filter(|m| m != n).map(Value::I32).collect(),
+            Value::Str(s) if !s.is_empty() => vec![Value::Str(String::new())],
+            Value::List(xs) => (0..xs.len()).map(|j| Value::List([&xs[..j], &xs[j + 1..]].concat())).collect(),
+            _ => Vec::new(),
+        };
+        for value in smaller {
+            let mut args = args.to_vec();
+            args[i] = value;
+            reduced.push(args);
+        }
+    }
+    reduced
+}
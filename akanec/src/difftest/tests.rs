@@ -0,0 +1,138 @@
+use std::{
+    rc::Rc,
+    thread,
+};
+use anyhow::Result;
+use crate::{
+    compiler::Options,
+    data::{
+        ast::{
+            ModuleAst,
+            ExprAst,
+            FnAst,
+            PrefixOpAst,
+            InfixOpAst,
+            ListAst,
+        },
+        value::Value,
+    },
+    interp::Interp,
+    lexer,
+    parser,
+};
+use super::{
+    check,
+    inputs,
+    jit_runner,
+    MAX_DEPTH,
+};
+
+fn parse(code: &str) -> ModuleAst {
+    parser::parse(lexer::lex(code.to_owned()).unwrap()).unwrap()
+}
+
+/// `check` interprets calls up to `MAX_DEPTH` deep, which takes a bigger stack than a test thread's.
+fn with_stack(f: impl FnOnce() + Send + 'static) {
+    thread::Builder::new().stack_size(64 * 1024 * 1024).spawn(f).unwrap().join().unwrap();
+}
+
+fn sub_to_add(expr_ast: &ExprAst) -> ExprAst {
+    match expr_ast {
+        ExprAst::Fn(fn_ast) =>
+            ExprAst::Fn(FnAst { fn_expr: Rc::new(sub_to_add(&fn_ast.fn_expr)), arg_expr: Rc::new(sub_to_add(&fn_ast.arg_expr)), span: fn_ast.span }),
+        ExprAst::PrefixOp(prefix_op_ast) =>
+            ExprAst::PrefixOp(PrefixOpAst { op_code: prefix_op_ast.op_code.clone(), rhs: Rc::new(sub_to_add(&prefix_op_ast.rhs)), span: prefix_op_ast.span }),
+        ExprAst::InfixOp(infix_op_ast) => {
+            let op_code = if infix_op_ast.op_code == "-" { "+".to_owned() } else { infix_op_ast.op_code.clone() };
+            ExprAst::InfixOp(InfixOpAst { op_code, lhs: Rc::new(sub_to_add(&infix_op_ast.lhs)), rhs: Rc::new(sub_to_add(&infix_op_ast.rhs)), span: infix_op_ast.span })
+        },
+        ExprAst::List(list_ast) =>
+            ExprAst::List(ListAst { elems: list_ast.elems.iter().map(sub_to_add).collect(), span: list_ast.span }),
+        expr_ast => expr_ast.clone(),
+    }
+}
+
+fn buggy_runner(module_ast: &ModuleAst, name: &str, args: &[Value]) -> Result<Value> {
+    let mut module_ast = module_ast.clone();
+    for fn_def_ast in &mut module_ast.fn_defs {
+        fn_def_ast.expr = sub_to_add(&fn_def_ast.expr);
+    }
+    Interp::new(&module_ast)?.call(name, args)
+}
+
+#[test]
+fn no_mismatch() {
+    with_stack(|| {
+        let module_ast = parse(r#"
+            add a b = a + b;
+            f x = add (x * 3) (x / 2) - x % 5;
+            even n = n == 0 || odd (n - 1);
+            odd n = n != 0 && even (n - 1);
+            greet : String -> String;
+            greet name = "Hello, " ++ name;
+            g : [i32] -> i32;
+            g xs = fold add 0 (1 :: xs) + length xs;
+            h : [i32] -> i32;
+            h xs = xs !! 0;
+        "#);
+        let options = Options::default();
+        assert_eq!(check(&module_ast, &mut jit_runner(&options)).unwrap(), None);
+    });
+}
+
+#[test]
+fn minimal_mismatch() {
+    let module_ast = parse(r#"
+        helper x = x * 2;
+        unused = 42;
+        ok x = x + 1;
+        f x = helper x + (x - 3) * 1;
+    "#);
+    let mismatch = check(&module_ast, &mut buggy_runner).unwrap().unwrap();
+    assert_eq!(mismatch.name, "f");
+    assert_eq!(mismatch.args, vec![Value::I32(0)]);
+    assert_eq!(mismatch.expected, Value::I32(-3));
+    assert_eq!(mismatch.actual, Ok(Value::I32(3)));
+    assert_eq!(mismatch.to_string(), "\
+Mismatch in f 0
+  interpreter: -3
+  compiled:    3
+Minimal program:
+f x = 0 - 3;
+");
+}
+
+#[test]
+fn compile_error_mismatch() {
+    let module_ast = parse("add a b = a + b; f = length (map (add 1) [1]);");
+    let options = Options::default();
+    let mismatch = check(&module_ast, &mut jit_runner(&options)).unwrap().unwrap();
+    assert_eq!(mismatch.actual, Err("add takes 2 arguments but 1 was given.".to_owned()));
+    assert!(mismatch.to_string().ends_with("Minimal program:\nadd a b = 0;\nf = add 0;\n"));
+}
+
+#[test]
+fn deep_recursion() {
+    with_stack(|| {
+        let count = "count n = n <= 0 || count (n - 1);";
+        let mut wrong_runner = |module_ast: &ModuleAst, name: &str, args: &[Value]| -> Result<Value> {
+            match name {
+                "count" => Interp::new(module_ast)?.call(name, args),
+                _ => Ok(Value::I32(0)),
+            }
+        };
+        let module_ast = parse(&format!("{count} deep = count {};", MAX_DEPTH - 10));
+        let mismatch = check(&module_ast, &mut wrong_runner).unwrap().unwrap();
+        assert_eq!(mismatch.name, "deep");
+        let module_ast = parse(&format!("{count} too_deep = count {};", MAX_DEPTH));
+        assert_eq!(check(&module_ast, &mut wrong_runner).unwrap(), None);
+    });
+}
+
+#[test]
+fn input_cases() {
+    use crate::data::ty::Ty;
+    assert_eq!(inputs(&[]), vec![Vec::<Value>::new()]);
+    assert_eq!(inputs(&[Ty::I32]).len(), super::I32_INPUTS.len());
+    assert_eq!(inputs(&[Ty::I32, Ty::I32, Ty::List]).len(), super::MAX_CASES);
+}
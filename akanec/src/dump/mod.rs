@@ -0,0 +1,178 @@
+#[cfg(test)]
+mod tests;
+
+use anyhow::{
+    bail,
+    Result,
+};
+use crate::{
+    data::{
+        ast::{
+            ModuleAst,
+            ExternFnAst,
+            FnDefAst,
+            ExprAst,
+        },
+        token::SpannedToken,
+        ty::{
+            Ty,
+            FnTy,
+        },
+    },
+    printer,
+};
+
+pub type Infer<'a> = dyn FnMut(&FnDefAst, &ExprAst) -> Result<Ty> + 'a;
+
+pub fn tokens(tokens: &[SpannedToken]) -> String {
+    tokens.iter().map(|spanned| format!("{} {:?}\n", spanned.span, spanned.token)).collect()
+}
+
+pub fn ast(module_ast: &ModuleAst) -> String {
+    let mut printer = Printer { out: String::new(), infer: None };
+    printer.module(module_ast);
+    printer.out
+}
+
+pub fn expr(expr_ast: &ExprAst) -> String {
+    let mut printer = Printer { out: String::new(), infer: None };
+    printer.expr(0, None, expr_ast);
+    printer.out
+}
+
+/// Like `ast`, but every function shows its resolved signature and every expression its type.
+/// Expressions `infer` cannot type, such as partial applications, are left unannotated.
+pub fn typed_ast(module_ast: &ModuleAst, infer: &mut Infer) -> String {
+    let mut printer = Printer { out: String::new(), infer: Some(infer) };
+    printer.module(module_ast);
+    printer.out
+}
+
+#[cfg(feature = "serde")]
+pub fn tokens_json(tokens: &[SpannedToken]) -> Result<String> {
+    Ok(serde_json::to_string_pretty(tokens)? + "\n")
+}
+
+#[cfg(feature = "serde")]
+pub fn ast_json(module_ast: &ModuleAst) -> Result<String> {
+    Ok(serde_json::to_string_pretty(module_ast)? + "\n")
+}
+
+/// Spans may be omitted, so tools can generate ASTs without source positions.
+#[cfg(feature = "serde")]
+pub fn ast_from_json(json: &str) -> Result<ModuleAst> {
+    match serde_json::from_str(json) {
+        Ok(module_ast) => Ok(module_ast),
+        Err(e) => bail!("Invalid AST JSON: {}", e),
+    }
+}
+
+#[cfg(not(feature = "serde"))]
+pub fn tokens_json(_tokens: &[SpannedToken]) -> Result<String> {
+    bail!("JSON output requires the serde feature.")
+}
+
+#[cfg(not(feature = "serde"))]
+pub fn ast_json(_module_ast: &ModuleAst) -> Result<String> {
+    bail!("JSON output requires the serde feature.")
+}
+
+#[cfg(not(feature = "serde"))]
+pub fn ast_from_json(_json: &str) -> Result<ModuleAst> {
+    bail!("JSON input requires the serde feature.")
+}
+
+struct Printer<'a, 'b> {
+    out: String,
+    infer: Option<&'a mut Infer<'b>>,
+}
+
+impl Printer<'_, '_> {
+    fn line(&mut self, depth: usize, s: &str) {
+        self.out += &"  ".repeat(depth);
+        self.out += s;
+        self.out += "\n";
+    }
+
+    fn module(&mut self, module_ast: &ModuleAst) {
+        match &module_ast.name {
+            Some(name) => self.line(0, &format!("Module {}", name.name)),
+            None => self.line(0, "Module"),
+        }
+        for import_ast in &module_ast.imports {
+            self.line(1, &format!("Import {}", import_ast.module.name));
+        }
+        for extern_fn_ast in &module_ast.extern_fns {
+            self.extern_fn(extern_fn_ast);
+        }
+        for fn_def_ast in &module_ast.fn_defs {
+            self.fn_def(fn_def_ast);
+        }
+    }
+
+    fn extern_fn(&mut self, extern_fn_ast: &ExternFnAst) {
+        let s = format!("ExternFn {:?} {} : {}", extern_fn_ast.abi.value, extern_fn_ast.ident.name, printer::ty(&extern_fn_ast.ty));
+        self.line(1, &s);
+    }
+
+    fn fn_def(&mut self, fn_def_ast: &FnDefAst) {
+        let visibility = if fn_def_ast.is_pub { "pub " } else { "" };
+        let name = &fn_def_ast.left_def.ident.name;
+        let fn_ty =
+            if self.infer.is_some() {
+                FnTy::from_ty_annot(fn_def_ast.ty_annot.as_ref(), fn_def_ast.left_def.args.len()).ok()
+            }
+            else {
+                None
+            };
+        match (&fn_ty, &fn_def_ast.ty_annot) {
+            (Some(fn_ty), _) => self.line(1, &format!("FnDef {}{} : {}", visibility, name, fn_ty)),
+            (None, Some(ty_annot)) => self.line(1, &format!("FnDef {}{} : {}", visibility, name, printer::ty(ty_annot))),
+            (None, None) => self.line(1, &format!("FnDef {}{}", visibility, name)),
+        }
+        for attr_ast in &fn_def_ast.attrs {
+            self.line(2, &format!("Attr {}", printer::attr(attr_ast)));
+        }
+        for (i, arg) in fn_def_ast.left_def.args.iter().enumerate() {
+            match fn_ty.as_ref().and_then(|fn_ty| fn_ty.args.get(i)) {
+                Some(ty) => self.line(2, &format!("Arg {} : {}", arg.name, ty)),
+                None => self.line(2, &format!("Arg {}", arg.name)),
+            }
+        }
+        self.expr(2, Some(fn_def_ast), &fn_def_ast.expr);
+    }
+
+    fn expr(&mut self, depth: usize, fn_def_ast: Option<&FnDefAst>, expr_ast: &ExprAst) {
+        let s = match expr_ast {
+            ExprAst::Fn(_) => "Fn".to_owned(),
+            ExprAst::PrefixOp(prefix_op_ast) => format!("PrefixOp {}", prefix_op_ast.op_code),
+            ExprAst::InfixOp(infix_op_ast) => format!("InfixOp {}", infix_op_ast.op_code),
+            ExprAst::Ident(ident_ast) => format!("Ident {}", ident_ast.name),
+            ExprAst::Num(num_ast) => format!("Num {}", num_ast.value),
+            ExprAst::Str(str_ast) => format!("Str {:?}", str_ast.value),
+            ExprAst::Char(char_ast) => format!("Char {:?}", char_ast.value),
+            ExprAst::List(_) => "List".to_owned(),
+        };
+        match self.infer.as_mut().zip(fn_def_ast).and_then(|(infer, fn_def_ast)| infer(fn_def_ast, expr_ast).ok()) {
+            Some(ty) => self.line(depth, &format!("{} : {}", s, ty)),
+            None => self.line(depth, &s),
+        }
+        match expr_ast {
+            ExprAst::Fn(fn_ast) => {
+                self.expr(depth + 1, fn_def_ast, &fn_ast.fn_expr);
+                self.expr(depth + 1, fn_def_ast, &fn_ast.arg_expr);
+            },
+            ExprAst::PrefixOp(prefix_op_ast) =>
+                self.expr(depth + 1, fn_def_ast, &prefix_op_ast.rhs),
+            ExprAst::InfixOp(infix_op_ast) => {
+                self.expr(depth + 1, fn_def_ast, &infix_op_ast.lhs);
+                self.expr(depth + 1, fn_def_ast, &infix_op_ast.rhs);
+            },
+            ExprAst::List(list_ast) =>
+                for elem in &list_ast.elems {
+                    self.expr(depth + 1, fn_def_ast, elem);
+                },
+            _ => (),
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use anyhow::bail;
+use crate::{
+    data::{
+        ast::ExprAst,
+        ty::Ty,
+    },
+    lexer,
+    parser,
+};
+
+fn parse(input: &str) -> crate::data::ast::ModuleAst {
+    parser::parse(lexer::lex(input.to_owned()).unwrap()).unwrap()
+}
+
+#[test]
+fn dump_tokens() {
+    let tokens = lexer::lex("f x = x;".to_owned()).unwrap();
+    assert_eq!(super::tokens(&tokens), "0..1 Ident(\"f\")\n2..3 Ident(\"x\")\n4..5 Equal\n6..7 Ident(\"x\")\n7..8 Semicolon\n8..8 Eof\n");
+}
+
+#[test]
+fn dump_ast() {
+    let module_ast = parse("module M; import N; extern \"C\" abs : i32 -> i32; #[allow(unused_fn)] pub f : (i32 -> i32) -> [i32]; f g = g 1 :: [-2, 'a'];");
+    assert_eq!(super::ast(&module_ast), "\
+Module M
+  Import N
+  ExternFn \"C\" abs : i32 -> i32
+  FnDef pub f : (i32 -> i32) -> [i32]
+    Attr #[allow(unused_fn)]
+    Arg g
+    InfixOp ::
+      Fn
+        Ident g
+        Num 1
+      List
+        PrefixOp -
+          Num 2
+        Char 'a'
+");
+}
+
+#[test]
+fn dump_typed_ast() {
+    let module_ast = parse("f x = g x 1;");
+    let mut infer = |_: &_, expr_ast: &ExprAst| match expr_ast {
+        ExprAst::Fn(_) => bail!("Invalid count of arguments."),
+        _ => Ok(Ty::I32),
+    };
+    assert_eq!(super::typed_ast(&module_ast, &mut infer), "\
+Module
+  FnDef f : i32 -> i32
+    Arg x : i32
+    Fn
+      Fn
+        Ident g : i32
+        Ident x : i32
+      Num 1 : i32
+");
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn dump_ast_json() {
+    let module_ast = parse("f = \"a\";");
+    let json = super::ast_json(&module_ast).unwrap();
+    assert!(json.contains("\"fn_defs\": ["));
+    assert!(json.contains("\"kind\": \"Str\",\n        \"value\": \"a\""));
+    assert!(json.ends_with("}\n"));
+
+    let parsed = super::ast_from_json(&json).unwrap();
+    assert_eq!(parsed, module_ast);
+    assert_eq!((parsed.fn_defs[0].expr.span().start, parsed.fn_defs[0].expr.span().end), (4, 7));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn ast_from_json_without_spans() {
+    let json = r#"{
+        "name": null,
+        "imports": [],
+        "extern_fns": [],
+        "fn_defs": [{
+            "is_pub": true,
+            "ty_annot": null,
+            "left_def": { "ident": { "name": "f" }, "args": [{ "name": "x" }] },
+            "expr": { "kind": "InfixOp", "op_code": "+", "lhs": { "kind": "Ident", "name": "x" }, "rhs": { "kind": "Num", "value": "1" } }
+        }]
+    }"#;
+    assert_eq!(super::ast_from_json(json).unwrap(), parse("pub f x = x + 1;").without_spans());
+    assert!(super::ast_from_json("{}").unwrap_err().to_string().starts_with("Invalid AST JSON: "));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn dump_tokens_json() {
+    let tokens = lexer::lex("f 'a';".to_owned()).unwrap();
+    let json = super::tokens_json(&tokens).unwrap();
+    assert!(json.contains("\"kind\": \"Char\",\n    \"value\": \"a\",\n    \"span\": {\n      \"start\": 2,\n      \"end\": 5\n    }"));
+    assert!(json.contains("\"kind\": \"Eof\","));
+}
@@ -0,0 +1,208 @@
+#[cfg(test)]
+mod tests;
+
+use std::fs;
+use anyhow::{
+    bail,
+    Result,
+};
+use crate::{
+    data::{
+        span::Span,
+        token::{
+            Token,
+            SpannedToken,
+        },
+    },
+    lexer,
+    parser,
+    printer,
+};
+
+pub const DEFAULT_WIDTH: usize = 100;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Options {
+    pub width: usize,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self { width: DEFAULT_WIDTH }
+    }
+}
+
+/// Returns whether the file was already formatted; unless `check` is set, it is rewritten if not.
+pub fn format_file(path: &str, options: &Options, check: bool) -> Result<bool> {
+    let code = match fs::read_to_string(path) {
+        Ok(code) => code,
+        Err(e) => bail!("Cannot read {}: {}", path, e),
+    };
+    let formatted = format(&code, options)?;
+    if formatted == code {
+        return Ok(true);
+    }
+    if !check {
+        fs::write(path, formatted)?;
+    }
+    Ok(false)
+}
+
+/// Reprints every top-level item of `code` in canonical form.
+/// Comments between items and single blank lines are kept,
+/// and comments inside an item are kept beside the same tokens.
+pub fn format(code: &str, options: &Options) -> Result<String> {
+    let tokens = lexer::lex_lossless(code.to_owned())?;
+    let module_ast = parser::parse(tokens.iter().filter(|spanned| !spanned.token.is_trivia()).cloned().collect())?;
+
+    let mut items = Vec::new();
+    if let Some(name) = &module_ast.name {
+        items.push((module_decl_span(&tokens), format!("module {};\n", name.name)));
+    }
+    for import_ast in &module_ast.imports {
+        items.push((import_ast.span, format!("import {};\n", import_ast.module.name)));
+    }
+    for extern_fn_ast in &module_ast.extern_fns {
+        items.push((extern_fn_ast.span, printer::extern_fn(extern_fn_ast)));
+    }
+    for fn_def_ast in &module_ast.fn_defs {
+        items.push((fn_def_ast.span, printer::fn_def_within(fn_def_ast, options.width)));
+    }
+    items.sort_by_key(|(span, _)| span.start);
+
+    let mut out = String::new();
+    let mut prev_end = None;
+    for (span, item) in items {
+        trivia(&mut out, &tokens, prev_end, span.start);
+        out += &with_comments(code, &tokens, span, item)?;
+        prev_end = Some(span.end);
+    }
+    trivia(&mut out, &tokens, prev_end, code.len());
+    Ok(out)
+}
+
+enum Attach {
+    /// A comment on its own line, written on its own line before the token.
+    Before,
+    /// A comment after a token on the same line, which ends that line.
+    After,
+}
+
+/// Puts back the comments inside the item at `span` into its formatted form `item`.
+/// Each comment is attached to a token of the item and written beside the same token of `item`.
+/// The two differ at most in parentheses, so the other tokens pair up in order;
+/// if they do not, the item is kept as written.
+fn with_comments(code: &str, tokens: &[SpannedToken], span: Span, item: String) -> Result<String> {
+    let mut written = Vec::new();
+    let mut comments = Vec::new();
+    let mut is_own_line = true;
+    for spanned in tokens.iter().filter(|spanned| span.start <= spanned.span.start && spanned.span.end <= span.end) {
+        match &spanned.token {
+            Token::Whitespace(whitespace) => is_own_line |= whitespace.contains('\n'),
+            Token::Comment(comment) => {
+                let attach = if is_own_line { Attach::Before } else { Attach::After };
+                let index = if is_own_line { written.len() } else { written.len() - 1 };
+                comments.push((attach, index, comment.trim_end()));
+                is_own_line = true;
+            },
+            Token::Eof => (),
+            token => {
+                if !is_paren(token) {
+                    written.push(token.clone());
+                }
+                is_own_line = false;
+            },
+        }
+    }
+    if comments.is_empty() {
+        return Ok(item);
+    }
+    let formatted = lexer::lex(item.clone())?.into_iter().filter(|spanned| !is_paren(&spanned.token) && spanned.token != Token::Eof).collect::<Vec<_>>();
+    if formatted.iter().map(|spanned| &spanned.token).ne(written.iter()) {
+        return Ok(format!("{}\n", &code[span.start..span.end]));
+    }
+
+    let mut out = String::new();
+    let mut prev_end = 0;
+    for (attach, index, comment) in comments {
+        match attach {
+            Attach::Before => {
+                let at = formatted[index].span.start;
+                out += &item[prev_end..at];
+                prev_end = at;
+                if !current_line(&out).trim().is_empty() {
+                    let indent = indent_of(current_line(&out)) + printer::INDENT;
+                    out.truncate(out.trim_end().len());
+                    out += &format!("\n{}", " ".repeat(indent));
+                }
+                let indent = indent_of(current_line(&out));
+                out += &format!("{}\n{}", comment, " ".repeat(indent));
+            },
+            Attach::After => {
+                let at = formatted[index].span.end;
+                out += &item[prev_end..at];
+                out += &format!(" {}", comment);
+                let rest = item[at..].trim_start_matches(' ');
+                prev_end = item.len() - rest.len();
+                if !rest.starts_with('\n') {
+                    let indent = indent_of(current_line(&out)) + printer::INDENT;
+                    out += &format!("\n{}", " ".repeat(indent));
+                }
+            },
+        }
+    }
+    out += &item[prev_end..];
+    Ok(out)
+}
+
+fn current_line(out: &str) -> &str {
+    out.rfind('\n').map_or(out, |i| &out[i + 1..])
+}
+
+fn indent_of(line: &str) -> usize {
+    line.len() - line.trim_start_matches(' ').len()
+}
+
+fn is_paren(token: &Token) -> bool {
+    matches!(token, Token::LParen | Token::RParen)
+}
+
+/// The module declaration is not an AST node of its own, so its span is found in the tokens.
+fn module_decl_span(tokens: &[SpannedToken]) -> Span {
+    let start = tokens.iter().find(|spanned| spanned.token == Token::Module).map_or(0, |spanned| spanned.span.start);
+    let end = tokens.iter().find(|spanned| spanned.token == Token::Semicolon && spanned.span.start >= start).map_or(start, |spanned| spanned.span.end);
+    Span::new(start, end)
+}
+
+/// Writes the comments and blank lines found between `start` and `end`.
+/// A comment on the same line as the previous item stays at the end of that line.
+fn trivia(out: &mut String, tokens: &[SpannedToken], prev_end: Option<usize>, end: usize) {
+    let start = prev_end.unwrap_or(0);
+    let mut newlines = 0;
+    let mut is_same_line = prev_end.is_some();
+    for spanned in tokens.iter().filter(|spanned| start <= spanned.span.start && spanned.span.end <= end && spanned.span.start < spanned.span.end) {
+        match &spanned.token {
+            Token::Whitespace(whitespace) => {
+                newlines += whitespace.matches('\n').count();
+                is_same_line &= newlines == 0;
+            },
+            Token::Comment(comment) => {
+                if is_same_line {
+                    out.pop();
+                    *out += " ";
+                }
+                else if newlines >= 2 && !out.is_empty() {
+                    *out += "\n";
+                }
+                *out += comment.trim_end();
+                *out += "\n";
+                newlines = 0;
+                is_same_line = false;
+            },
+            _ => (),
+        }
+    }
+    if newlines >= 2 && !out.is_empty() && end < tokens.last().map_or(0, |spanned| spanned.span.start) {
+        *out += "\n";
+    }
+}
@@ -0,0 +1,75 @@
+use std::{
+    env,
+    fs,
+    process,
+};
+use super::Options;
+
+fn format(code: &str) -> String {
+    super::format(code, &Options::default()).unwrap()
+}
+
+fn format_within(code: &str, width: usize) -> String {
+    super::format(code, &Options { width }).unwrap()
+}
+
+#[test]
+fn format_items() {
+    assert_eq!(format("module  M ;import A;f x=x*2+1"), "module M;\nimport A;\nf x = x * 2 + 1;\n");
+    assert_eq!(format("pub f:i32->i32;f x=x;\n\n\n\ng=f 1;\n\n"), "pub f : i32 -> i32;\nf x = x;\n\ng = f 1;\n");
+    assert_eq!(format("f = 1; extern \"C\" abs : i32 -> i32; g = 2;"), "f = 1;\nextern \"C\" abs : i32 -> i32;\ng = 2;\n");
+    assert_eq!(format(""), "");
+    assert!(super::format("f = ;", &Options::default()).is_err());
+}
+
+#[test]
+fn format_comments() {
+    assert_eq!(format("// head\n\n// f\nf = 1;  // one   \n// g\ng = 2;\n\n// tail\n"), "// head\n\n// f\nf = 1; // one\n// g\ng = 2;\n\n// tail\n");
+    assert_eq!(format("f x = x +\n  // own line\n  1;\ng=2;"), "f x = x +\n    // own line\n    1;\ng = 2;\n");
+    assert_eq!(format("f x=x+ // trailing\n1*2;"), "f x = x + // trailing\n    1 * 2;\n");
+    assert_eq!(format("f x=((x+1))* // a\n// b\n2;"), "f x = (x + 1) * // a\n    // b\n    2;\n");
+    assert_eq!(format("pub f:i32->i32; // sig\nf x=\n// body\nx;"), "pub f : i32 -> i32; // sig\nf x =\n    // body\n    x;\n");
+    assert_eq!(
+        format_within("f = [aaaa, // first\nbbbb + cccc, dddd];", 20),
+        "f =\n    [\n        aaaa, // first\n        bbbb + cccc,\n        dddd\n    ];\n"
+    );
+    assert_eq!(format("// only"), "// only\n");
+    assert_eq!(format("f = 1 // no semicolon"), "f = 1; // no semicolon\n");
+}
+
+#[test]
+fn format_width() {
+    assert_eq!(
+        format_within("f = g aaaa bbbb (h cccc dddd);", 24),
+        "f =\n    g\n        aaaa\n        bbbb\n        (h cccc dddd);\n"
+    );
+    assert_eq!(
+        format_within("f = aaaa + bbbb * cccc - dddd;", 24),
+        "f =\n    aaaa\n        + bbbb * cccc\n        - dddd;\n"
+    );
+    assert_eq!(
+        format_within("f = aaaa :: bbbb :: cccc :: [];", 20),
+        "f =\n    aaaa\n        :: bbbb\n        :: cccc\n        :: [];\n"
+    );
+    assert_eq!(
+        format_within("f = [aaaa, bbbb + cccc, dddd];", 20),
+        "f =\n    [\n        aaaa,\n        bbbb + cccc,\n        dddd\n    ];\n"
+    );
+    assert_eq!(format_within("f = -(aaaa + bbbb);", 12), "f =\n    -(aaaa\n        + bbbb);\n");
+    assert_eq!(format_within("f = aaaa + bbbb;", 16), "f = aaaa + bbbb;\n");
+}
+
+#[test]
+fn format_file_check() {
+    let path = env::temp_dir().join(format!("akanec_formatter_check_{}.akane", process::id()));
+    let path_str = path.to_str().unwrap();
+    fs::write(&path, "f x=x;").unwrap();
+    assert!(!super::format_file(path_str, &Options::default(), true).unwrap());
+    assert_eq!(fs::read_to_string(&path).unwrap(), "f x=x;");
+    assert!(!super::format_file(path_str, &Options::default(), false).unwrap());
+    assert_eq!(fs::read_to_string(&path).unwrap(), "f x = x;\n");
+    assert!(super::format_file(path_str, &Options::default(), true).unwrap());
+    fs::remove_file(&path).unwrap();
+}
+
+
@@ -0,0 +1,195 @@
+#[cfg(test)]
+mod tests;
+
+use std::collections::HashMap;
+use anyhow::Result;
+use crate::{
+    data::{
+        ast::{
+            ModuleAst,
+            FnDefAst,
+            ExprAst,
+            TyAst,
+        },
+        span::Span,
+        token::{
+            Token,
+            SpannedToken,
+        },
+    },
+    lexer,
+    parser,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Class {
+    /// The name of a defined or declared function.
+    FnDef,
+    /// A parameter, where it is defined and where it is used.
+    Param,
+    /// A function named in an expression, whether applied or passed around.
+    Call,
+    Ty,
+    Module,
+    Keyword,
+    Op,
+    Num,
+    Str,
+    Char,
+    Comment,
+}
+
+impl Class {
+    /// Also the CSS class of the HTML output.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Class::FnDef => "fn-def",
+            Class::Param => "param",
+            Class::Call => "call",
+            Class::Ty => "ty",
+            Class::Module => "module",
+            Class::Keyword => "keyword",
+            Class::Op => "op",
+            Class::Num => "num",
+            Class::Str => "str",
+            Class::Char => "char",
+            Class::Comment => "comment",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Highlight {
+    pub class: Class,
+    pub span: Span,
+}
+
+/// Classifies the ranges of `code` in source order; whitespace and punctuation are left out.
+/// Identifiers are classified by the AST, so while `code` does not parse,
+/// only declared names are told apart from the others, which are left out too.
+pub fn highlight(code: &str) -> Result<Vec<Highlight>> {
+    let tokens = lexer::lex_lossless(code.to_owned())?;
+    let mut idents = HashMap::new();
+    if let Ok(module_ast) = parser::parse(tokens.iter().filter(|spanned| !spanned.token.is_trivia()).cloned().collect()) {
+        classify_module(&module_ast, &mut idents);
+    }
+    let mut highlights = Vec::new();
+    for (i, spanned) in tokens.iter().enumerate() {
+        let class = match &spanned.token {
+            Token::Module | Token::Import | Token::Pub | Token::Extern => Class::Keyword,
+            Token::Ident(_) =>
+                match idents.get(&spanned.span.start) {
+                    Some(class) => *class,
+                    None if is_declared(&tokens[i + 1..]) => Class::FnDef,
+                    None => continue,
+                },
+            Token::Num(_) => Class::Num,
+            Token::Str(_) => Class::Str,
+            Token::Char(_) => Class::Char,
+            Token::OpCode(_) | Token::Equal | Token::Colon => Class::Op,
+            Token::Comment(_) => Class::Comment,
+            _ => continue,
+        };
+        if spanned.span.start < spanned.span.end {
+            highlights.push(Highlight { class, span: spanned.span });
+        }
+    }
+    Ok(highlights)
+}
+
+/// Renders `code` as a `pre` element, every classified range in a `span` of its class.
+pub fn html(code: &str) -> Result<String> {
+    let mut out = "<pre class=\"akane\"><code>".to_owned();
+    let mut end = 0;
+    for highlight in highlight(code)? {
+        escape(&mut out, &code[end..highlight.span.start]);
+        out += &format!("<span class=\"{}\">", highlight.class.name());
+        escape(&mut out, &code[highlight.span.start..highlight.span.end]);
+        out += "</span>";
+        end = highlight.span.end;
+    }
+    escape(&mut out, &code[end..]);
+    out += "</code></pre>\n";
+    Ok(out)
+}
+
+fn escape(out: &mut String, s: &str) {
+    for c in s.chars() {
+        match c {
+            '&' => *out += "&amp;",
+            '<' => *out += "&lt;",
+            '>' => *out += "&gt;",
+            '"' => *out += "&quot;",
+            c => out.push(c),
+        }
+    }
+}
+
+/// A name followed by a colon starts a type annotation or an extern declaration.
+fn is_declared(rest: &[SpannedToken]) -> bool {
+    rest.iter().find(|spanned| !spanned.token.is_trivia()).is_some_and(|spanned| spanned.token == Token::Colon)
+}
+
+fn classify_module(module_ast: &ModuleAst, idents: &mut HashMap<usize, Class>) {
+    if let Some(name) = &module_ast.name {
+        idents.insert(name.span.start, Class::Module);
+    }
+    for import_ast in &module_ast.imports {
+        idents.insert(import_ast.module.span.start, Class::Module);
+    }
+    for extern_fn_ast in &module_ast.extern_fns {
+        idents.insert(extern_fn_ast.ident.span.start, Class::FnDef);
+        classify_ty(&extern_fn_ast.ty, idents);
+    }
+    for fn_def_ast in &module_ast.fn_defs {
+        classify_fn_def(fn_def_ast, idents);
+    }
+}
+
+fn classify_fn_def(fn_def_ast: &FnDefAst, idents: &mut HashMap<usize, Class>) {
+    if let Some(ty_annot) = &fn_def_ast.ty_annot {
+        classify_ty(ty_annot, idents);
+    }
+    idents.insert(fn_def_ast.left_def.ident.span.start, Class::FnDef);
+    for arg in &fn_def_ast.left_def.args {
+        idents.insert(arg.span.start, Class::Param);
+    }
+    classify_expr(&fn_def_ast.expr, fn_def_ast, idents);
+}
+
+fn classify_expr(expr_ast: &ExprAst, fn_def_ast: &FnDefAst, idents: &mut HashMap<usize, Class>) {
+    match expr_ast {
+        ExprAst::Fn(fn_ast) => {
+            classify_expr(&fn_ast.fn_expr, fn_def_ast, idents);
+            classify_expr(&fn_ast.arg_expr, fn_def_ast, idents);
+        },
+        ExprAst::PrefixOp(prefix_op_ast) =>
+            classify_expr(&prefix_op_ast.rhs, fn_def_ast, idents),
+        ExprAst::InfixOp(infix_op_ast) => {
+            classify_expr(&infix_op_ast.lhs, fn_def_ast, idents);
+            classify_expr(&infix_op_ast.rhs, fn_def_ast, idents);
+        },
+        ExprAst::Ident(ident_ast) => {
+            let is_param = fn_def_ast.left_def.args.iter().any(|arg| arg.name == ident_ast.name);
+            idents.insert(ident_ast.span.start, if is_param { Class::Param } else { Class::Call });
+        },
+        ExprAst::List(list_ast) =>
+            for elem in &list_ast.elems {
+                classify_expr(elem, fn_def_ast, idents);
+            },
+        ExprAst::Num(_) | ExprAst::Str(_) | ExprAst::Char(_) => (),
+    }
+}
+
+fn classify_ty(ty_ast: &TyAst, idents: &mut HashMap<usize, Class>) {
+    match ty_ast {
+        TyAst::Fn(fn_ty_ast) => {
+            classify_ty(&fn_ty_ast.lhs, idents);
+            classify_ty(&fn_ty_ast.rhs, idents);
+        },
+        TyAst::List(list_ty_ast) => classify_ty(&list_ty_ast.elem, idents),
+        TyAst::Ident(ident_ast) => {
+            idents.insert(ident_ast.span.start, Class::Ty);
+        },
+    }
+}
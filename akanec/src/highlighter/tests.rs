@@ -0,0 +1,76 @@
+use super::Class;
+
+fn classes(code: &str) -> Vec<(&str, Class)> {
+    super::highlight(code).unwrap().into_iter().map(|highlight| (&code[highlight.span.start..highlight.span.end], highlight.class)).collect()
+}
+
+#[test]
+fn highlight_items() {
+    assert_eq!(
+        classes("module M;\nimport Math;\nextern \"C\" abs : i32 -> i32;"),
+        vec![
+            ("module", Class::Keyword),
+            ("M", Class::Module),
+            ("import", Class::Keyword),
+            ("Math", Class::Module),
+            ("extern", Class::Keyword),
+            ("\"C\"", Class::Str),
+            ("abs", Class::FnDef),
+            (":", Class::Op),
+            ("i32", Class::Ty),
+            ("->", Class::Op),
+            ("i32", Class::Ty),
+        ]
+    );
+}
+
+#[test]
+fn highlight_fn_def() {
+    assert_eq!(
+        classes("// twice\npub f : [i32] -> i32;\nf x = g x (Math.h 1) + length ['a', \"b\"]; // done"),
+        vec![
+            ("// twice", Class::Comment),
+            ("pub", Class::Keyword),
+            ("f", Class::FnDef),
+            (":", Class::Op),
+            ("i32", Class::Ty),
+            ("->", Class::Op),
+            ("i32", Class::Ty),
+            ("f", Class::FnDef),
+            ("x", Class::Param),
+            ("=", Class::Op),
+            ("g", Class::Call),
+            ("x", Class::Param),
+            ("Math.h", Class::Call),
+            ("1", Class::Num),
+            ("+", Class::Op),
+            ("length", Class::Call),
+            ("'a'", Class::Char),
+            ("\"b\"", Class::Str),
+            ("// done", Class::Comment),
+        ]
+    );
+}
+
+#[test]
+fn highlight_unparsed() {
+    assert_eq!(
+        classes("f : i32;\nf x = x +"),
+        vec![
+            ("f", Class::FnDef),
+            (":", Class::Op),
+            ("=", Class::Op),
+            ("+", Class::Op),
+        ]
+    );
+    assert!(super::highlight("f = \"").is_err());
+}
+
+#[test]
+fn highlight_html() {
+    assert_eq!(
+        super::html("f x = x < \"&\";\n").unwrap(),
+        "<pre class=\"akane\"><code><span class=\"fn-def\">f</span> <span class=\"param\">x</span> <span class=\"op\">=</span> \
+         <span class=\"param\">x</span> <span class=\"op\">&lt;</span> <span class=\"str\">&quot;&amp;&quot;</span>;\n</code></pre>\n"
+    );
+}
@@ -0,0 +1,350 @@
+#[cfg(test)]
+mod tests;
+
+use std::{
+    collections::HashMap,
+    rc::Rc,
+};
+use anyhow::{
+    bail,
+    Result,
+};
+use crate::data::{
+    ast::{
+        ModuleAst,
+        FnDefAst,
+        ExprAst,
+        FnAst,
+        PrefixOpAst,
+        InfixOpAst,
+        IdentAst,
+        NumAst,
+        ListAst,
+    },
+    builtins::builtin_arity,
+    ty::FnTy,
+    value::{
+        Value,
+        num_value,
+        neg_num_value,
+        checked_div,
+    },
+};
+
+/// The default depth of calls past which a call fails, low enough for the 2 MiB stack of a test thread.
+pub const MAX_DEPTH: usize = 200;
+
+#[derive(Clone, Debug, PartialEq)]
+enum Object {
+    Value(Value),
+    Closure(Closure),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Closure {
+    name: String,
+    args: Vec<Object>,
+}
+
+type Env = HashMap<String, Object>;
+
+pub struct Interp {
+    fn_defs: HashMap<String, Rc<FnDefAst>>,
+    extern_fns: HashMap<String, FnTy>,
+    depth: usize,
+    max_depth: usize,
+}
+
+impl Interp {
+    pub fn new(module_ast: &ModuleAst) -> Result<Self> {
+        let mut extern_fns = HashMap::new();
+        for extern_fn_ast in &module_ast.extern_fns {
+            extern_fns.insert(extern_fn_ast.ident.name.clone(), FnTy::from_ty_ast(&extern_fn_ast.ty)?);
+        }
+        let mut fn_defs = HashMap::new();
+        for fn_def_ast in &module_ast.fn_defs {
+            let name = &fn_def_ast.left_def.ident.name;
+            FnTy::from_ty_annot(fn_def_ast.ty_annot.as_ref(), fn_def_ast.left_def.args.len())?;
+            if extern_fns.contains_key(name) || fn_defs.insert(name.clone(), Rc::new(fn_def_ast.clone())).is_some() {
+                bail!("Function cannot be redefined.");
+            }
+        }
+        Ok(Self { fn_defs, extern_fns, depth: 0, max_depth: MAX_DEPTH })
+    }
+
+    /// Each call takes several kilobytes of native stack, so a depth past `MAX_DEPTH`
+    /// needs a thread with a stack to match.
+    pub fn set_max_depth(&mut self, max_depth: usize) {
+        self.max_depth = max_depth;
+    }
+
+    pub fn fn_ty(&self, name: &str) -> Result<FnTy> {
+        if let Some(fn_def_ast) = self.fn_defs.get(name) {
+            FnTy::from_ty_annot(fn_def_ast.ty_annot.as_ref(), fn_def_ast.left_def.args.len())
+        }
+        else if let Some(fn_ty) = self.extern_fns.get(name) {
+            Ok(fn_ty.clone())
+        }
+        else {
+            bail!("Unknown function.");
+        }
+    }
+
+    pub fn call(&mut self, name: &str, args: &[Value]) -> Result<Value> {
+        let arity = match self.fn_defs.get(name) {
+            Some(fn_def_ast) => fn_def_ast.left_def.args.len(),
+            None if self.extern_fns.contains_key(name) => bail!("Extern functions cannot be interpreted: {}", name),
+            None => bail!("Unknown function."),
+        };
+        if args.len() != arity {
+            bail!("Invalid count of arguments.");
+        }
+        let args = args.iter().cloned().map(Object::Value).collect();
+        into_value(self.invoke(name, args)?)
+    }
+
+    pub fn eval(&mut self, expr_ast: &ExprAst) -> Result<Value> {
+        let object = self.eval_expr(&Env::new(), expr_ast)?;
+        into_value(object)
+    }
+
+    fn eval_expr(&mut self, env: &Env, expr_ast: &ExprAst) -> Result<Object> {
+        match expr_ast {
+            ExprAst::Fn(fn_ast) =>
+                self.eval_fn(env, fn_ast),
+            ExprAst::PrefixOp(prefix_op_ast) =>
+                self.eval_prefix_op(env, prefix_op_ast),
+            ExprAst::InfixOp(infix_op_ast) =>
+                self.eval_infix_op(env, infix_op_ast),
+            ExprAst::Ident(ident_ast) =>
+                self.eval_ident(env, ident_ast),
+            ExprAst::Num(num_ast) =>
+                eval_num(num_ast),
+            ExprAst::Str(str_ast) =>
+                Ok(Object::Value(Value::Str(str_ast.value.clone()))),
+            ExprAst::Char(char_ast) =>
+                Ok(Object::Value(Value::I32(char_ast.value as i32))),
+            ExprAst::List(list_ast) =>
+                self.eval_list(env, list_ast),
+        }
+    }
+
+    fn eval_fn(&mut self, env: &Env, fn_ast: &FnAst) -> Result<Object> {
+        let f = self.eval_expr(env, fn_ast.fn_expr.as_ref())?;
+        let arg = self.eval_expr(env, fn_ast.arg_expr.as_ref())?;
+        self.apply(f, arg)
+    }
+
+    fn eval_prefix_op(&mut self, env: &Env, prefix_op_ast: &PrefixOpAst) -> Result<Object> {
+        if let ("-", ExprAst::Num(num_ast)) = (prefix_op_ast.op_code.as_str(), prefix_op_ast.rhs.as_ref()) {
+            return Ok(Object::Value(Value::I32(neg_num_value(num_ast)?)));
+        }
+        let rhs = self.eval_expr(env, prefix_op_ast.rhs.as_ref())?;
+        match (prefix_op_ast.op_code.as_str(), rhs) {
+            ("-", Object::Value(Value::I32(n))) =>
+                Ok(Object::Value(Value::I32(n.wrapping_neg()))),
+            ("-", _) =>
+                bail!("Type mismatch."),
+            _ => bail!("Invalid operator."),
+        }
+    }
+
+    fn eval_infix_op(&mut self, env: &Env, infix_op_ast: &InfixOpAst) -> Result<Object> {
+        let op_code = infix_op_ast.op_code.as_str();
+        if let "&&" | "||" = op_code {
+            let lhs = as_bool(self.eval_expr(env, infix_op_ast.lhs.as_ref())?)?;
+            if lhs == (op_code == "||") {
+                return Ok(Object::Value(Value::I32(lhs as i32)));
+            }
+            let rhs = as_bool(self.eval_expr(env, infix_op_ast.rhs.as_ref())?)?;
+            return Ok(Object::Value(Value::I32(rhs as i32)));
+        }
+        let lhs = into_value(self.eval_expr(env, infix_op_ast.lhs.as_ref())?)?;
+        let rhs = into_value(self.eval_expr(env, infix_op_ast.rhs.as_ref())?)?;
+        let value = match (op_code, lhs, rhs) {
+            ("+", Value::I32(lhs), Value::I32(rhs)) =>
+                Value::I32(lhs.wrapping_add(rhs)),
+            ("-", Value::I32(lhs), Value::I32(rhs)) =>
+                Value::I32(lhs.wrapping_sub(rhs)),
+            ("*", Value::I32(lhs), Value::I32(rhs)) =>
+                Value::I32(lhs.wrapping_mul(rhs)),
+            ("/", Value::I32(lhs), Value::I32(rhs)) =>
+                Value::I32(checked_div(lhs, rhs, i32::checked_div)?),
+            ("%", Value::I32(lhs), Value::I32(rhs)) =>
+                Value::I32(checked_div(lhs, rhs, i32::checked_rem)?),
+            ("==", Value::I32(lhs), Value::I32(rhs)) =>
+                Value::I32((lhs == rhs) as i32),
+            ("!=", Value::I32(lhs), Value::I32(rhs)) =>
+                Value::I32((lhs != rhs) as i32),
+            ("<", Value::I32(lhs), Value::I32(rhs)) =>
+                Value::I32((lhs < rhs) as i32),
+            ("<=", Value::I32(lhs), Value::I32(rhs)) =>
+                Value::I32((lhs <= rhs) as i32),
+            (">", Value::I32(lhs), Value::I32(rhs)) =>
+                Value::I32((lhs > rhs) as i32),
+            (">=", Value::I32(lhs), Value::I32(rhs)) =>
+                Value::I32((lhs >= rhs) as i32),
+            ("==", Value::Str(lhs), Value::Str(rhs)) =>
+                Value::I32((lhs == rhs) as i32),
+            ("!=", Value::Str(lhs), Value::Str(rhs)) =>
+                Value::I32((lhs != rhs) as i32),
+            ("++", Value::Str(lhs), Value::Str(rhs)) =>
+                Value::Str(lhs + &rhs),
+            ("++", Value::List(lhs), Value::List(rhs)) =>
+                Value::List([lhs, rhs].concat()),
+            ("::", Value::I32(x), Value::List(xs)) =>
+                Value::List([vec![x], xs].concat()),
+            ("!!", Value::List(xs), Value::I32(index)) =>
+                match usize::try_from(index).ok().and_then(|index| xs.get(index)) {
+                    Some(x) => Value::I32(*x),
+                    None => bail!("Index out of bounds."),
+                },
+            ("+" | "-" | "*" | "/" | "%" | "==" | "!=" | "<" | "<=" | ">" | ">=" | "++" | "::" | "!!", _, _) =>
+                bail!("Type mismatch."),
+            _ => bail!("Invalid operator."),
+        };
+        Ok(Object::Value(value))
+    }
+
+    fn eval_ident(&mut self, env: &Env, ident_ast: &IdentAst) -> Result<Object> {
+        let name = &ident_ast.name;
+        if let Some(object) = env.get(name) {
+            Ok(object.clone())
+        }
+        else if let Some(fn_def_ast) = self.fn_defs.get(name) {
+            if fn_def_ast.left_def.args.is_empty() {
+                self.invoke(name, Vec::new())
+            }
+            else {
+                Ok(Object::Closure(Closure { name: name.clone(), args: Vec::new() }))
+            }
+        }
+        else if self.extern_fns.contains_key(name) {
+            bail!("Extern functions cannot be interpreted: {}", name);
+        }
+        else if builtin_arity(name).is_some() {
+            Ok(Object::Closure(Closure { name: name.clone(), args: Vec::new() }))
+        }
+        else {
+            bail!("Unknown identifier.");
+        }
+    }
+
+    fn eval_list(&mut self, env: &Env, list_ast: &ListAst) -> Result<Object> {
+        let mut xs = Vec::new();
+        for elem in &list_ast.elems {
+            match self.eval_expr(env, elem)? {
+                Object::Value(Value::I32(x)) => xs.push(x),
+                _ => bail!("Only lists of i32 are supported."),
+            }
+        }
+        Ok(Object::Value(Value::List(xs)))
+    }
+
+    fn apply(&mut self, f: Object, arg: Object) -> Result<Object> {
+        let mut closure = match f {
+            Object::Closure(closure) => closure,
+            Object::Value(_) => bail!("Not a function."),
+        };
+        closure.args.push(arg);
+        if closure.args.len() == self.arity(&closure.name) {
+            self.invoke(&closure.name, closure.args)
+        }
+        else {
+            Ok(Object::Closure(closure))
+        }
+    }
+
+    fn arity(&self, name: &str) -> usize {
+        match self.fn_defs.get(name) {
+            Some(fn_def_ast) => fn_def_ast.left_def.args.len(),
+            None => builtin_arity(name).unwrap(),
+        }
+    }
+
+    fn invoke(&mut self, name: &str, args: Vec<Object>) -> Result<Object> {
+        let fn_def_ast = match self.fn_defs.get(name) {
+            Some(fn_def_ast) => fn_def_ast.clone(),
+            None => return self.invoke_builtin(name, args),
+        };
+        if self.depth == self.max_depth {
+            bail!("Stack overflow.");
+        }
+        let mut env = Env::new();
+        for (ident, arg) in fn_def_ast.left_def.args.iter().zip(args) {
+            if env.insert(ident.name.clone(), arg).is_some() {
+                bail!("Duplicate identifier name.");
+            }
+        }
+        self.depth += 1;
+        let result = self.eval_expr(&env, &fn_def_ast.expr);
+        self.depth -= 1;
+        result
+    }
+
+    fn invoke_builtin(&mut self, name: &str, args: Vec<Object>) -> Result<Object> {
+        let mut args = args.into_iter();
+        let value = match (name, args.next(), args.next(), args.next()) {
+            ("length", Some(Object::Value(Value::Str(s))), None, None) =>
+                Value::I32(s.len() as i32),
+            ("length", Some(Object::Value(Value::List(xs))), None, None) =>
+                Value::I32(xs.len() as i32),
+            ("map", Some(f @ Object::Closure(_)), Some(Object::Value(Value::List(xs))), None) => {
+                let mut ys = Vec::new();
+                for x in xs {
+                    ys.push(self.apply_i32(&f, &[x])?);
+                }
+                Value::List(ys)
+            },
+            ("filter", Some(f @ Object::Closure(_)), Some(Object::Value(Value::List(xs))), None) => {
+                let mut ys = Vec::new();
+                for x in xs {
+                    if self.apply_i32(&f, &[x])? != 0 {
+                        ys.push(x);
+                    }
+                }
+                Value::List(ys)
+            },
+            ("fold", Some(f @ Object::Closure(_)), Some(Object::Value(Value::I32(init))), Some(Object::Value(Value::List(xs)))) => {
+                let mut acc = init;
+                for x in xs {
+                    acc = self.apply_i32(&f, &[acc, x])?;
+                }
+                Value::I32(acc)
+            },
+            ("c_str", _, _, _) =>
+                bail!("Pointers cannot be interpreted."),
+            _ => bail!("Type mismatch."),
+        };
+        Ok(Object::Value(value))
+    }
+
+    fn apply_i32(&mut self, f: &Object, args: &[i32]) -> Result<i32> {
+        let mut result = f.clone();
+        for arg in args {
+            result = self.apply(result, Object::Value(Value::I32(*arg)))?;
+        }
+        match result {
+            Object::Value(Value::I32(n)) => Ok(n),
+            _ => bail!("Type mismatch."),
+        }
+    }
+}
+
+fn eval_num(num_ast: &NumAst) -> Result<Object> {
+    Ok(Object::Value(Value::I32(num_value(num_ast)?)))
+}
+
+fn as_bool(object: Object) -> Result<bool> {
+    match object {
+        Object::Value(Value::I32(n)) => Ok(n != 0),
+        _ => bail!("Type mismatch."),
+    }
+}
+
+fn into_value(object: Object) -> Result<Value> {
+    match object {
+        Object::Value(value) => Ok(value),
+        Object::Closure(closure) => bail!("Function value cannot be returned: {}", closure.name),
+    }
+}
@@ -0,0 +1,138 @@
+use crate::{
+    data::value::Value,
+    lexer,
+    parser,
+};
+use super::{
+    Interp,
+    MAX_DEPTH,
+};
+
+fn interp(code: &str) -> Interp {
+    let module_ast = parser::parse(lexer::lex(code.to_owned()).unwrap()).unwrap();
+    Interp::new(&module_ast).unwrap()
+}
+
+fn eval(interp: &mut Interp, code: &str) -> Value {
+    interp.eval(&parser::parse_expr(lexer::lex(code.to_owned()).unwrap()).unwrap()).unwrap()
+}
+
+fn str(s: &str) -> Value {
+    Value::Str(s.to_owned())
+}
+
+#[test]
+fn eval_arithmetic() {
+    let mut interp = interp("");
+    assert_eq!(eval(&mut interp, "1 + 2 * 3"), Value::I32(7));
+    assert_eq!(eval(&mut interp, "(7 - 2) * 3 / 2 % 5"), Value::I32(2));
+    assert_eq!(eval(&mut interp, "-7 / 2"), Value::I32(-3));
+    assert_eq!(eval(&mut interp, "-7 % 2"), Value::I32(-1));
+    assert_eq!(eval(&mut interp, "2147483647 + 1"), Value::I32(i32::MIN));
+    assert_eq!(eval(&mut interp, "1 < 2"), Value::I32(1));
+    assert_eq!(eval(&mut interp, "2 <= 1"), Value::I32(0));
+    assert_eq!(eval(&mut interp, "'a'"), Value::I32(97));
+}
+
+#[test]
+fn eval_str_and_list() {
+    let mut interp = interp("");
+    assert_eq!(eval(&mut interp, r#""Hello, " ++ "world""#), str("Hello, world"));
+    assert_eq!(eval(&mut interp, r#""a" == "a""#), Value::I32(1));
+    assert_eq!(eval(&mut interp, r#"length "あ""#), Value::I32(3));
+    assert_eq!(eval(&mut interp, "1 :: [2] ++ [3]"), Value::List(vec![1, 2, 3]));
+    assert_eq!(eval(&mut interp, "[10, 20] !! 1"), Value::I32(20));
+}
+
+#[test]
+fn eval_logical() {
+    let mut interp = interp("loop x = loop x;");
+    assert_eq!(eval(&mut interp, "0 && loop 1"), Value::I32(0));
+    assert_eq!(eval(&mut interp, "2 || loop 1"), Value::I32(1));
+    assert_eq!(eval(&mut interp, "2 && 3"), Value::I32(1));
+    assert_eq!(eval(&mut interp, "0 || 0"), Value::I32(0));
+}
+
+#[test]
+fn call_fn() {
+    let mut interp = interp(r#"
+        add a b = a + b;
+        two = add 1 1;
+        greet : String -> String;
+        greet name = "Hello, " ++ name ++ "!";
+    "#);
+    assert_eq!(interp.call("add", &[Value::I32(1), Value::I32(-3)]).unwrap(), Value::I32(-2));
+    assert_eq!(interp.call("two", &[]).unwrap(), Value::I32(2));
+    assert_eq!(interp.call("greet", &[str("あかね")]).unwrap(), str("Hello, あかね!"));
+}
+
+#[test]
+fn call_curried() {
+    let mut interp = interp(r#"
+        add a b = a + b;
+        apply : (i32 -> i32) -> i32 -> i32;
+        apply f x = f x;
+        f xs = map (add 10) (filter (apply (add 0)) xs);
+        sum xs = fold add 0 xs;
+    "#);
+    assert_eq!(eval(&mut interp, "apply (add 1) 2"), Value::I32(3));
+    assert_eq!(interp.call("f", &[Value::List(vec![1, 0, 2])]).unwrap(), Value::List(vec![11, 12]));
+    assert_eq!(interp.call("sum", &[Value::List(vec![1, 2, 3])]).unwrap(), Value::I32(6));
+}
+
+#[test]
+fn call_recursive() {
+    let mut interp = interp(r#"
+        even n = n == 0 || odd (n - 1);
+        odd n = n != 0 && even (n - 1);
+        count n = n <= 0 || count (n - 1);
+    "#);
+    assert_eq!(interp.call("even", &[Value::I32(10)]).unwrap(), Value::I32(1));
+    assert_eq!(interp.call("odd", &[Value::I32(7)]).unwrap(), Value::I32(1));
+    assert_eq!(interp.call("count", &[Value::I32(MAX_DEPTH as i32 - 1)]).unwrap(), Value::I32(1));
+    assert_eq!(interp.call("count", &[Value::I32(MAX_DEPTH as i32)]).unwrap_err().to_string(), "Stack overflow.");
+    interp.set_max_depth(50);
+    assert_eq!(interp.call("count", &[Value::I32(49)]).unwrap(), Value::I32(1));
+    assert_eq!(interp.call("count", &[Value::I32(50)]).unwrap_err().to_string(), "Stack overflow.");
+}
+
+#[test]
+fn builtin_shadowed() {
+    let mut interp = interp("length xs = 42; f length = length + 1;");
+    assert_eq!(eval(&mut interp, "length [1]"), Value::I32(42));
+    assert_eq!(interp.call("f", &[Value::I32(1)]).unwrap(), Value::I32(2));
+}
+
+#[test]
+fn eval_error() {
+    let mut interp = interp(r#"
+        add a b = a + b;
+        extern "C" abs : i32 -> i32;
+        f x x = x;
+    "#);
+    let mut eval_err = |code: &str| {
+        interp.eval(&parser::parse_expr(lexer::lex(code.to_owned()).unwrap()).unwrap()).unwrap_err().to_string()
+    };
+    assert_eq!(eval_err("1 / 0"), "Division by zero.");
+    assert_eq!(eval_err("1 % 0"), "Division by zero.");
+    assert_eq!(eval_err("-2147483648 / (-1)"), "Integer overflow.");
+    assert_eq!(eval_err("2147483649"), "Integer literal out of range.");
+    assert_eq!(eval_err("2147483648"), "Integer literal out of range.");
+    assert_eq!(eval_err("2147483648 + 1"), "Integer literal out of range.");
+    assert_eq!(eval_err("-2147483649"), "Integer literal out of range.");
+    assert_eq!(eval_err("[1] !! 1"), "Index out of bounds.");
+    assert_eq!(eval_err(r#"1 + "a""#), "Type mismatch.");
+    assert_eq!(eval_err("1 2"), "Not a function.");
+    assert_eq!(eval_err("nothing"), "Unknown identifier.");
+    assert_eq!(eval_err("add 1"), "Function value cannot be returned: add");
+    assert_eq!(eval_err("abs 1"), "Extern functions cannot be interpreted: abs");
+    assert_eq!(eval_err("f 1 2"), "Duplicate identifier name.");
+    assert_eq!(interp.call("add", &[Value::I32(1)]).unwrap_err().to_string(), "Invalid count of arguments.");
+    assert_eq!(interp.call("nothing", &[]).unwrap_err().to_string(), "Unknown function.");
+}
+
+#[test]
+fn redefined() {
+    let module_ast = parser::parse(lexer::lex("f = 1; f = 2;".to_owned()).unwrap()).unwrap();
+    assert_eq!(Interp::new(&module_ast).err().unwrap().to_string(), "Function cannot be redefined.");
+}
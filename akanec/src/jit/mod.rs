@@ -0,0 +1,271 @@
+#[cfg(test)]
+mod tests;
+
+use std::{
+    collections::HashMap,
+    mem,
+    slice,
+};
+use anyhow::{
+    bail,
+    Result,
+};
+use llvm_sys::{
+    prelude::LLVMValueRef,
+    LLVMLinkage,
+};
+use crate::{
+    codegen,
+    compiler::{
+        self,
+        Options,
+    },
+    data::{
+        ast::ExprAst,
+        ty::{
+            Ty,
+            FnTy,
+        },
+        value::Value,
+    },
+    llvm::LLVM,
+};
+
+pub struct Jit {
+    llvm: LLVM,
+    /// The functions defined again in a later module, with the count of modules added by then,
+    /// which suffixes their symbols.
+    versions: HashMap<String, usize>,
+    module_count: usize,
+}
+
+impl Jit {
+    pub fn new(mut llvm: LLVM, opt_level: u32) -> Result<Self> {
+        llvm.create_jit(opt_level)?;
+        Ok(Self { llvm, versions: HashMap::new(), module_count: 0 })
+    }
+
+    pub fn fn_ty(&self, name: &str) -> Result<FnTy> {
+        self.llvm.get_fn_ty(name)
+    }
+
+    pub fn fn_tys(&self) -> &HashMap<String, FnTy> {
+        self.llvm.fn_tys()
+    }
+
+    pub fn get_function_address(&mut self, name: &str) -> Result<u64> {
+        let symbol = self.versioned(codegen::symbol_name(name), name);
+        self.llvm.get_function_address(&symbol)
+    }
+
+    /// Generates a module of its own with `gen` and adds it to the engine, so that functions
+    /// can be defined after the JIT is created. `defined` are the functions `gen` defines.
+    /// One defined before gets a symbol of its own, the code compiled before keeping the old one.
+    /// If anything fails, the JIT is left as it was.
+    pub fn add_module(&mut self, module_id: &str, defined: &[String], options: &Options, gen: impl FnOnce(&mut LLVM) -> Result<()>) -> Result<()> {
+        let fn_tys = self.llvm.fn_tys().clone();
+        let versions = self.versions.clone();
+        self.llvm.new_module(module_id)?;
+        let result = self.gen_module(defined, options, gen).and_then(|_| self.llvm.add_module());
+        if result.is_err() {
+            self.llvm.discard_module();
+            self.llvm.set_fn_tys(fn_tys);
+            self.versions = versions;
+        }
+        else {
+            self.module_count += 1;
+        }
+        result
+    }
+
+    fn gen_module(&mut self, defined: &[String], options: &Options, gen: impl FnOnce(&mut LLVM) -> Result<()>) -> Result<()> {
+        for name in defined {
+            if self.llvm.remove_fn_ty(name).is_some() {
+                self.versions.insert(name.clone(), self.module_count);
+            }
+        }
+        codegen::declare_fns(&mut self.llvm)?;
+        gen(&mut self.llvm)?;
+        for name in defined {
+            let fn_ty = self.fn_ty(name)?;
+            let f = self.llvm.get_named_function(codegen::symbol_name(name))?;
+            if LLVM::count_basic_blocks(f) != 0 {
+                LLVM::set_linkage(f, LLVMLinkage::LLVMExternalLinkage);
+            }
+            if is_supported(&fn_ty) {
+                gen_trampoline(&mut self.llvm, name, &fn_ty)?;
+            }
+        }
+        compiler::optimize(&mut self.llvm, options)?;
+        let mut versions = self.versions.iter().map(|(name, version)| (name.clone(), *version)).collect::<Vec<_>>();
+        versions.sort();
+        for (name, version) in versions {
+            for symbol in [codegen::symbol_name(&name).to_owned(), trampoline_name(&name)] {
+                if let Ok(f) = self.llvm.get_named_function(&symbol) {
+                    self.llvm.set_value_name(f, &format!("{}.{}", symbol, version));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Infers the type of an expression calling the functions compiled so far,
+    /// in a module that is thrown away.
+    pub fn infer_ty(&mut self, module_id: &str, expr_ast: &ExprAst) -> Result<Ty> {
+        self.llvm.new_module(module_id)?;
+        let result = codegen::declare_fns(&mut self.llvm).and_then(|_| codegen::infer_ty(&mut self.llvm, expr_ast));
+        self.llvm.discard_module();
+        result
+    }
+
+    fn versioned(&self, symbol: &str, name: &str) -> String {
+        match self.versions.get(name) {
+            Some(version) => format!("{}.{}", symbol, version),
+            None => symbol.to_owned(),
+        }
+    }
+
+    pub fn call(&mut self, name: &str, args: &[Value]) -> Result<Value> {
+        let fn_ty = self.fn_ty(name)?;
+        if !is_supported(&fn_ty) {
+            bail!("Unsupported function type: {}", fn_ty);
+        }
+        if args.len() != fn_ty.args.len() {
+            bail!("Invalid count of arguments.");
+        }
+        let mut buffers = Vec::new();
+        let mut slots = Vec::new();
+        for (arg, ty) in args.iter().zip(&fn_ty.args) {
+            if arg.ty() != *ty {
+                bail!("Type mismatch.");
+            }
+            slots.push(to_slot(arg, &mut buffers));
+        }
+        let address = self.llvm.get_function_address(&self.versioned(&trampoline_name(name), name))?;
+        let mut ret = 0i64;
+        unsafe {
+            let trampoline: extern "C" fn(*const i64, *mut i64) = mem::transmute(address as usize);
+            trampoline(slots.as_ptr(), &mut ret);
+            Ok(from_slot(ret, &fn_ty.ret))
+        }
+    }
+}
+
+pub fn gen_trampolines(llvm: &mut LLVM) -> Result<()> {
+    let mut fn_tys = llvm.fn_tys().iter().map(|(name, fn_ty)| (name.clone(), fn_ty.clone())).collect::<Vec<_>>();
+    fn_tys.sort_by(|(lhs, _), (rhs, _)| lhs.cmp(rhs));
+    for (name, fn_ty) in fn_tys {
+        if is_supported(&fn_ty) {
+            gen_trampoline(llvm, &name, &fn_ty)?;
+        }
+    }
+    Ok(())
+}
+
+// A trampoline takes every argument and the result as an i64 slot
+// so that functions of any supported type can be called through a single Rust signature.
+fn gen_trampoline(llvm: &mut LLVM, name: &str, fn_ty: &FnTy) -> Result<LLVMValueRef> {
+    let f = llvm.get_named_function(codegen::symbol_name(name))?;
+    let f_ty = LLVM::global_get_value_type(f)?;
+    let int64_ty = llvm.int64_type()?;
+    let slots_ty = LLVM::pointer_type(int64_ty)?;
+    let void_ty = llvm.void_type()?;
+    let trampoline_ty = llvm.function_type(void_ty, vec![slots_ty, slots_ty])?;
+    let trampoline = llvm.add_function(&trampoline_name(name), trampoline_ty)?;
+    let block = llvm.append_basic_block(trampoline, "entry")?;
+    llvm.position_builder_at_end(block);
+    let slots = LLVM::get_param(trampoline, 0)?;
+    let ret = LLVM::get_param(trampoline, 1)?;
+    let mut args = Vec::new();
+    for (i, ty) in fn_ty.args.iter().enumerate() {
+        let index = llvm.const_int64(i as u64)?;
+        let slot = llvm.build_in_bounds_gep(int64_ty, slots, vec![index], "slot")?;
+        args.push(gen_load_slot(llvm, slot, ty)?);
+    }
+    let value = llvm.build_call(f_ty, f, args, "calltmp")?;
+    gen_store_slot(llvm, ret, value, &fn_ty.ret)?;
+    llvm.build_ret_void()?;
+    Ok(trampoline)
+}
+
+fn gen_load_slot(llvm: &mut LLVM, slot: LLVMValueRef, ty: &Ty) -> Result<LLVMValueRef> {
+    if *ty == Ty::I32 {
+        let int64_ty = llvm.int64_type()?;
+        let value = llvm.build_load(int64_ty, slot, "slotvalue")?;
+        let int_ty = llvm.int32_type()?;
+        llvm.build_trunc(value, int_ty, "arg")
+    }
+    else {
+        let ptr_ty = LLVM::pointer_type(llvm.int8_type()?)?;
+        let slot = llvm.build_pointer_cast(slot, LLVM::pointer_type(ptr_ty)?, "ptrslot")?;
+        llvm.build_load(ptr_ty, slot, "arg")
+    }
+}
+
+fn gen_store_slot(llvm: &mut LLVM, slot: LLVMValueRef, value: LLVMValueRef, ty: &Ty) -> Result<LLVMValueRef> {
+    if *ty == Ty::I32 {
+        let int64_ty = llvm.int64_type()?;
+        let value = llvm.build_sext(value, int64_ty, "slotvalue")?;
+        llvm.build_store(value, slot)
+    }
+    else {
+        let ptr_ty = LLVM::pointer_type(llvm.int8_type()?)?;
+        let slot = llvm.build_pointer_cast(slot, LLVM::pointer_type(ptr_ty)?, "ptrslot")?;
+        llvm.build_store(value, slot)
+    }
+}
+
+fn trampoline_name(name: &str) -> String {
+    format!("akane.jit.{}", name)
+}
+
+fn is_supported(fn_ty: &FnTy) -> bool {
+    fn_ty.args.iter().chain([fn_ty.ret.as_ref()]).all(|ty| !matches!(ty, Ty::Fn(_)))
+}
+
+// Strings and lists are laid out as `codegen::runtime` expects: an i64 length followed by the data.
+fn to_slot(value: &Value, buffers: &mut Vec<Vec<i64>>) -> i64 {
+    let buffer = match value {
+        Value::I32(n) => return *n as i64,
+        Value::Ptr(p) => return *p as i64,
+        Value::Str(s) => {
+            let mut buffer = vec![0i64; 1 + (s.len() + 8) / 8];
+            buffer[0] = s.len() as i64;
+            unsafe {
+                let data = buffer.as_mut_ptr().add(1) as *mut u8;
+                data.copy_from_nonoverlapping(s.as_ptr(), s.len());
+            }
+            buffer
+        },
+        Value::List(xs) => {
+            let mut buffer = vec![0i64; 1 + xs.len().div_ceil(2)];
+            buffer[0] = xs.len() as i64;
+            unsafe {
+                let data = buffer.as_mut_ptr().add(1) as *mut i32;
+                data.copy_from_nonoverlapping(xs.as_ptr(), xs.len());
+            }
+            buffer
+        },
+    };
+    let p = buffer.as_ptr() as i64;
+    buffers.push(buffer);
+    p
+}
+
+unsafe fn from_slot(slot: i64, ty: &Ty) -> Value {
+    match ty {
+        Ty::I32 => Value::I32(slot as i32),
+        Ty::Ptr => Value::Ptr(slot as usize),
+        Ty::Str => {
+            let p = slot as *const i64;
+            let data = slice::from_raw_parts(p.add(1) as *const u8, *p as usize);
+            Value::Str(String::from_utf8_lossy(data).into_owned())
+        },
+        Ty::List => {
+            let p = slot as *const i64;
+            let data = slice::from_raw_parts(p.add(1) as *const i32, *p as usize);
+            Value::List(data.to_vec())
+        },
+        Ty::Fn(_) => unreachable!(),
+    }
+}
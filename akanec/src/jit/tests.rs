@@ -0,0 +1,117 @@
+use std::mem;
+use crate::{
+    compiler::{
+        self,
+        OptLevel,
+        Options,
+    },
+    data::value::Value,
+};
+use super::Jit;
+
+fn jit_with_options(code: &str, options: &Options) -> Jit {
+    let module_ast = crate::parser::parse(crate::lexer::lex(code.to_owned()).unwrap()).unwrap();
+    compiler::jit_module("test", &module_ast, options).unwrap()
+}
+
+fn jit(code: &str) -> Jit {
+    jit_with_options(code, &Options::default())
+}
+
+fn str(s: &str) -> Value {
+    Value::Str(s.to_owned())
+}
+
+#[test]
+fn call_i32() {
+    let mut jit = jit("pub add a b = a + b; one = 1; main = add one 2;");
+    assert_eq!(jit.call("add", &[Value::I32(1), Value::I32(-3)]).unwrap(), Value::I32(-2));
+    assert_eq!(jit.call("one", &[]).unwrap(), Value::I32(1));
+    assert_eq!(jit.call("main", &[]).unwrap(), Value::I32(3));
+}
+
+#[test]
+fn call_str() {
+    let mut jit = jit(r#"
+        greet : String -> String;
+        greet name = "Hello, " ++ name ++ "!";
+        len : String -> i32;
+        len s = length s;
+    "#);
+    assert_eq!(jit.call("greet", &[str("あかね")]).unwrap(), str("Hello, あかね!"));
+    assert_eq!(jit.call("len", &[str("abc")]).unwrap(), Value::I32(3));
+}
+
+#[test]
+fn call_list() {
+    let mut jit = jit(r#"
+        add_one x = x + 1;
+        f : [i32] -> [i32];
+        f xs = map add_one (0 :: xs);
+    "#);
+    assert_eq!(jit.call("f", &[Value::List(vec![1, 2])]).unwrap(), Value::List(vec![1, 2, 3]));
+    assert_eq!(jit.call("f", &[Value::List(vec![])]).unwrap(), Value::List(vec![1]));
+}
+
+#[test]
+fn call_arithmetic() {
+    let mut jit = jit("f a b = (a - b) * 3 / 2 % 5; neg x = -x; lt a b = a < b; ge a b = a >= b;");
+    assert_eq!(jit.call("f", &[Value::I32(7), Value::I32(2)]).unwrap(), Value::I32(2));
+    assert_eq!(jit.call("f", &[Value::I32(2), Value::I32(7)]).unwrap(), Value::I32(-2));
+    assert_eq!(jit.call("neg", &[Value::I32(3)]).unwrap(), Value::I32(-3));
+    assert_eq!(jit.call("lt", &[Value::I32(1), Value::I32(2)]).unwrap(), Value::I32(1));
+    assert_eq!(jit.call("ge", &[Value::I32(1), Value::I32(2)]).unwrap(), Value::I32(0));
+}
+
+#[test]
+fn call_recursive() {
+    let mut jit = jit("even n = n == 0 || odd (n - 1); odd n = n != 0 && even (n - 1);");
+    assert_eq!(jit.call("even", &[Value::I32(10)]).unwrap(), Value::I32(1));
+    assert_eq!(jit.call("odd", &[Value::I32(10)]).unwrap(), Value::I32(0));
+    assert_eq!(jit.call("odd", &[Value::I32(7)]).unwrap(), Value::I32(1));
+}
+
+#[test]
+fn call_optimized() {
+    let options = Options { opt_level: OptLevel::O2, ..Default::default() };
+    let mut jit = jit_with_options("double x = x + x; pub quad x = double (double x);", &options);
+    assert_eq!(jit.call("quad", &[Value::I32(5)]).unwrap(), Value::I32(20));
+}
+
+#[test]
+fn call_error() {
+    let mut jit = jit("add a b = a + b; apply : (i32 -> i32) -> i32 -> i32; apply f x = f x;");
+    assert_eq!(jit.call("add", &[Value::I32(1)]).unwrap_err().to_string(), "Invalid count of arguments.");
+    assert_eq!(jit.call("add", &[Value::I32(1), str("a")]).unwrap_err().to_string(), "Type mismatch.");
+    assert!(jit.call("apply", &[Value::I32(1), Value::I32(1)]).is_err());
+    assert!(jit.call("nothing", &[]).is_err());
+}
+
+#[test]
+fn get_function_address() {
+    let mut jit = jit("pub add a b = a + b;");
+    let address = jit.get_function_address("add").unwrap();
+    let add: extern "C" fn(i32, i32) -> i32 = unsafe { mem::transmute(address as usize) };
+    assert_eq!(add(2, 3), 5);
+    assert!(jit.get_function_address("nothing").is_err());
+}
+
+#[test]
+fn unresolved_extern() {
+    let module_ast = crate::parser::parse(crate::lexer::lex(r#"extern "C" akane_no_such_fn : i32; f = akane_no_such_fn;"#.to_owned()).unwrap()).unwrap();
+    let e = compiler::jit_module("test", &module_ast, &Options::default()).err().unwrap();
+    assert_eq!(e.to_string(), "Unresolved external functions: akane_no_such_fn");
+}
+
+#[test]
+fn parse_value() {
+    use crate::data::ty::Ty;
+    assert_eq!(Value::parse("-12", &Ty::I32).unwrap(), Value::I32(-12));
+    assert_eq!(Value::parse("abc", &Ty::Str).unwrap(), str("abc"));
+    assert_eq!(Value::parse("[1, 2]", &Ty::List).unwrap(), Value::List(vec![1, 2]));
+    assert_eq!(Value::parse("[]", &Ty::List).unwrap(), Value::List(vec![]));
+    assert!(Value::parse("a", &Ty::I32).is_err());
+    assert!(Value::parse("0", &Ty::Ptr).is_err());
+    assert_eq!(Value::List(vec![1, 2]).to_string(), "[1, 2]");
+    assert_eq!(str("a\n").to_string(), r#""a\n""#);
+}
@@ -1,37 +1,85 @@
 #[cfg(test)]
 mod tests;
 
-use std::iter::Peekable;
+use std::{
+    cell::Cell,
+    iter::Peekable,
+    rc::Rc,
+    str::Chars,
+};
 use anyhow::{
     bail,
     Result,
 };
-use crate::data::token::Token;
+use crate::data::{
+    diagnostic::Diagnostic,
+    span::Span,
+    token::{
+        Token,
+        SpannedToken,
+    },
+};
+
+struct Counted<'a> {
+    chars: Chars<'a>,
+    offset: Rc<Cell<usize>>,
+}
 
-pub fn lex(input: String) -> Result<Vec<Token>> {
-    let mut tokens = Vec::new();
-    let mut chars = input.chars().peekable();
+impl Iterator for Counted<'_> {
+    type Item = char;
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.chars.next()?;
+        self.offset.set(self.offset.get() + c.len_utf8());
+        Some(c)
+    }
+}
+
+pub fn lex(input: String) -> Result<Vec<SpannedToken>> {
+    lex_with_trivia(input, false)
+}
+
+/// Also keeps whitespace and comments as trivia tokens, so that the spans cover the whole input.
+pub fn lex_lossless(input: String) -> Result<Vec<SpannedToken>> {
+    lex_with_trivia(input, true)
+}
+
+fn lex_with_trivia(input: String, keep_trivia: bool) -> Result<Vec<SpannedToken>> {
+    let mut tokens: Vec<SpannedToken> = Vec::new();
+    let consumed = Rc::new(Cell::new(0));
+    let mut chars = Counted { chars: input.chars(), offset: consumed.clone() }.peekable();
     loop {
+        let start = offset(&mut chars, &consumed);
         if let Some(token) = assume_eof(&mut chars)? {
-            if let Some(last) = tokens.last() {
-                if *last != Token::Semicolon {
-                    tokens.push(Token::Semicolon);
+            if let Some(last) = tokens.iter().rev().find(|spanned| !spanned.token.is_trivia()) {
+                if last.token != Token::Semicolon {
+                    let span = Span::new(last.span.end, last.span.end);
+                    tokens.push(SpannedToken { token: Token::Semicolon, span });
                 }
             }
-            tokens.push(token);
+            tokens.push(SpannedToken { token, span: Span::new(start, start) });
             return Ok(tokens);
         }
-        if let Some(_) = assume_whitespace(&mut chars)? {
-            continue;
-        }
-        if let Some(token) = assume_token(&mut chars)? {
-            tokens.push(token);
-            continue;
+        let token = match assume_token(&mut chars) {
+            Ok(Some(token)) => token,
+            Ok(None) => {
+                let end = start + chars.peek().map_or(0, |c| c.len_utf8());
+                bail!(Diagnostic::error("No valid token found.", Span::new(start, end)))
+            },
+            Err(e) => bail!(Diagnostic::error(e.to_string(), Span::new(start, offset(&mut chars, &consumed)))),
+        };
+        if keep_trivia || !token.is_trivia() {
+            tokens.push(SpannedToken { token, span: Span::new(start, offset(&mut chars, &consumed)) });
         }
-        bail!("No valid token found.")
     }
 }
 
+/// The peeked character has already been counted, so it is subtracted back.
+fn offset(chars: &mut Peekable<Counted>, consumed: &Cell<usize>) -> usize {
+    let peeked = chars.peek().map_or(0, |c| c.len_utf8());
+    consumed.get() - peeked
+}
+
 fn assume_eof(chars: &mut Peekable<impl Iterator<Item = char>>) -> Result<Option<Token>> {
     if chars.peek().is_none() {
         Ok(Some(Token::Eof))
@@ -41,14 +89,13 @@ fn assume_eof(chars: &mut Peekable<impl Iterator<Item = char>>) -> Result<Option
     }
 }
 
-fn assume_whitespace(chars: &mut Peekable<impl Iterator<Item = char>>) -> Result<Option<()>> {
-    let mut consumed = false;
+fn assume_whitespace(chars: &mut Peekable<impl Iterator<Item = char>>) -> Result<Option<Token>> {
+    let mut token = String::new();
     while is_whitespace(chars.peek()) {
-        chars.next();
-        consumed = true;
+        token.push(chars.next().unwrap());
     }
-    if consumed {
-        Ok(Some(()))
+    if !token.is_empty() {
+        Ok(Some(Token::Whitespace(token)))
     }
     else {
         Ok(None)
@@ -56,7 +103,10 @@ fn assume_whitespace(chars: &mut Peekable<impl Iterator<Item = char>>) -> Result
 }
 
 fn assume_token(chars: &mut Peekable<impl Iterator<Item = char>>) -> Result<Option<Token>> {
-    if let Some(token) = assume_semicolon(chars)? {
+    if let Some(token) = assume_whitespace(chars)? {
+        Ok(Some(token))
+    }
+    else if let Some(token) = assume_semicolon(chars)? {
         Ok(Some(token))
     }
     else if let Some(token) = assume_keyword_or_ident(chars)? {
@@ -65,9 +115,21 @@ fn assume_token(chars: &mut Peekable<impl Iterator<Item = char>>) -> Result<Opti
     else if let Some(token) = assume_num(chars)? {
         Ok(Some(token))
     }
+    else if let Some(token) = assume_str(chars)? {
+        Ok(Some(token))
+    }
+    else if let Some(token) = assume_char(chars)? {
+        Ok(Some(token))
+    }
     else if let Some(token) = assume_paren(chars)? {
         Ok(Some(token))
     }
+    else if let Some(token) = assume_bracket(chars)? {
+        Ok(Some(token))
+    }
+    else if let Some(token) = assume_comma(chars)? {
+        Ok(Some(token))
+    }
     else if let Some(token) = assume_symbol_or_op_code(chars)? {
         Ok(Some(token))
     }
@@ -89,10 +151,28 @@ fn assume_semicolon(chars: &mut Peekable<impl Iterator<Item = char>>) -> Result<
 fn assume_keyword_or_ident(chars: &mut Peekable<impl Iterator<Item = char>>) -> Result<Option<Token>> {
     if is_ident_head(chars.peek()) {
         let mut token = String::from(chars.next().unwrap());
-        while is_ident_tail(chars.peek()) {
+        let mut segment_head = token.chars().next().unwrap();
+        loop {
+            while is_ident_tail(chars.peek()) {
+                token.push(chars.next().unwrap());
+            }
+            if !segment_head.is_uppercase() || !is_dot(chars.peek()) {
+                break;
+            }
             token.push(chars.next().unwrap());
+            if !is_ident_head(chars.peek()) {
+                bail!("Identifier required after qualifier.");
+            }
+            segment_head = chars.next().unwrap();
+            token.push(segment_head);
+        }
+        match token.as_str() {
+            "module" => Ok(Some(Token::Module)),
+            "import" => Ok(Some(Token::Import)),
+            "pub" => Ok(Some(Token::Pub)),
+            "extern" => Ok(Some(Token::Extern)),
+            _ => Ok(Some(Token::Ident(token))),
         }
-        Ok(Some(Token::Ident(token)))
     }
     else {
         Ok(None)
@@ -112,6 +192,78 @@ fn assume_num(chars: &mut Peekable<impl Iterator<Item = char>>) -> Result<Option
     }
 }
 
+fn assume_str(chars: &mut Peekable<impl Iterator<Item = char>>) -> Result<Option<Token>> {
+    if is_double_quote(chars.peek()) {
+        chars.next();
+        let mut token = String::new();
+        loop {
+            match chars.next() {
+                Some('"') => return Ok(Some(Token::Str(token))),
+                Some('\\') => token.push(escape(chars)?),
+                Some(c) => token.push(c),
+                None => bail!("Unterminated string literal."),
+            }
+        }
+    }
+    else {
+        Ok(None)
+    }
+}
+
+fn assume_char(chars: &mut Peekable<impl Iterator<Item = char>>) -> Result<Option<Token>> {
+    if is_single_quote(chars.peek()) {
+        chars.next();
+        let c = match chars.next() {
+            Some('\'') => bail!("Empty character literal."),
+            Some('\\') => escape(chars)?,
+            Some(c) => c,
+            None => bail!("Unterminated character literal."),
+        };
+        if is_single_quote(chars.peek()) {
+            chars.next();
+            Ok(Some(Token::Char(c)))
+        }
+        else {
+            bail!("Character literal must contain exactly one character.")
+        }
+    }
+    else {
+        Ok(None)
+    }
+}
+
+fn escape(chars: &mut Peekable<impl Iterator<Item = char>>) -> Result<char> {
+    match chars.next() {
+        Some('n') => Ok('\n'),
+        Some('r') => Ok('\r'),
+        Some('t') => Ok('\t'),
+        Some('0') => Ok('\0'),
+        Some('\\') => Ok('\\'),
+        Some('"') => Ok('"'),
+        Some('\'') => Ok('\''),
+        Some('u') => escape_unicode(chars),
+        _ => bail!("Invalid escape sequence."),
+    }
+}
+
+fn escape_unicode(chars: &mut Peekable<impl Iterator<Item = char>>) -> Result<char> {
+    if chars.next() != Some('{') {
+        bail!("Invalid unicode escape.");
+    }
+    let mut digits = String::new();
+    loop {
+        match chars.next() {
+            Some('}') => break,
+            Some(c) if c.is_ascii_hexdigit() && digits.len() < 6 => digits.push(c),
+            _ => bail!("Invalid unicode escape."),
+        }
+    }
+    match u32::from_str_radix(&digits, 16).ok().and_then(char::from_u32) {
+        Some(c) => Ok(c),
+        None => bail!("Invalid unicode escape."),
+    }
+}
+
 fn assume_paren(chars: &mut Peekable<impl Iterator<Item = char>>) -> Result<Option<Token>> {
     let c = chars.peek();
     if is_l_paren(c) {
@@ -127,15 +279,49 @@ fn assume_paren(chars: &mut Peekable<impl Iterator<Item = char>>) -> Result<Opti
     }
 }
 
+fn assume_bracket(chars: &mut Peekable<impl Iterator<Item = char>>) -> Result<Option<Token>> {
+    let c = chars.peek();
+    if is_l_bracket(c) {
+        chars.next();
+        Ok(Some(Token::LBracket))
+    }
+    else if is_r_bracket(c) {
+        chars.next();
+        Ok(Some(Token::RBracket))
+    }
+    else {
+        Ok(None)
+    }
+}
+
+fn assume_comma(chars: &mut Peekable<impl Iterator<Item = char>>) -> Result<Option<Token>> {
+    if is_comma(chars.peek()) {
+        chars.next();
+        Ok(Some(Token::Comma))
+    }
+    else {
+        Ok(None)
+    }
+}
+
 fn assume_symbol_or_op_code(chars: &mut Peekable<impl Iterator<Item = char>>) -> Result<Option<Token>> {
     if is_op_code(chars.peek()) {
         let mut token = String::from(chars.next().unwrap());
         while is_op_code(chars.peek()) {
             token.push(chars.next().unwrap());
         }
-        if is_equal(&mut token) {
+        if is_comment(&token) {
+            while !is_newline(chars.peek()) && chars.peek().is_some() {
+                token.push(chars.next().unwrap());
+            }
+            Ok(Some(Token::Comment(token)))
+        }
+        else if is_equal(&token) {
             Ok(Some(Token::Equal))
         }
+        else if is_colon(&token) {
+            Ok(Some(Token::Colon))
+        }
         else {
             Ok(Some(Token::OpCode(token)))
         }
@@ -146,27 +332,43 @@ fn assume_symbol_or_op_code(chars: &mut Peekable<impl Iterator<Item = char>>) ->
 }
 
 fn is_whitespace(c: Option<&char>) -> bool {
-    c.map_or(false, |c| c.is_whitespace())
+    c.is_some_and(|c| c.is_whitespace())
+}
+
+fn is_newline(c: Option<&char>) -> bool {
+    c.is_some_and(|c| *c == '\n')
 }
 
 fn is_semicolon(c: Option<&char>) -> bool {
-    c.map_or(false, |c| *c == ';')
+    c.is_some_and(|c| *c == ';')
 }
 
 fn is_ident_head(c: Option<&char>) -> bool {
-    c.map_or(false, |c| *c == '_' || c.is_alphabetic())
+    c.is_some_and(|c| *c == '_' || c.is_alphabetic())
 }
 
 fn is_ident_tail(c: Option<&char>) -> bool {
-    c.map_or(false, |c| *c == '_' || c.is_alphanumeric())
+    c.is_some_and(|c| *c == '_' || c.is_alphanumeric())
+}
+
+fn is_dot(c: Option<&char>) -> bool {
+    c.is_some_and(|c| *c == '.')
 }
 
 fn is_num(c: Option<&char>) -> bool {
-    c.map_or(false, |c| c.is_numeric())
+    c.is_some_and(|c| c.is_numeric())
+}
+
+fn is_double_quote(c: Option<&char>) -> bool {
+    c.is_some_and(|c| *c == '"')
+}
+
+fn is_single_quote(c: Option<&char>) -> bool {
+    c.is_some_and(|c| *c == '\'')
 }
 
 fn is_op_code(c: Option<&char>) -> bool {
-    c.map_or(false, |c| [
+    c.is_some_and(|c| [
         '!',
         '#',
         '$',
@@ -176,6 +378,7 @@ fn is_op_code(c: Option<&char>) -> bool {
         '+',
         '.',
         '/',
+        ':',
         '<',
         '=',
         '>',
@@ -190,13 +393,34 @@ fn is_op_code(c: Option<&char>) -> bool {
 }
 
 fn is_l_paren(c: Option<&char>) -> bool {
-    c.map_or(false, |c| *c == '(')
+    c.is_some_and(|c| *c == '(')
 }
 
 fn is_r_paren(c: Option<&char>) -> bool {
-    c.map_or(false, |c| *c == ')')
+    c.is_some_and(|c| *c == ')')
+}
+
+fn is_l_bracket(c: Option<&char>) -> bool {
+    c.is_some_and(|c| *c == '[')
+}
+
+fn is_r_bracket(c: Option<&char>) -> bool {
+    c.is_some_and(|c| *c == ']')
+}
+
+fn is_comma(c: Option<&char>) -> bool {
+    c.is_some_and(|c| *c == ',')
 }
 
 fn is_equal(s: &str) -> bool {
     s == "="
 }
+
+fn is_colon(s: &str) -> bool {
+    s == ":"
+}
+
+/// An operator starting with `//` begins a line comment instead.
+fn is_comment(s: &str) -> bool {
+    s.starts_with("//")
+}
@@ -1,7 +1,7 @@
 use super::Token;
 
 fn lex(input: &str) -> Vec<Token> {
-    super::lex(input.to_owned()).unwrap()
+    super::lex(input.to_owned()).unwrap().into_iter().map(|spanned| spanned.token).collect()
 }
 
 fn eof() -> Token {
@@ -12,6 +12,22 @@ fn semicolon() -> Token {
     Token::Semicolon
 }
 
+fn module() -> Token {
+    Token::Module
+}
+
+fn import() -> Token {
+    Token::Import
+}
+
+fn pub_() -> Token {
+    Token::Pub
+}
+
+fn extern_() -> Token {
+    Token::Extern
+}
+
 fn ident(s: &str) -> Token {
     Token::Ident(s.to_owned())
 }
@@ -20,6 +36,14 @@ fn num(s: &str) -> Token {
     Token::Num(s.to_owned())
 }
 
+fn str(s: &str) -> Token {
+    Token::Str(s.to_owned())
+}
+
+fn char(c: char) -> Token {
+    Token::Char(c)
+}
+
 fn op_code(s: &str) -> Token {
     Token::OpCode(s.to_owned())
 }
@@ -28,6 +52,10 @@ fn equal() -> Token {
     Token::Equal
 }
 
+fn colon() -> Token {
+    Token::Colon
+}
+
 fn l_paren() -> Token {
     Token::LParen
 }
@@ -36,6 +64,18 @@ fn r_paren() -> Token {
     Token::RParen
 }
 
+fn l_bracket() -> Token {
+    Token::LBracket
+}
+
+fn r_bracket() -> Token {
+    Token::RBracket
+}
+
+fn comma() -> Token {
+    Token::Comma
+}
+
 #[test]
 fn lex_eof() {
     assert_eq!(lex(""), &[eof()]);
@@ -53,6 +93,22 @@ fn lex_keyword_or_ident() {
     assert_eq!(lex("A"), &[ident("A"), semicolon(), eof()]);
     assert_eq!(lex("あ"), &[ident("あ"), semicolon(), eof()]);
     assert_eq!(lex("AbcDef_123"), &[ident("AbcDef_123"), semicolon(), eof()]);
+    assert_eq!(lex("module"), &[module(), semicolon(), eof()]);
+    assert_eq!(lex("import"), &[import(), semicolon(), eof()]);
+    assert_eq!(lex("pub"), &[pub_(), semicolon(), eof()]);
+    assert_eq!(lex("extern"), &[extern_(), semicolon(), eof()]);
+    assert_eq!(lex("modules"), &[ident("modules"), semicolon(), eof()]);
+}
+
+#[test]
+fn lex_qualified_ident() {
+    assert_eq!(lex("Math.square"), &[ident("Math.square"), semicolon(), eof()]);
+    assert_eq!(lex("Data.List.map"), &[ident("Data.List.map"), semicolon(), eof()]);
+    assert_eq!(lex("Data.List"), &[ident("Data.List"), semicolon(), eof()]);
+    assert_eq!(lex("f.g"), &[ident("f"), op_code("."), ident("g"), semicolon(), eof()]);
+    assert_eq!(lex("Math . square"), &[ident("Math"), op_code("."), ident("square"), semicolon(), eof()]);
+    assert!(super::lex("Math.".to_owned()).is_err());
+    assert!(super::lex("Math.1".to_owned()).is_err());
 }
 
 #[test]
@@ -61,18 +117,67 @@ fn lex_num() {
     assert_eq!(lex("1234567890"), &[num("1234567890"), semicolon(), eof()]);
 }
 
+#[test]
+fn lex_str() {
+    assert_eq!(lex(r#""""#), &[str(""), semicolon(), eof()]);
+    assert_eq!(lex(r#""abc""#), &[str("abc"), semicolon(), eof()]);
+    assert_eq!(lex(r#""あいう""#), &[str("あいう"), semicolon(), eof()]);
+    assert_eq!(lex(r#""a\n\t\r\0\\\"\'""#), &[str("a\n\t\r\0\\\"'"), semicolon(), eof()]);
+    assert_eq!(lex(r#""\u{41}\u{3042}\u{1F600}""#), &[str("Aあ😀"), semicolon(), eof()]);
+}
+
+#[test]
+fn lex_str_error() {
+    assert!(super::lex(r#""abc"#.to_owned()).is_err());
+    assert!(super::lex(r#""\q""#.to_owned()).is_err());
+    assert!(super::lex(r#""\u41""#.to_owned()).is_err());
+    assert!(super::lex(r#""\u{D800}""#.to_owned()).is_err());
+    assert!(super::lex(r#""\u{1234567}""#.to_owned()).is_err());
+}
+
+#[test]
+fn lex_char() {
+    assert_eq!(lex("'a'"), &[char('a'), semicolon(), eof()]);
+    assert_eq!(lex("'あ'"), &[char('あ'), semicolon(), eof()]);
+    assert_eq!(lex(r"'\n'"), &[char('\n'), semicolon(), eof()]);
+    assert_eq!(lex(r"'\''"), &[char('\''), semicolon(), eof()]);
+    assert_eq!(lex(r"'\u{3042}'"), &[char('あ'), semicolon(), eof()]);
+}
+
+#[test]
+fn lex_char_error() {
+    assert!(super::lex("''".to_owned()).is_err());
+    assert!(super::lex("'ab'".to_owned()).is_err());
+    assert!(super::lex("'a".to_owned()).is_err());
+}
+
 #[test]
 fn lex_paren() {
     assert_eq!(lex("("), &[l_paren(), semicolon(), eof()]);
     assert_eq!(lex(")"), &[r_paren(), semicolon(), eof()]);
 }
 
+#[test]
+fn lex_bracket() {
+    assert_eq!(lex("["), &[l_bracket(), semicolon(), eof()]);
+    assert_eq!(lex("]"), &[r_bracket(), semicolon(), eof()]);
+}
+
+#[test]
+fn lex_comma() {
+    assert_eq!(lex(","), &[comma(), semicolon(), eof()]);
+}
+
 #[test]
 fn lex_symbol_or_op_code() {
     assert_eq!(lex("="), &[equal(), semicolon(), eof()]);
     assert_eq!(lex("=="), &[op_code("=="), semicolon(), eof()]);
     assert_eq!(lex("+"), &[op_code("+"), semicolon(), eof()]);
     assert_eq!(lex(">>="), &[op_code(">>="), semicolon(), eof()]);
+    assert_eq!(lex(":"), &[colon(), semicolon(), eof()]);
+    assert_eq!(lex("::"), &[op_code("::"), semicolon(), eof()]);
+    assert_eq!(lex("++"), &[op_code("++"), semicolon(), eof()]);
+    assert_eq!(lex("!!"), &[op_code("!!"), semicolon(), eof()]);
 }
 
 #[test]
@@ -97,3 +202,82 @@ fn lex_statement() {
         ]
     );
 }
+
+#[test]
+fn lex_ty_annot() {
+    assert_eq!(
+        lex(r#"greet : String -> String; greet name = "Hello, " ++ name"#),
+        &[
+            ident("greet"),
+            colon(),
+            ident("String"),
+            op_code("->"),
+            ident("String"),
+            semicolon(),
+            ident("greet"),
+            ident("name"),
+            equal(),
+            str("Hello, "),
+            op_code("++"),
+            ident("name"),
+            semicolon(),
+            eof(),
+        ]
+    );
+}
+
+#[test]
+fn lex_list() {
+    assert_eq!(
+        lex("f = 0 :: [1, 2]"),
+        &[
+            ident("f"),
+            equal(),
+            num("0"),
+            op_code("::"),
+            l_bracket(),
+            num("1"),
+            comma(),
+            num("2"),
+            r_bracket(),
+            semicolon(),
+            eof(),
+        ]
+    );
+}
+
+#[test]
+fn lex_spans() {
+    let spans = super::lex("f \"あ\" 'b'\n+ 12".to_owned()).unwrap().into_iter().map(|spanned| (spanned.span.start, spanned.span.end)).collect::<Vec<_>>();
+    assert_eq!(spans, vec![(0, 1), (2, 7), (8, 11), (12, 13), (14, 16), (16, 16), (16, 16)]);
+}
+
+#[test]
+fn lex_comments() {
+    assert_eq!(lex("f = 1; // one\n// two\ng = 2"), lex("f = 1; g = 2"));
+    assert_eq!(lex("f = a +// c\n b"), &[ident("f"), equal(), ident("a"), op_code("+//"), ident("c"), ident("b"), semicolon(), eof()]);
+    assert_eq!(lex("// only"), &[eof()]);
+}
+
+#[test]
+fn lex_lossless() {
+    let code = "f x = x // double\n\t+ x;\n";
+    let tokens = super::lex_lossless(code.to_owned()).unwrap();
+    assert_eq!(tokens.iter().map(|spanned| &code[spanned.span.start..spanned.span.end]).collect::<String>(), code);
+    assert_eq!(tokens[7].token, Token::Whitespace(" ".to_owned()));
+    assert_eq!(tokens[8].token, Token::Comment("// double".to_owned()));
+    assert_eq!(tokens[9].token, Token::Whitespace("\n\t".to_owned()));
+    assert_eq!(tokens.iter().filter(|spanned| !spanned.token.is_trivia()).map(|spanned| spanned.token.clone()).collect::<Vec<_>>(), lex(code));
+}
+
+#[test]
+fn lex_error_spans() {
+    let span = |input: &str| {
+        let e = super::lex(input.to_owned()).unwrap_err();
+        let diagnostic = e.downcast_ref::<crate::data::diagnostic::Diagnostic>().unwrap();
+        (diagnostic.message.clone(), diagnostic.span.start, diagnostic.span.end)
+    };
+    assert_eq!(span("f = \"a\\q\";"), ("Invalid escape sequence.".to_owned(), 4, 8));
+    assert_eq!(span("f = 'ab';"), ("Character literal must contain exactly one character.".to_owned(), 4, 6));
+    assert_eq!(span("f = 1 \u{7} 2;"), ("No valid token found.".to_owned(), 6, 7));
+}
@@ -1,6 +1,27 @@
 pub mod data;
 pub mod lexer;
 pub mod parser;
+pub mod printer;
+pub mod formatter;
+pub mod highlighter;
+pub mod loader;
+pub mod resolver;
+pub mod arity;
+pub mod linter;
+pub mod simplifier;
+pub mod interp;
+pub mod dump;
+#[cfg(feature = "llvm")]
 pub mod llvm;
+#[cfg(feature = "llvm")]
 pub mod codegen;
+#[cfg(feature = "llvm")]
+pub mod jit;
+#[cfg(feature = "llvm")]
+pub mod repl;
+#[cfg(feature = "llvm")]
 pub mod compiler;
+#[cfg(feature = "llvm")]
+pub mod difftest;
+#[cfg(feature = "llvm")]
+pub mod snapshot;
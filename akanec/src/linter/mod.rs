@@ -0,0 +1,243 @@
+#[cfg(test)]
+mod tests;
+
+use std::collections::HashMap;
+use anyhow::Result;
+use clap::ValueEnum;
+use crate::{
+    data::{
+        ast::{
+            ModuleAst,
+            FnDefAst,
+        },
+        builtins::builtin_arity,
+        diagnostic::{
+            Diagnostic,
+            Diagnostics,
+        },
+        span::Span,
+        token::{
+            Token,
+            SpannedToken,
+        },
+    },
+    printer,
+    resolver::{
+        self,
+        Def,
+        SymbolTable,
+    },
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, ValueEnum)]
+pub enum Lint {
+    /// A function that is neither public nor main and is not used by one that is
+    #[value(name = "unused_fn")]
+    UnusedFn,
+    /// A parameter that is not used in the body of its function
+    #[value(name = "unused_param")]
+    UnusedParam,
+    /// A parameter named like a function, or a function named like a builtin
+    #[value(name = "shadowed_name")]
+    ShadowedName,
+    /// Parentheses the parser does not need
+    #[value(name = "redundant_parens")]
+    RedundantParens,
+}
+
+impl Lint {
+    pub const ALL: [Lint; 4] = [Lint::UnusedFn, Lint::UnusedParam, Lint::ShadowedName, Lint::RedundantParens];
+
+    /// Also the name used in attributes and on the command line.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Lint::UnusedFn => "unused_fn",
+            Lint::UnusedParam => "unused_param",
+            Lint::ShadowedName => "shadowed_name",
+            Lint::RedundantParens => "redundant_parens",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|lint| lint.name() == name)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Level {
+    Allow,
+    #[default]
+    Warn,
+    Deny,
+}
+
+impl Level {
+    /// The level set by an attribute named `name`.
+    pub fn from_attr(name: &str) -> Option<Self> {
+        match name {
+            "allow" => Some(Level::Allow),
+            "warn" => Some(Level::Warn),
+            "deny" => Some(Level::Deny),
+            _ => None,
+        }
+    }
+}
+
+/// The level of every lint, `Warn` unless set otherwise.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Levels(HashMap<Lint, Level>);
+
+impl Levels {
+    pub fn get(&self, lint: Lint) -> Level {
+        self.0.get(&lint).copied().unwrap_or_default()
+    }
+
+    pub fn set(&mut self, lint: Lint, level: Level) {
+        self.0.insert(lint, level);
+    }
+}
+
+/// Fails with the denied lints if there are any, returning the warnings otherwise.
+pub fn check(tokens: &[SpannedToken], module_ast: &ModuleAst, symbol_table: &SymbolTable, levels: &Levels) -> Result<Vec<Diagnostic>> {
+    let diagnostics = lint(tokens, module_ast, symbol_table, levels);
+    Diagnostics::check(&diagnostics)?;
+    Ok(diagnostics)
+}
+
+/// Lints a module as written, `tokens` being those it was parsed from, in source order.
+/// A lint found in a function is reported at the level the function's attributes set for it,
+/// or at the one in `levels` if they do not name it; allowed lints are left out and denied ones are errors.
+pub fn lint(tokens: &[SpannedToken], module_ast: &ModuleAst, symbol_table: &SymbolTable, levels: &Levels) -> Vec<Diagnostic> {
+    let tokens = tokens.iter().filter(|spanned| !spanned.token.is_trivia()).collect::<Vec<_>>();
+    let mut starts = HashMap::new();
+    let mut ends = HashMap::new();
+    for (i, spanned) in tokens.iter().enumerate() {
+        starts.entry(spanned.span.start).or_insert(i);
+        ends.entry(spanned.span.end).or_insert(i);
+    }
+    let mut linter = Linter { module_ast, symbol_table, tokens, starts, ends, diagnostics: Vec::new() };
+    let fn_levels = module_ast.fn_defs.iter().map(|fn_def_ast| linter.fn_levels(fn_def_ast, levels)).collect::<Vec<_>>();
+    linter.lint_unused_fns(&fn_levels);
+    for (fn_def_ast, levels) in module_ast.fn_defs.iter().zip(&fn_levels) {
+        linter.lint_fn_def(fn_def_ast, levels);
+    }
+    let mut diagnostics = linter.diagnostics;
+    diagnostics.sort_by_key(|diagnostic| diagnostic.span.start);
+    diagnostics
+}
+
+struct Linter<'a> {
+    module_ast: &'a ModuleAst,
+    symbol_table: &'a SymbolTable,
+    tokens: Vec<&'a SpannedToken>,
+    /// The index of the token starting, and of the one ending, at each offset.
+    starts: HashMap<usize, usize>,
+    ends: HashMap<usize, usize>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Linter<'_> {
+    /// Applies the attributes of `fn_def_ast` to `levels`, reporting those that cannot be applied.
+    fn fn_levels(&mut self, fn_def_ast: &FnDefAst, levels: &Levels) -> Levels {
+        let mut levels = levels.clone();
+        for attr_ast in &fn_def_ast.attrs {
+            let Some(level) = Level::from_attr(&attr_ast.name.name) else {
+                let message = format!("Unknown attribute: {}", attr_ast.name.name);
+                self.diagnostics.push(Diagnostic::error(message, attr_ast.name.span));
+                continue;
+            };
+            if attr_ast.args.is_empty() {
+                self.diagnostics.push(Diagnostic::error("Lint name required.", attr_ast.span));
+            }
+            for arg in &attr_ast.args {
+                match Lint::from_name(&arg.name) {
+                    Some(lint) => levels.set(lint, level),
+                    None => self.diagnostics.push(Diagnostic::warning(unknown_lint(&arg.name), arg.span)),
+                }
+            }
+        }
+        levels
+    }
+
+    /// A function is used if it is public or `main`, or if a used function names it.
+    fn lint_unused_fns(&mut self, fn_levels: &[Levels]) {
+        let fn_defs = &self.module_ast.fn_defs;
+        let mut is_used = fn_defs.iter().map(|fn_def_ast| fn_def_ast.is_pub || fn_def_ast.left_def.ident.name == "main").collect::<Vec<_>>();
+        let mut stack = (0..fn_defs.len()).filter(|i| is_used[*i]).collect::<Vec<_>>();
+        while let Some(i) = stack.pop() {
+            for ident in fn_defs[i].expr.idents() {
+                if let Some(Def::Fn(j)) = self.symbol_table.lookup(i, &fn_defs[i], &ident.name) {
+                    if !is_used[j] {
+                        is_used[j] = true;
+                        stack.push(j);
+                    }
+                }
+            }
+        }
+        for (i, fn_def_ast) in fn_defs.iter().enumerate() {
+            let ident = &fn_def_ast.left_def.ident;
+            if !is_used[i] && !ident.name.starts_with('_') {
+                self.report(&fn_levels[i], Lint::UnusedFn, format!("Unused function: {}", ident.name), ident.span);
+            }
+        }
+    }
+
+    fn lint_fn_def(&mut self, fn_def_ast: &FnDefAst, levels: &Levels) {
+        let ident = &fn_def_ast.left_def.ident;
+        if builtin_arity(&ident.name).is_some() {
+            self.report(levels, Lint::ShadowedName, format!("Function {} shadows a builtin.", ident.name), ident.span);
+        }
+        let idents = fn_def_ast.expr.idents();
+        let args = &fn_def_ast.left_def.args;
+        for (i, arg) in args.iter().enumerate() {
+            if args[..i].iter().any(|prev| prev.name == arg.name) {
+                continue;
+            }
+            if self.symbol_table.global(&arg.name).is_some() {
+                self.report(levels, Lint::ShadowedName, format!("Parameter {} shadows a function.", arg.name), arg.span);
+            }
+            else if builtin_arity(&arg.name).is_some() {
+                self.report(levels, Lint::ShadowedName, format!("Parameter {} shadows a builtin.", arg.name), arg.span);
+            }
+            if !arg.name.starts_with('_') && !idents.iter().any(|ident| ident.name == arg.name) {
+                self.report(levels, Lint::UnusedParam, format!("Unused parameter: {}", arg.name), arg.span);
+            }
+        }
+        let redundant = printer::redundant_parens(&fn_def_ast.expr, &|expr_ast| self.parens(expr_ast.span()).0).into_iter()
+            .map(|expr_ast| self.parens(expr_ast.span()).1)
+            .collect::<Vec<_>>();
+        for span in redundant {
+            self.report(levels, Lint::RedundantParens, "Redundant parentheses.".to_owned(), span);
+        }
+    }
+
+    /// How many pairs of parentheses directly enclose `span`, and the span of the outermost one.
+    fn parens(&self, span: Span) -> (usize, Span) {
+        let (Some(&start), Some(&end)) = (self.starts.get(&span.start), self.ends.get(&span.end)) else {
+            return (0, span);
+        };
+        let (mut first, mut last) = (start, end);
+        while first > 0 && last + 1 < self.tokens.len() && self.tokens[first - 1].token == Token::LParen && self.tokens[last + 1].token == Token::RParen {
+            first -= 1;
+            last += 1;
+        }
+        (start - first, Span::new(self.tokens[first].span.start, self.tokens[last].span.end))
+    }
+
+    fn report(&mut self, levels: &Levels, lint: Lint, message: String, span: Span) {
+        match levels.get(lint) {
+            Level::Allow => (),
+            Level::Warn => self.diagnostics.push(Diagnostic::warning(message, span)),
+            Level::Deny => self.diagnostics.push(Diagnostic::error(message, span)),
+        }
+    }
+}
+
+fn unknown_lint(name: &str) -> String {
+    let max_distance = (name.chars().count() / 3).max(1);
+    let closest = Lint::ALL.into_iter().map(|lint| lint.name()).min_by_key(|lint_name| resolver::edit_distance(name, lint_name));
+    match closest.filter(|lint_name| resolver::edit_distance(name, lint_name) <= max_distance) {
+        Some(lint_name) => format!("Unknown lint: {} (did you mean {}?)", name, lint_name),
+        None => format!("Unknown lint: {}", name),
+    }
+}
@@ -0,0 +1,133 @@
+use crate::{
+    data::diagnostic::Severity,
+    lexer,
+    parser,
+    resolver,
+};
+use super::{
+    Level,
+    Levels,
+    Lint,
+};
+
+fn lint_with(code: &str, levels: &Levels) -> Vec<(Severity, String, usize, usize)> {
+    let tokens = lexer::lex(code.to_owned()).unwrap();
+    let module_ast = parser::parse(tokens.clone()).unwrap();
+    let symbol_table = resolver::resolve(&module_ast);
+    super::lint(&tokens, &module_ast, &symbol_table, levels).into_iter()
+        .map(|diagnostic| (diagnostic.severity, diagnostic.message, diagnostic.span.start, diagnostic.span.end))
+        .collect()
+}
+
+fn lint(code: &str) -> Vec<(Severity, String, usize, usize)> {
+    lint_with(code, &Levels::default())
+}
+
+fn messages(code: &str) -> Vec<String> {
+    lint(code).into_iter().map(|(_, message, _, _)| message).collect()
+}
+
+#[test]
+fn lint_unused_fns() {
+    let code = "pub f x = g x;\ng x = x;\nh x = i x;\ni x = h x;\nmain = 0;\n_j = 0;";
+    assert_eq!(
+        lint(code),
+        vec![
+            (Severity::Warning, "Unused function: h".to_owned(), 24, 25),
+            (Severity::Warning, "Unused function: i".to_owned(), 35, 36),
+        ]
+    );
+    assert_eq!(messages("module M;\npub f = M.g;\ng = 0;"), Vec::<String>::new());
+    assert_eq!(messages("f = map g [1];\ng x = x;"), vec!["Unused function: f", "Unused function: g"]);
+    assert_eq!(messages("pub f = map g [1];\ng x = x;"), Vec::<String>::new());
+}
+
+#[test]
+fn lint_unused_params() {
+    assert_eq!(
+        lint("pub f x y _z = x;\npub g x x = 0;"),
+        vec![
+            (Severity::Warning, "Unused parameter: y".to_owned(), 8, 9),
+            (Severity::Warning, "Unused parameter: x".to_owned(), 24, 25),
+        ]
+    );
+}
+
+#[test]
+fn lint_shadowed_names() {
+    assert_eq!(
+        lint("pub f g = g;\npub g map = map;\npub length xs = 0;"),
+        vec![
+            (Severity::Warning, "Parameter g shadows a function.".to_owned(), 6, 7),
+            (Severity::Warning, "Parameter map shadows a builtin.".to_owned(), 19, 22),
+            (Severity::Warning, "Function length shadows a builtin.".to_owned(), 34, 40),
+            (Severity::Warning, "Unused parameter: xs".to_owned(), 41, 43),
+        ]
+    );
+}
+
+#[test]
+fn lint_redundant_parens() {
+    assert_eq!(
+        lint("pub f x = (x + 1) * ((x)) + (f (x));"),
+        vec![
+            (Severity::Warning, "Redundant parentheses.".to_owned(), 20, 25),
+            (Severity::Warning, "Redundant parentheses.".to_owned(), 28, 35),
+            (Severity::Warning, "Redundant parentheses.".to_owned(), 31, 34),
+        ]
+    );
+    assert_eq!(messages("pub f x = (x);"), vec!["Redundant parentheses."]);
+    assert_eq!(messages("pub f x = -(x + 1) * (x - 1) :: f (-x) (f x);"), Vec::<String>::new());
+}
+
+#[test]
+fn lint_levels() {
+    let code = "pub f x = (x);\ng = 0;";
+    let mut levels = Levels::default();
+    levels.set(Lint::UnusedFn, Level::Deny);
+    levels.set(Lint::RedundantParens, Level::Allow);
+    assert_eq!(lint_with(code, &levels), vec![(Severity::Error, "Unused function: g".to_owned(), 15, 16)]);
+    let code = "#[allow(redundant_parens)]\npub f x = (x);\n#[warn(unused_fn)] #[deny(unused_param)]\ng y = 0;";
+    assert_eq!(
+        lint_with(code, &levels),
+        vec![
+            (Severity::Warning, "Unused function: g".to_owned(), 83, 84),
+            (Severity::Error, "Unused parameter: y".to_owned(), 85, 86),
+        ]
+    );
+}
+
+#[test]
+fn lint_attr_errors() {
+    assert_eq!(
+        lint("#[inline] #[allow] #[allow(unused_fns, unknown)]\ng = 0;"),
+        vec![
+            (Severity::Error, "Unknown attribute: inline".to_owned(), 2, 8),
+            (Severity::Error, "Lint name required.".to_owned(), 10, 18),
+            (Severity::Warning, "Unknown lint: unused_fns (did you mean unused_fn?)".to_owned(), 27, 37),
+            (Severity::Warning, "Unknown lint: unknown".to_owned(), 39, 46),
+            (Severity::Warning, "Unused function: g".to_owned(), 49, 50),
+        ]
+    );
+}
+
+#[test]
+fn check_errors() {
+    let code = "#[deny(unused_param)]\npub f x = 0;\ng = 0;";
+    let tokens = lexer::lex(code.to_owned()).unwrap();
+    let module_ast = parser::parse(tokens.clone()).unwrap();
+    let symbol_table = resolver::resolve(&module_ast);
+    let e = super::check(&tokens, &module_ast, &symbol_table, &Levels::default()).unwrap_err();
+    assert_eq!(e.to_string(), "Unused parameter: x");
+    let module_ast = parser::parse(lexer::lex("pub f x = x;\ng = 0;".to_owned()).unwrap()).unwrap();
+    let warnings = super::check(&[], &module_ast, &resolver::resolve(&module_ast), &Levels::default()).unwrap();
+    assert_eq!(warnings.len(), 1);
+}
+
+#[test]
+fn lint_names() {
+    for lint in Lint::ALL {
+        assert_eq!(Lint::from_name(lint.name()), Some(lint));
+    }
+    assert_eq!(Lint::from_name("unused"), None);
+}
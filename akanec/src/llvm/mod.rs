@@ -1,9 +1,17 @@
+#[cfg(test)]
+mod tests;
 mod pool;
 
 use std::{
     collections::HashMap,
-    ffi::CString,
+    ffi::{
+        CStr,
+        CString,
+    },
     fs,
+    mem,
+    os::raw::c_char,
+    ptr,
 };
 use anyhow::{
     bail,
@@ -13,32 +21,126 @@ use llvm_sys::{
     analysis::{
         LLVMVerifierFailureAction,
         LLVMVerifyFunction,
+        LLVMVerifyModule,
+    },
+    bit_writer::LLVMWriteBitcodeToFile,
+    execution_engine::{
+        LLVMAddModule,
+        LLVMCreateMCJITCompilerForModule,
+        LLVMDisposeExecutionEngine,
+        LLVMExecutionEngineRef,
+        LLVMFindFunction,
+        LLVMGetFunctionAddress,
+        LLVMInitializeMCJITCompilerOptions,
+        LLVMLinkInMCJIT,
+        LLVMMCJITCompilerOptions,
+    },
+    error::{
+        LLVMDisposeErrorMessage,
+        LLVMGetErrorMessage,
     },
     core::{
         LLVMAddFunction,
-        LLVMAppendBasicBlock,
+        LLVMAddGlobal,
+        LLVMAddIncoming,
+        LLVMAppendBasicBlockInContext,
         LLVMBuildAdd,
+        LLVMBuildBr,
         LLVMBuildCall2,
+        LLVMBuildCondBr,
+        LLVMBuildICmp,
+        LLVMBuildInBoundsGEP2,
+        LLVMBuildLoad2,
+        LLVMBuildMul,
+        LLVMBuildNeg,
+        LLVMBuildPhi,
+        LLVMBuildPointerCast,
         LLVMBuildRet,
+        LLVMBuildRetVoid,
+        LLVMBuildSDiv,
+        LLVMBuildSExt,
+        LLVMBuildSRem,
+        LLVMBuildStore,
+        LLVMBuildSub,
+        LLVMBuildTrunc,
+        LLVMBuildUnreachable,
+        LLVMBuildZExt,
         LLVMConstInt,
+        LLVMConstPointerCast,
+        LLVMConstStringInContext,
+        LLVMConstStructInContext,
         LLVMContextCreate,
         LLVMContextDispose,
         LLVMCountBasicBlocks,
-        LLVMCountParams,
         LLVMCreateBuilderInContext,
         LLVMDeleteFunction,
+        LLVMDeleteGlobal,
         LLVMDisposeBuilder,
         LLVMDisposeMessage,
         LLVMDisposeModule,
         LLVMFunctionType,
-        LLVMGetCalledFunctionType,
+        LLVMGetBasicBlockParent,
+        LLVMGetFirstFunction,
+        LLVMGetFirstGlobal,
+        LLVMGetInsertBlock,
+        LLVMGetIntrinsicID,
         LLVMGetNamedFunction,
+        LLVMGetNextFunction,
+        LLVMGetNextGlobal,
         LLVMGetParam,
+        LLVMGetUndef,
+        LLVMGetValueName2,
+        LLVMGlobalGetValueType,
+        LLVMInt8TypeInContext,
         LLVMInt32TypeInContext,
+        LLVMInt64TypeInContext,
+        LLVMIsDeclaration,
         LLVMModuleCreateWithNameInContext,
+        LLVMPointerType,
         LLVMPositionBuilderAtEnd,
         LLVMPrintModuleToString,
+        LLVMReplaceAllUsesWith,
+        LLVMSetGlobalConstant,
+        LLVMSetInitializer,
+        LLVMSetLinkage,
+        LLVMSetTarget,
+        LLVMSetUnnamedAddress,
         LLVMSetValueName2,
+        LLVMTypeOf,
+        LLVMVoidTypeInContext,
+    },
+    LLVMIntPredicate,
+    LLVMLinkage,
+    LLVMUnnamedAddr,
+    support::{
+        LLVMLoadLibraryPermanently,
+        LLVMSearchForAddressOfSymbol,
+    },
+    target::{
+        LLVM_InitializeNativeAsmPrinter,
+        LLVM_InitializeNativeTarget,
+        LLVMDisposeTargetData,
+        LLVMSetModuleDataLayout,
+    },
+    transforms::pass_builder::{
+        LLVMCreatePassBuilderOptions,
+        LLVMDisposePassBuilderOptions,
+        LLVMPassBuilderOptionsSetVerifyEach,
+        LLVMRunPasses,
+    },
+    target_machine::{
+        LLVMCodeGenFileType,
+        LLVMCodeGenOptLevel,
+        LLVMCodeModel,
+        LLVMCreateTargetDataLayout,
+        LLVMCreateTargetMachine,
+        LLVMDisposeTargetMachine,
+        LLVMGetDefaultTargetTriple,
+        LLVMGetTargetFromTriple,
+        LLVMRelocMode,
+        LLVMTargetMachineEmitToFile,
+        LLVMTargetMachineRef,
+        LLVMTargetRef,
     },
     prelude::{
         LLVMBasicBlockRef,
@@ -53,6 +155,10 @@ use pool::{
     CStrPool,
     SlicePool,
 };
+use crate::data::ty::{
+    Ty,
+    FnTy,
+};
 
 pub trait Ptr: Copy {
     fn is_null(&self) -> bool;
@@ -80,7 +186,12 @@ pub struct LLVM {
     context: LLVMContextRef,
     module: LLVMModuleRef,
     builder: LLVMBuilderRef,
-    named_values: HashMap<String, LLVMValueRef>,
+    named_values: HashMap<String, (LLVMValueRef, Ty)>,
+    fn_tys: HashMap<String, FnTy>,
+    verify_each: bool,
+    engine: Option<LLVMExecutionEngineRef>,
+    /// While a module is generated to be added to the engine, the module the engine was last given.
+    jit_module: Option<LLVMModuleRef>,
     c_str_pool: CStrPool,
     type_slice_pool: SlicePool<LLVMTypeRef>,
     value_slice_pool: SlicePool<LLVMValueRef>,
@@ -90,7 +201,13 @@ impl Drop for LLVM {
     fn drop(&mut self) {
         unsafe {
             LLVMDisposeBuilder(self.builder);
-            LLVMDisposeModule(self.module);
+            if self.jit_module.is_some() {
+                LLVMDisposeModule(self.module);
+            }
+            match self.engine {
+                Some(engine) => LLVMDisposeExecutionEngine(engine),
+                None => LLVMDisposeModule(self.module),
+            }
             LLVMContextDispose(self.context);
         }
     }
@@ -109,6 +226,10 @@ impl LLVM {
                 module,
                 builder,
                 named_values: HashMap::new(),
+                fn_tys: HashMap::new(),
+                verify_each: false,
+                engine: None,
+                jit_module: None,
                 c_str_pool,
                 type_slice_pool: SlicePool::new(),
                 value_slice_pool: SlicePool::new(),
@@ -116,24 +237,58 @@ impl LLVM {
         }
     }
 
+    pub fn verify_each(&self) -> bool {
+        self.verify_each
+    }
+
+    pub fn set_verify_each(&mut self, verify_each: bool) {
+        self.verify_each = verify_each;
+    }
+
+    pub fn fn_tys(&self) -> &HashMap<String, FnTy> {
+        &self.fn_tys
+    }
+
     pub fn clear_named_value(&mut self) {
         self.named_values.clear();
     }
 
-    pub fn get_named_value(&self, name: &str) -> Result<LLVMValueRef> {
+    pub fn get_named_value(&self, name: &str) -> Result<(LLVMValueRef, Ty)> {
         match self.named_values.get(name) {
-            Some(value) => Ok(*value),
+            Some((value, ty)) => Ok((*value, ty.clone())),
             None => bail!("Unknown identifier."),
         }
     }
 
-    pub fn insert_named_value(&mut self, name: String, value: LLVMValueRef) -> Result<LLVMValueRef> {
-        match self.named_values.insert(name, value) {
+    pub fn insert_named_value(&mut self, name: String, value: LLVMValueRef, ty: Ty) -> Result<LLVMValueRef> {
+        match self.named_values.insert(name, (value, ty)) {
             Some(_) => bail!("Duplicate identifier name."),
             None => Ok(value),
         }
     }
 
+    pub fn get_fn_ty(&self, name: &str) -> Result<FnTy> {
+        match self.fn_tys.get(name) {
+            Some(fn_ty) => Ok(fn_ty.clone()),
+            None => bail!("Unknown function."),
+        }
+    }
+
+    pub fn set_fn_tys(&mut self, fn_tys: HashMap<String, FnTy>) {
+        self.fn_tys = fn_tys;
+    }
+
+    pub fn remove_fn_ty(&mut self, name: &str) -> Option<FnTy> {
+        self.fn_tys.remove(name)
+    }
+
+    pub fn insert_fn_ty(&mut self, name: String, fn_ty: FnTy) -> Result<()> {
+        match self.fn_tys.insert(name, fn_ty) {
+            Some(_) => bail!("Function cannot be redefined."),
+            None => Ok(()),
+        }
+    }
+
     fn ptr_to_result<LLVMRef: Ptr>(value: LLVMRef) -> Result<LLVMRef> {
         if value.is_null() {
             bail!("Pointer is null.")
@@ -143,6 +298,22 @@ impl LLVM {
         }
     }
 
+    pub fn void_type(&self) -> Result<LLVMTypeRef> {
+        unsafe {
+            Self::ptr_to_result(
+                LLVMVoidTypeInContext(self.context)
+            )
+        }
+    }
+
+    pub fn int8_type(&self) -> Result<LLVMTypeRef> {
+        unsafe {
+            Self::ptr_to_result(
+                LLVMInt8TypeInContext(self.context)
+            )
+        }
+    }
+
     pub fn int32_type(&self) -> Result<LLVMTypeRef> {
         unsafe {
             Self::ptr_to_result(
@@ -151,6 +322,30 @@ impl LLVM {
         }
     }
 
+    pub fn int64_type(&self) -> Result<LLVMTypeRef> {
+        unsafe {
+            Self::ptr_to_result(
+                LLVMInt64TypeInContext(self.context)
+            )
+        }
+    }
+
+    pub fn pointer_type(elem_ty: LLVMTypeRef) -> Result<LLVMTypeRef> {
+        unsafe {
+            Self::ptr_to_result(
+                LLVMPointerType(elem_ty, 0)
+            )
+        }
+    }
+
+    pub fn type_of(value: LLVMValueRef) -> Result<LLVMTypeRef> {
+        unsafe {
+            Self::ptr_to_result(
+                LLVMTypeOf(value)
+            )
+        }
+    }
+
     pub fn function_type(&mut self, ret_ty: LLVMTypeRef, arg_tys: Vec<LLVMTypeRef>) -> Result<LLVMTypeRef> {
         unsafe {
             let arg_count = arg_tys.len() as u32;
@@ -161,10 +356,10 @@ impl LLVM {
         }
     }
 
-    pub fn get_called_function_type(fn_value: LLVMValueRef) -> Result<LLVMTypeRef> {
+    pub fn global_get_value_type(global: LLVMValueRef) -> Result<LLVMTypeRef> {
         unsafe {
             Self::ptr_to_result(
-                LLVMGetCalledFunctionType(fn_value)
+                LLVMGlobalGetValueType(global)
             )
         }
     }
@@ -178,6 +373,82 @@ impl LLVM {
         }
     }
 
+    pub fn const_int64(&self, value: u64) -> Result<LLVMValueRef> {
+        unsafe {
+            let ty = LLVMInt64TypeInContext(self.context);
+            Self::ptr_to_result(
+                LLVMConstInt(ty, value, 0)
+            )
+        }
+    }
+
+    pub fn const_int8(&self, value: u8) -> Result<LLVMValueRef> {
+        unsafe {
+            let ty = LLVMInt8TypeInContext(self.context);
+            Self::ptr_to_result(
+                LLVMConstInt(ty, value as u64, 0)
+            )
+        }
+    }
+
+    pub fn const_string(&mut self, bytes: &[u8]) -> Result<LLVMValueRef> {
+        unsafe {
+            Self::ptr_to_result(
+                LLVMConstStringInContext(self.context, bytes.as_ptr() as *const i8, bytes.len() as u32, 0)
+            )
+        }
+    }
+
+    pub fn const_struct(&mut self, values: Vec<LLVMValueRef>) -> Result<LLVMValueRef> {
+        unsafe {
+            let value_count = values.len() as u32;
+            let value_vec = self.value_slice_pool.slice(&values);
+            Self::ptr_to_result(
+                LLVMConstStructInContext(self.context, value_vec, value_count, 0)
+            )
+        }
+    }
+
+    pub fn const_pointer_cast(value: LLVMValueRef, ty: LLVMTypeRef) -> Result<LLVMValueRef> {
+        unsafe {
+            Self::ptr_to_result(
+                LLVMConstPointerCast(value, ty)
+            )
+        }
+    }
+
+    pub fn add_global(&mut self, ty: LLVMTypeRef, name: &str) -> Result<LLVMValueRef> {
+        unsafe {
+            Self::ptr_to_result(
+                LLVMAddGlobal(self.module, ty, self.c_str_pool.c_str(name))
+            )
+        }
+    }
+
+    pub fn set_initializer(global: LLVMValueRef, value: LLVMValueRef) {
+        unsafe {
+            LLVMSetInitializer(global, value)
+        }
+    }
+
+    pub fn set_global_constant(global: LLVMValueRef, is_constant: bool) {
+        unsafe {
+            LLVMSetGlobalConstant(global, is_constant as i32)
+        }
+    }
+
+    pub fn set_linkage(global: LLVMValueRef, linkage: LLVMLinkage) {
+        unsafe {
+            LLVMSetLinkage(global, linkage)
+        }
+    }
+
+    pub fn set_unnamed_address(global: LLVMValueRef, unnamed_addr: LLVMUnnamedAddr) {
+        unsafe {
+            LLVMSetUnnamedAddress(global, unnamed_addr)
+        }
+    }
+
     pub fn set_value_name(&mut self, value: LLVMValueRef, name: &str) {
         unsafe {
             LLVMSetValueName2(value, self.c_str_pool.c_str(name), name.len())
@@ -206,24 +477,62 @@ impl LLVM {
         }
     }
 
-    pub fn get_param(fn_value: LLVMValueRef, index: usize) -> Result<LLVMValueRef> {
+    pub fn functions(&self) -> Vec<LLVMValueRef> {
+        let mut functions = Vec::new();
+        unsafe {
+            let mut f = LLVMGetFirstFunction(self.module);
+            while !f.is_null() {
+                functions.push(f);
+                f = LLVMGetNextFunction(f);
+            }
+        }
+        functions
+    }
+
+    pub fn globals(&self) -> Vec<LLVMValueRef> {
+        let mut globals = Vec::new();
+        unsafe {
+            let mut global = LLVMGetFirstGlobal(self.module);
+            while !global.is_null() {
+                globals.push(global);
+                global = LLVMGetNextGlobal(global);
+            }
+        }
+        globals
+    }
+
+    pub fn delete_global(global: LLVMValueRef) {
+        unsafe {
+            LLVMDeleteGlobal(global)
+        }
+    }
+
+    pub fn get_undef(ty: LLVMTypeRef) -> Result<LLVMValueRef> {
         unsafe {
             Self::ptr_to_result(
-                LLVMGetParam(fn_value, index as u32)
+                LLVMGetUndef(ty)
             )
         }
     }
 
-    pub fn count_params(fn_value: LLVMValueRef) -> usize {
+    pub fn replace_all_uses_with(old_value: LLVMValueRef, new_value: LLVMValueRef) {
+        unsafe {
+            LLVMReplaceAllUsesWith(old_value, new_value)
+        }
+    }
+
+    pub fn get_param(fn_value: LLVMValueRef, index: usize) -> Result<LLVMValueRef> {
         unsafe {
-            LLVMCountParams(fn_value) as usize
+            Self::ptr_to_result(
+                LLVMGetParam(fn_value, index as u32)
+            )
         }
     }
 
     pub fn append_basic_block(&mut self, fn_value: LLVMValueRef, name: &str) -> Result<LLVMBasicBlockRef> {
         unsafe {
             Self::ptr_to_result(
-                LLVMAppendBasicBlock(fn_value, self.c_str_pool.c_str(name))
+                LLVMAppendBasicBlockInContext(self.context, fn_value, self.c_str_pool.c_str(name))
             )
         }
     }
@@ -240,6 +549,26 @@ impl LLVM {
         }
     }
 
+    pub fn get_insert_block(&self) -> Option<LLVMBasicBlockRef> {
+        unsafe {
+            let block = LLVMGetInsertBlock(self.builder);
+            if block.is_null() {
+                None
+            }
+            else {
+                Some(block)
+            }
+        }
+    }
+
+    pub fn get_basic_block_parent(block: LLVMBasicBlockRef) -> Result<LLVMValueRef> {
+        unsafe {
+            Self::ptr_to_result(
+                LLVMGetBasicBlockParent(block)
+            )
+        }
+    }
+
     pub fn build_call(&mut self, fn_ty: LLVMTypeRef, fn_value: LLVMValueRef, args: Vec<LLVMValueRef>, name: &str) -> Result<LLVMValueRef> {
         unsafe {
             let arg_count = args.len() as u32;
@@ -259,6 +588,156 @@ impl LLVM {
         }
     }
 
+    pub fn build_sub(&mut self, lhs: LLVMValueRef, rhs: LLVMValueRef, name: &str) -> Result<LLVMValueRef> {
+        unsafe {
+            Self::ptr_to_result(
+                LLVMBuildSub(self.builder, lhs, rhs, self.c_str_pool.c_str(name))
+            )
+        }
+    }
+
+    pub fn build_mul(&mut self, lhs: LLVMValueRef, rhs: LLVMValueRef, name: &str) -> Result<LLVMValueRef> {
+        unsafe {
+            Self::ptr_to_result(
+                LLVMBuildMul(self.builder, lhs, rhs, self.c_str_pool.c_str(name))
+            )
+        }
+    }
+
+    pub fn build_sdiv(&mut self, lhs: LLVMValueRef, rhs: LLVMValueRef, name: &str) -> Result<LLVMValueRef> {
+        unsafe {
+            Self::ptr_to_result(
+                LLVMBuildSDiv(self.builder, lhs, rhs, self.c_str_pool.c_str(name))
+            )
+        }
+    }
+
+    pub fn build_srem(&mut self, lhs: LLVMValueRef, rhs: LLVMValueRef, name: &str) -> Result<LLVMValueRef> {
+        unsafe {
+            Self::ptr_to_result(
+                LLVMBuildSRem(self.builder, lhs, rhs, self.c_str_pool.c_str(name))
+            )
+        }
+    }
+
+    pub fn build_neg(&mut self, value: LLVMValueRef, name: &str) -> Result<LLVMValueRef> {
+        unsafe {
+            Self::ptr_to_result(
+                LLVMBuildNeg(self.builder, value, self.c_str_pool.c_str(name))
+            )
+        }
+    }
+
+    pub fn build_icmp(&mut self, op: LLVMIntPredicate, lhs: LLVMValueRef, rhs: LLVMValueRef, name: &str) -> Result<LLVMValueRef> {
+        unsafe {
+            Self::ptr_to_result(
+                LLVMBuildICmp(self.builder, op, lhs, rhs, self.c_str_pool.c_str(name))
+            )
+        }
+    }
+
+    pub fn build_zext(&mut self, value: LLVMValueRef, ty: LLVMTypeRef, name: &str) -> Result<LLVMValueRef> {
+        unsafe {
+            Self::ptr_to_result(
+                LLVMBuildZExt(self.builder, value, ty, self.c_str_pool.c_str(name))
+            )
+        }
+    }
+
+    pub fn build_sext(&mut self, value: LLVMValueRef, ty: LLVMTypeRef, name: &str) -> Result<LLVMValueRef> {
+        unsafe {
+            Self::ptr_to_result(
+                LLVMBuildSExt(self.builder, value, ty, self.c_str_pool.c_str(name))
+            )
+        }
+    }
+
+    pub fn build_trunc(&mut self, value: LLVMValueRef, ty: LLVMTypeRef, name: &str) -> Result<LLVMValueRef> {
+        unsafe {
+            Self::ptr_to_result(
+                LLVMBuildTrunc(self.builder, value, ty, self.c_str_pool.c_str(name))
+            )
+        }
+    }
+
+    pub fn build_pointer_cast(&mut self, value: LLVMValueRef, ty: LLVMTypeRef, name: &str) -> Result<LLVMValueRef> {
+        unsafe {
+            Self::ptr_to_result(
+                LLVMBuildPointerCast(self.builder, value, ty, self.c_str_pool.c_str(name))
+            )
+        }
+    }
+
+    pub fn build_in_bounds_gep(&mut self, ty: LLVMTypeRef, ptr: LLVMValueRef, indices: Vec<LLVMValueRef>, name: &str) -> Result<LLVMValueRef> {
+        unsafe {
+            let index_count = indices.len() as u32;
+            let index_vec = self.value_slice_pool.slice(&indices);
+            Self::ptr_to_result(
+                LLVMBuildInBoundsGEP2(self.builder, ty, ptr, index_vec, index_count, self.c_str_pool.c_str(name))
+            )
+        }
+    }
+
+    pub fn build_load(&mut self, ty: LLVMTypeRef, ptr: LLVMValueRef, name: &str) -> Result<LLVMValueRef> {
+        unsafe {
+            Self::ptr_to_result(
+                LLVMBuildLoad2(self.builder, ty, ptr, self.c_str_pool.c_str(name))
+            )
+        }
+    }
+
+    pub fn build_store(&self, value: LLVMValueRef, ptr: LLVMValueRef) -> Result<LLVMValueRef> {
+        unsafe {
+            Self::ptr_to_result(
+                LLVMBuildStore(self.builder, value, ptr)
+            )
+        }
+    }
+
+    pub fn build_br(&self, dest: LLVMBasicBlockRef) -> Result<LLVMValueRef> {
+        unsafe {
+            Self::ptr_to_result(
+                LLVMBuildBr(self.builder, dest)
+            )
+        }
+    }
+
+    pub fn build_cond_br(&self, cond: LLVMValueRef, then_block: LLVMBasicBlockRef, else_block: LLVMBasicBlockRef) -> Result<LLVMValueRef> {
+        unsafe {
+            Self::ptr_to_result(
+                LLVMBuildCondBr(self.builder, cond, then_block, else_block)
+            )
+        }
+    }
+
+    pub fn build_phi(&mut self, ty: LLVMTypeRef, incoming: Vec<(LLVMValueRef, LLVMBasicBlockRef)>, name: &str) -> Result<LLVMValueRef> {
+        unsafe {
+            let phi = Self::ptr_to_result(
+                LLVMBuildPhi(self.builder, ty, self.c_str_pool.c_str(name))
+            )?;
+            for (value, block) in incoming {
+                self.add_incoming(phi, value, block);
+            }
+            Ok(phi)
+        }
+    }
+
+    pub fn add_incoming(&mut self, phi: LLVMValueRef, value: LLVMValueRef, block: LLVMBasicBlockRef) {
+        unsafe {
+            let mut value = value;
+            let mut block = block;
+            LLVMAddIncoming(phi, &mut value, &mut block, 1)
+        }
+    }
+
+    pub fn build_unreachable(&self) -> Result<LLVMValueRef> {
+        unsafe {
+            Self::ptr_to_result(
+                LLVMBuildUnreachable(self.builder)
+            )
+        }
+    }
+
     pub fn build_ret(&self, ret_value: LLVMValueRef) -> Result<LLVMValueRef> {
         unsafe {
             Self::ptr_to_result(
@@ -267,9 +746,35 @@ impl LLVM {
         }
     }
 
+    pub fn build_ret_void(&self) -> Result<LLVMValueRef> {
+        unsafe {
+            Self::ptr_to_result(
+                LLVMBuildRetVoid(self.builder)
+            )
+        }
+    }
+
     pub fn verify_function(fn_value: LLVMValueRef) -> bool {
         unsafe {
-            LLVMVerifyFunction(fn_value, LLVMVerifierFailureAction::LLVMPrintMessageAction) == 0
+            LLVMVerifyFunction(fn_value, LLVMVerifierFailureAction::LLVMReturnStatusAction) == 0
+        }
+    }
+
+    pub fn verify_module(&self) -> Result<()> {
+        unsafe {
+            let mut message = ptr::null_mut();
+            let failed = LLVMVerifyModule(self.module, LLVMVerifierFailureAction::LLVMReturnStatusAction, &mut message);
+            let message = Self::take_message(message);
+            if failed != 0 {
+                bail!("Invalid module: {}", message.trim_end());
+            }
+            Ok(())
+        }
+    }
+
+    pub fn print_module_to_string(&self) -> String {
+        unsafe {
+            Self::take_message(LLVMPrintModuleToString(self.module))
         }
     }
 
@@ -281,4 +786,216 @@ impl LLVM {
             Ok(())
         }
     }
+
+    pub fn write_bitcode_to_file(&mut self, path: &str) -> Result<()> {
+        unsafe {
+            let path = self.c_str_pool.c_str(path);
+            if LLVMWriteBitcodeToFile(self.module, path) != 0 {
+                bail!("Cannot write bitcode.");
+            }
+            Ok(())
+        }
+    }
+
+    pub fn run_passes(&mut self, passes: &str) -> Result<()> {
+        unsafe {
+            let machine = self.create_target_machine(LLVMCodeGenOptLevel::LLVMCodeGenLevelDefault)?;
+            let passes = self.c_str_pool.c_str(passes);
+            let options = LLVMCreatePassBuilderOptions();
+            LLVMPassBuilderOptionsSetVerifyEach(options, self.verify_each as i32);
+            let error = LLVMRunPasses(self.module, passes, machine, options);
+            LLVMDisposePassBuilderOptions(options);
+            LLVMDisposeTargetMachine(machine);
+            if !error.is_null() {
+                let message = LLVMGetErrorMessage(error);
+                let s = CStr::from_ptr(message).to_string_lossy().into_owned();
+                LLVMDisposeErrorMessage(message);
+                bail!("Cannot run passes: {}", s);
+            }
+            Ok(())
+        }
+    }
+
+    /// `opt_level` is the codegen level from 0 to 3, as for `create_jit`.
+    pub fn emit_to_file(&mut self, path: &str, file_type: LLVMCodeGenFileType, opt_level: u32) -> Result<()> {
+        let opt_level = match opt_level {
+            0 => LLVMCodeGenOptLevel::LLVMCodeGenLevelNone,
+            1 => LLVMCodeGenOptLevel::LLVMCodeGenLevelLess,
+            2 => LLVMCodeGenOptLevel::LLVMCodeGenLevelDefault,
+            _ => LLVMCodeGenOptLevel::LLVMCodeGenLevelAggressive,
+        };
+        unsafe {
+            let machine = self.create_target_machine(opt_level)?;
+            let path = self.c_str_pool.c_str(path);
+            let mut message = ptr::null_mut();
+            let failed = LLVMTargetMachineEmitToFile(machine, self.module, path as *mut c_char, file_type, &mut message);
+            LLVMDisposeTargetMachine(machine);
+            if failed != 0 {
+                bail!("Cannot emit file: {}", Self::take_message(message));
+            }
+            Ok(())
+        }
+    }
+
+    pub fn create_jit(&mut self, opt_level: u32) -> Result<()> {
+        if self.engine.is_some() {
+            bail!("JIT is already created.");
+        }
+        unsafe {
+            Self::initialize_native_target()?;
+            LLVMLinkInMCJIT();
+            let unresolved = self.unresolved_externs();
+            if !unresolved.is_empty() {
+                bail!("Unresolved external functions: {}", unresolved.join(", "));
+            }
+            let mut options: LLVMMCJITCompilerOptions = mem::zeroed();
+            let size = mem::size_of::<LLVMMCJITCompilerOptions>();
+            LLVMInitializeMCJITCompilerOptions(&mut options, size);
+            options.OptLevel = opt_level;
+            let mut engine = ptr::null_mut();
+            let mut message = ptr::null_mut();
+            if LLVMCreateMCJITCompilerForModule(&mut engine, self.module, &mut options, size, &mut message) != 0 {
+                bail!("Cannot create JIT: {}", Self::take_message(message));
+            }
+            self.engine = Some(engine);
+            Ok(())
+        }
+    }
+
+    /// Starts generating a module of its own in the same context, to be added to the engine
+    /// with `add_module` or thrown away with `discard_module`.
+    pub fn new_module(&mut self, module_id: &str) -> Result<()> {
+        if self.engine.is_none() {
+            bail!("JIT is not created.");
+        }
+        if self.jit_module.is_some() {
+            bail!("Module is already being generated.");
+        }
+        unsafe {
+            let module = LLVMModuleCreateWithNameInContext(self.c_str_pool.c_str(module_id), self.context);
+            self.jit_module = Some(mem::replace(&mut self.module, module));
+        }
+        Ok(())
+    }
+
+    /// Hands the module being generated to the engine, resolving its declarations
+    /// against the modules added before and the process.
+    pub fn add_module(&mut self) -> Result<()> {
+        let (Some(engine), Some(_)) = (self.engine, self.jit_module) else {
+            bail!("No module is being generated.");
+        };
+        let unresolved = self.unresolved_externs();
+        if !unresolved.is_empty() {
+            bail!("Unresolved external functions: {}", unresolved.join(", "));
+        }
+        unsafe {
+            LLVMAddModule(engine, self.module);
+        }
+        self.jit_module = None;
+        Ok(())
+    }
+
+    pub fn discard_module(&mut self) {
+        if let Some(module) = self.jit_module.take() {
+            unsafe {
+                LLVMDisposeModule(self.module);
+            }
+            self.module = module;
+        }
+    }
+
+    pub fn get_function_address(&mut self, name: &str) -> Result<u64> {
+        let engine = match self.engine {
+            Some(engine) => engine,
+            None => bail!("JIT is not created."),
+        };
+        unsafe {
+            match LLVMGetFunctionAddress(engine, self.c_str_pool.c_str(name)) {
+                0 => bail!("Unknown function."),
+                address => Ok(address),
+            }
+        }
+    }
+
+    fn unresolved_externs(&self) -> Vec<String> {
+        let mut unresolved = Vec::new();
+        unsafe {
+            LLVMLoadLibraryPermanently(ptr::null());
+            let mut f = LLVMGetFirstFunction(self.module);
+            while !f.is_null() {
+                if LLVMIsDeclaration(f) != 0 && LLVMGetIntrinsicID(f) == 0 {
+                    let mut len = 0;
+                    let name = LLVMGetValueName2(f, &mut len);
+                    if LLVMSearchForAddressOfSymbol(name).is_null() && !self.engine_defines(name) {
+                        unresolved.push(CStr::from_ptr(name).to_string_lossy().into_owned());
+                    }
+                }
+                f = LLVMGetNextFunction(f);
+            }
+        }
+        unresolved
+    }
+
+    fn engine_defines(&self, name: *const c_char) -> bool {
+        let Some(engine) = self.engine else {
+            return false;
+        };
+        unsafe {
+            let mut f = ptr::null_mut();
+            LLVMFindFunction(engine, name, &mut f) == 0
+        }
+    }
+
+    fn initialize_native_target() -> Result<()> {
+        unsafe {
+            if LLVM_InitializeNativeTarget() != 0 || LLVM_InitializeNativeAsmPrinter() != 0 {
+                bail!("Cannot initialize native target.");
+            }
+            Ok(())
+        }
+    }
+
+    fn create_target_machine(&mut self, opt_level: LLVMCodeGenOptLevel) -> Result<LLVMTargetMachineRef> {
+        unsafe {
+            Self::initialize_native_target()?;
+            let triple = LLVMGetDefaultTargetTriple();
+            let mut target: LLVMTargetRef = ptr::null_mut();
+            let mut message = ptr::null_mut();
+            if LLVMGetTargetFromTriple(triple, &mut target, &mut message) != 0 {
+                let triple = Self::take_message(triple);
+                bail!("Unknown target {}: {}", triple, Self::take_message(message));
+            }
+            let cpu = self.c_str_pool.c_str("generic");
+            let features = self.c_str_pool.c_str("");
+            let machine = LLVMCreateTargetMachine(
+                target,
+                triple,
+                cpu,
+                features,
+                opt_level,
+                LLVMRelocMode::LLVMRelocPIC,
+                LLVMCodeModel::LLVMCodeModelDefault,
+            );
+            LLVMSetTarget(self.module, triple);
+            Self::take_message(triple);
+            if machine.is_null() {
+                bail!("Cannot create target machine.");
+            }
+            let data_layout = LLVMCreateTargetDataLayout(machine);
+            LLVMSetModuleDataLayout(self.module, data_layout);
+            LLVMDisposeTargetData(data_layout);
+            Ok(machine)
+        }
+    }
+
+    fn take_message(message: *mut c_char) -> String {
+        if message.is_null() {
+            return String::new();
+        }
+        unsafe {
+            let s = CStr::from_ptr(message).to_string_lossy().into_owned();
+            LLVMDisposeMessage(message);
+            s
+        }
+    }
 }
@@ -0,0 +1,29 @@
+use super::LLVM;
+
+fn gen_add_one(llvm: &mut LLVM, ret_value: fn(&LLVM) -> super::LLVMValueRef) -> super::LLVMValueRef {
+    let int_ty = llvm.int32_type().unwrap();
+    let f_ty = llvm.function_type(int_ty, vec![int_ty]).unwrap();
+    let f = llvm.add_function("add_one", f_ty).unwrap();
+    let block = llvm.append_basic_block(f, "entry").unwrap();
+    llvm.position_builder_at_end(block);
+    let value = ret_value(llvm);
+    llvm.build_ret(value).unwrap();
+    f
+}
+
+#[test]
+fn verify_valid_module() {
+    let mut llvm = LLVM::new("valid");
+    let f = gen_add_one(&mut llvm, |llvm| llvm.const_int(1, 0).unwrap());
+    assert!(LLVM::verify_function(f));
+    assert!(llvm.verify_module().is_ok());
+}
+
+#[test]
+fn verify_invalid_module() {
+    let mut llvm = LLVM::new("invalid");
+    let f = gen_add_one(&mut llvm, |llvm| llvm.const_int8(1).unwrap());
+    assert!(!LLVM::verify_function(f));
+    let message = llvm.verify_module().unwrap_err().to_string();
+    assert!(message.starts_with("Invalid module: Function return type does not match operand type of return inst!"));
+}
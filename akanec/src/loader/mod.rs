@@ -0,0 +1,267 @@
+#[cfg(test)]
+mod tests;
+
+use std::{
+    collections::{
+        HashMap,
+        HashSet,
+    },
+    fs,
+    path::{
+        Path,
+        PathBuf,
+    },
+    rc::Rc,
+};
+use anyhow::{
+    bail,
+    Result,
+};
+use crate::{
+    data::{
+        ast::{
+            ModuleAst,
+            FnDefAst,
+            LeftDefAst,
+            ExprAst,
+            FnAst,
+            PrefixOpAst,
+            InfixOpAst,
+            IdentAst,
+            ListAst,
+        },
+        token::SpannedToken,
+    },
+    dump,
+    lexer,
+    parser,
+};
+
+/// The module a program was loaded from as written, for the passes that work on a single file
+/// rather than on the linked module.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Source {
+    pub tokens: Vec<SpannedToken>,
+    pub module_ast: ModuleAst,
+}
+
+pub fn load(in_path: &str, search_paths: &[String]) -> Result<ModuleAst> {
+    load_with_source(in_path, search_paths).map(|(module_ast, _)| module_ast)
+}
+
+/// Also returns the root module as written, unless it was read from JSON and has no tokens.
+pub fn load_with_source(in_path: &str, search_paths: &[String]) -> Result<(ModuleAst, Option<Source>)> {
+    let mut dirs = Vec::new();
+    if let Some(dir) = Path::new(in_path).parent() {
+        dirs.push(dir.to_path_buf());
+    }
+    dirs.extend(search_paths.iter().map(PathBuf::from));
+    let (root, source) = read_source(Path::new(in_path))?;
+    let mut modules = Vec::new();
+    let mut loaded = HashSet::new();
+    let mut stack = Vec::new();
+    load_imports(&root, &dirs, &mut modules, &mut loaded, &mut stack)?;
+    modules.push(root);
+    Ok((link(&modules)?, source))
+}
+
+pub fn link(modules: &Vec<ModuleAst>) -> Result<ModuleAst> {
+    let mut fn_names = HashMap::new();
+    for module in modules {
+        let name = module.name.as_ref().map(|name| name.name.clone());
+        let names = module.fn_defs.iter().map(|fn_def| (fn_def.left_def.ident.name.clone(), fn_def.is_pub)).collect::<HashMap<_, _>>();
+        if fn_names.insert(name.clone(), names).is_some() {
+            match name {
+                Some(name) => bail!("Module defined twice: {}", name),
+                None => bail!("Only one module can be anonymous."),
+            }
+        }
+    }
+    let mut extern_fns = Vec::new();
+    let mut fn_defs = Vec::new();
+    for module in modules {
+        let scope = Scope::new(module, &fn_names)?;
+        extern_fns.extend(module.extern_fns.iter().cloned());
+        for fn_def in &module.fn_defs {
+            fn_defs.push(scope.qualify_fn_def(fn_def)?);
+        }
+    }
+    Ok(ModuleAst { name: None, imports: Vec::new(), extern_fns, fn_defs })
+}
+
+/// Reads a single module without resolving its imports.
+pub fn read_module(path: &Path) -> Result<ModuleAst> {
+    read_source(path).map(|(module_ast, _)| module_ast)
+}
+
+fn read_source(path: &Path) -> Result<(ModuleAst, Option<Source>)> {
+    let code = match fs::read_to_string(path) {
+        Ok(code) => code,
+        Err(e) => bail!("Cannot read {}: {}", path.display(), e),
+    };
+    if path.extension().is_some_and(|ext| ext == "json") {
+        return Ok((dump::ast_from_json(&code)?, None));
+    }
+    let tokens = lexer::lex(code)?;
+    let module_ast = parser::parse(tokens.clone())?;
+    Ok((module_ast.clone(), Some(Source { tokens, module_ast })))
+}
+
+fn load_imports(module: &ModuleAst, dirs: &[PathBuf], modules: &mut Vec<ModuleAst>, loaded: &mut HashSet<String>, stack: &mut Vec<String>) -> Result<()> {
+    if let Some(name) = &module.name {
+        stack.push(name.name.clone());
+    }
+    for import in &module.imports {
+        let name = &import.module.name;
+        if stack.contains(name) {
+            let mut cycle = stack.clone();
+            cycle.push(name.clone());
+            let start = cycle.iter().position(|n| n == name).unwrap();
+            bail!("Cyclic import: {}", cycle[start..].join(" -> "));
+        }
+        if loaded.contains(name) {
+            continue;
+        }
+        let path = find_module(name, dirs)?;
+        let imported = read_module(&path)?;
+        match &imported.name {
+            Some(ident) if ident.name == *name => (),
+            _ => bail!("{} must declare `module {};`", path.display(), name),
+        }
+        load_imports(&imported, dirs, modules, loaded, stack)?;
+        loaded.insert(name.clone());
+        modules.push(imported);
+    }
+    if module.name.is_some() {
+        stack.pop();
+    }
+    Ok(())
+}
+
+pub fn find_module(name: &str, dirs: &[PathBuf]) -> Result<PathBuf> {
+    let relative = format!("{}.akane", name.replace('.', "/"));
+    for dir in dirs {
+        let path = dir.join(&relative);
+        if path.is_file() {
+            return Ok(path);
+        }
+    }
+    bail!("Module not found: {}", name)
+}
+
+fn split_qualified(name: &str) -> Option<(&str, &str)> {
+    name.rfind('.').map(|i| (&name[..i], &name[i + 1..]))
+}
+
+struct Scope<'a> {
+    module: Option<&'a str>,
+    imports: HashSet<&'a str>,
+    fn_names: &'a HashMap<Option<String>, HashMap<String, bool>>,
+}
+
+impl<'a> Scope<'a> {
+    fn new(module: &'a ModuleAst, fn_names: &'a HashMap<Option<String>, HashMap<String, bool>>) -> Result<Self> {
+        let mut imports = HashSet::new();
+        for import in &module.imports {
+            if !fn_names.contains_key(&Some(import.module.name.clone())) {
+                bail!("Module not found: {}", import.module.name);
+            }
+            imports.insert(import.module.name.as_str());
+        }
+        Ok(Self {
+            module: module.name.as_ref().map(|name| name.name.as_str()),
+            imports,
+            fn_names,
+        })
+    }
+
+    fn qualify(&self, name: &str) -> String {
+        match self.module {
+            Some(module) => format!("{}.{}", module, name),
+            None => name.to_owned(),
+        }
+    }
+
+    fn defines(&self, module: Option<&str>, name: &str) -> bool {
+        self.fn_names.get(&module.map(|m| m.to_owned())).is_some_and(|names| names.contains_key(name))
+    }
+
+    fn exports(&self, module: Option<&str>, name: &str) -> bool {
+        self.fn_names.get(&module.map(|m| m.to_owned())).and_then(|names| names.get(name)).copied().unwrap_or(false)
+    }
+
+    fn qualify_fn_def(&self, fn_def: &FnDefAst) -> Result<FnDefAst> {
+        let args = fn_def.left_def.args.iter().map(|arg| arg.name.as_str()).collect::<Vec<_>>();
+        Ok(FnDefAst {
+            attrs: fn_def.attrs.clone(),
+            is_pub: fn_def.is_pub,
+            ty_annot: fn_def.ty_annot.clone(),
+            left_def: LeftDefAst {
+                ident: IdentAst { name: self.qualify(&fn_def.left_def.ident.name), span: fn_def.left_def.ident.span },
+                args: fn_def.left_def.args.clone(),
+                span: fn_def.left_def.span,
+            },
+            expr: self.qualify_expr(&fn_def.expr, &args)?,
+            span: fn_def.span,
+        })
+    }
+
+    fn qualify_expr(&self, expr: &ExprAst, args: &[&str]) -> Result<ExprAst> {
+        match expr {
+            ExprAst::Fn(fn_ast) =>
+                Ok(ExprAst::Fn(FnAst {
+                    fn_expr: Rc::new(self.qualify_expr(&fn_ast.fn_expr, args)?),
+                    arg_expr: Rc::new(self.qualify_expr(&fn_ast.arg_expr, args)?),
+                    span: fn_ast.span,
+                })),
+            ExprAst::PrefixOp(prefix_op_ast) =>
+                Ok(ExprAst::PrefixOp(PrefixOpAst {
+                    op_code: prefix_op_ast.op_code.clone(),
+                    rhs: Rc::new(self.qualify_expr(&prefix_op_ast.rhs, args)?),
+                    span: prefix_op_ast.span,
+                })),
+            ExprAst::InfixOp(infix_op_ast) =>
+                Ok(ExprAst::InfixOp(InfixOpAst {
+                    op_code: infix_op_ast.op_code.clone(),
+                    lhs: Rc::new(self.qualify_expr(&infix_op_ast.lhs, args)?),
+                    rhs: Rc::new(self.qualify_expr(&infix_op_ast.rhs, args)?),
+                    span: infix_op_ast.span,
+                })),
+            ExprAst::Ident(ident_ast) =>
+                Ok(ExprAst::Ident(self.qualify_ident(ident_ast, args)?)),
+            ExprAst::List(list_ast) => {
+                let mut elems = Vec::new();
+                for elem in &list_ast.elems {
+                    elems.push(self.qualify_expr(elem, args)?);
+                }
+                Ok(ExprAst::List(ListAst { elems, span: list_ast.span }))
+            },
+            ExprAst::Num(_) | ExprAst::Str(_) | ExprAst::Char(_) =>
+                Ok(expr.clone()),
+        }
+    }
+
+    fn qualify_ident(&self, ident: &IdentAst, args: &[&str]) -> Result<IdentAst> {
+        let name = &ident.name;
+        match split_qualified(name) {
+            Some((module, fn_name)) => {
+                if Some(module) != self.module && !self.imports.contains(module) {
+                    bail!("Module not imported: {}", module);
+                }
+                if !self.defines(Some(module), fn_name) {
+                    bail!("Unknown identifier: {}", name);
+                }
+                if Some(module) != self.module && !self.exports(Some(module), fn_name) {
+                    bail!("Function is private: {}", name);
+                }
+                Ok(ident.clone())
+            },
+            None if args.contains(&name.as_str()) =>
+                Ok(ident.clone()),
+            None if self.defines(self.module, name) =>
+                Ok(IdentAst { name: self.qualify(name), span: ident.span }),
+            None =>
+                Ok(ident.clone()),
+        }
+    }
+}
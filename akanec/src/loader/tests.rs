@@ -0,0 +1,196 @@
+use std::{
+    env,
+    fs,
+    path::PathBuf,
+    process,
+};
+use crate::data::ast::{
+    ModuleAst,
+    FnDefAst,
+};
+
+fn write_files(test_name: &str, files: &[(&str, &str)]) -> PathBuf {
+    let dir = env::temp_dir().join(format!("akanec_loader_{}_{}", test_name, process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    for (path, code) in files {
+        let path = dir.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, code).unwrap();
+    }
+    dir
+}
+
+fn load_module(test_name: &str, files: &[(&str, &str)]) -> anyhow::Result<ModuleAst> {
+    let dir = write_files(test_name, files);
+    let result = super::load(dir.join(files[0].0).to_str().unwrap(), &[]);
+    fs::remove_dir_all(&dir).unwrap();
+    result
+}
+
+fn load(test_name: &str, files: &[(&str, &str)]) -> anyhow::Result<Vec<FnDefAst>> {
+    load_module(test_name, files).map(|module| module.without_spans().fn_defs)
+}
+
+fn parse_module(s: &str) -> ModuleAst {
+    crate::parser::parse(crate::lexer::lex(s.to_owned()).unwrap()).unwrap()
+}
+
+fn parse(s: &str) -> Vec<FnDefAst> {
+    parse_module(s).without_spans().fn_defs
+}
+
+fn load_err(test_name: &str, files: &[(&str, &str)]) -> String {
+    load(test_name, files).unwrap_err().to_string()
+}
+
+#[test]
+fn load_single() {
+    assert_eq!(
+        load("single", &[("main.akane", "f a = a + 1; g a = f a")]).unwrap(),
+        parse("f a = a + 1; g a = f a")
+    );
+}
+
+#[test]
+fn load_import() {
+    assert_eq!(
+        load("import", &[
+            ("main.akane", "import Math; f a = Math.quad a; g a = f a"),
+            ("Math.akane", "module Math; double x = x + x; pub quad x = double (double x)"),
+        ]).unwrap(),
+        parse("Math.double x = x + x; pub Math.quad x = Math.double (Math.double x); f a = Math.quad a; g a = f a")
+    );
+}
+
+#[test]
+fn load_nested_module() {
+    assert_eq!(
+        load("nested_module", &[
+            ("main.akane", "import Data.List; f = Data.List.empty"),
+            ("Data/List.akane", "module Data.List; pub empty : [i32]; empty = []"),
+        ]).unwrap(),
+        parse("pub Data.List.empty : [i32]; Data.List.empty = []; f = Data.List.empty")
+    );
+}
+
+#[test]
+fn load_diamond() {
+    assert_eq!(
+        load("diamond", &[
+            ("main.akane", "import A; import B; f = A.a + B.b"),
+            ("A.akane", "module A; import C; pub a = C.c"),
+            ("B.akane", "module B; import C; pub b = C.c"),
+            ("C.akane", "module C; pub c = 1"),
+        ]).unwrap(),
+        parse("pub C.c = 1; pub A.a = C.c; pub B.b = C.c; f = A.a + B.b")
+    );
+}
+
+#[test]
+fn load_args_shadow_fns() {
+    assert_eq!(
+        load("args_shadow_fns", &[
+            ("main.akane", "import Math; f = Math.g 1"),
+            ("Math.akane", "module Math; x = 1; pub g x = x"),
+        ]).unwrap(),
+        parse("Math.x = 1; pub Math.g x = x; f = Math.g 1")
+    );
+}
+
+#[test]
+fn load_search_path() {
+    let dir = write_files("search_path", &[
+        ("src/main.akane", "import Math; f = Math.one"),
+        ("lib/Math.akane", "module Math; pub one = 1"),
+    ]);
+    let main = dir.join("src/main.akane");
+    let lib = dir.join("lib");
+    assert!(super::load(main.to_str().unwrap(), &[]).is_err());
+    assert_eq!(
+        super::load(main.to_str().unwrap(), &[lib.to_str().unwrap().to_owned()]).unwrap().without_spans().fn_defs,
+        parse("pub Math.one = 1; f = Math.one")
+    );
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn load_extern_fn() {
+    let module = load_module("extern_fn", &[
+        ("main.akane", r#"import Libc; extern "C" rand : i32; f = Libc.absolute rand"#),
+        ("Libc.akane", r#"module Libc; extern "C" abs : i32 -> i32; pub absolute x = abs x"#),
+    ]).unwrap().without_spans();
+    assert_eq!(
+        module.extern_fns,
+        [
+            parse_module(r#"extern "C" abs : i32 -> i32;"#).without_spans().extern_fns,
+            parse_module(r#"extern "C" rand : i32;"#).without_spans().extern_fns,
+        ].concat()
+    );
+    assert_eq!(module.fn_defs, parse("pub Libc.absolute x = abs x; f = Libc.absolute rand"));
+}
+
+#[test]
+fn load_cyclic_import() {
+    assert_eq!(
+        load_err("cyclic_import", &[
+            ("main.akane", "import A; f = A.a"),
+            ("A.akane", "module A; import B; a = B.b"),
+            ("B.akane", "module B; import A; b = A.a"),
+        ]),
+        "Cyclic import: A -> B -> A"
+    );
+    assert_eq!(
+        load_err("self_import", &[
+            ("A.akane", "module A; import A; a = 0"),
+        ]),
+        "Cyclic import: A -> A"
+    );
+}
+
+#[test]
+fn load_error() {
+    assert_eq!(
+        load_err("not_found", &[("main.akane", "import Math; f = 0")]),
+        "Module not found: Math"
+    );
+    assert_eq!(
+        load_err("not_imported", &[
+            ("main.akane", "import A; f = Math.one"),
+            ("A.akane", "module A; a = 0"),
+        ]),
+        "Module not imported: Math"
+    );
+    assert_eq!(
+        load_err("unknown_qualified", &[
+            ("main.akane", "import Math; f = Math.two"),
+            ("Math.akane", "module Math; one = 1"),
+        ]),
+        "Unknown identifier: Math.two"
+    );
+    assert_eq!(
+        load_err("private", &[
+            ("main.akane", "import Math; f = Math.one"),
+            ("Math.akane", "module Math; one = 1"),
+        ]),
+        "Function is private: Math.one"
+    );
+    assert!(
+        load_err("name_mismatch", &[
+            ("main.akane", "import Math; f = Math.one"),
+            ("Math.akane", "module Maths; one = 1"),
+        ]).ends_with("must declare `module Math;`")
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn load_json() {
+    let json = crate::dump::ast_json(&parse_module("import Math; f a = Math.double a")).unwrap();
+    assert_eq!(
+        load("json", &[
+            ("main.json", &json),
+            ("Math.akane", "module Math; pub double x = x + x"),
+        ]).unwrap(),
+        parse("pub Math.double x = x + x; f a = Math.double a")
+    );
+}
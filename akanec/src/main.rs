@@ -1,26 +1,327 @@
-mod data;
-mod lexer;
-mod parser;
-mod llvm;
-mod codegen;
-mod compiler;
-
-use anyhow::Result;
-use clap::Parser;
+use std::{
+    fs,
+    io::{
+        self,
+        BufRead,
+        Write,
+    },
+    panic,
+    process,
+    thread,
+};
+use anyhow::{
+    bail,
+    Result,
+};
+use clap::{
+    Args,
+    Parser,
+    Subcommand,
+};
+use akanec::{
+    compiler,
+    data::{
+        diagnostic::Diagnostic,
+        ty::FnTy,
+        value::Value,
+    },
+    formatter,
+    interp::Interp,
+    linter::{
+        Level,
+        Levels,
+        Lint,
+    },
+    loader,
+    repl::Session,
+};
 
 #[derive(Parser, Debug)]
 #[command(name = "akanec", author, version, about, long_about = None)]
-struct Args {
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[command(flatten)]
+    compile: CompileArgs,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Compile and link an executable
+    Build(BuildArgs),
+    /// Compile in memory and call a function
+    Run(RunArgs),
+    /// Start an interactive session
+    Repl(ReplArgs),
+    /// Evaluate a function with the reference interpreter
+    Interpret(InterpretArgs),
+    /// Format source files in place
+    Fmt(FmtArgs),
+}
+
+#[derive(Args, Debug)]
+struct CompileArgs {
+    /// Input file path
+    #[arg(required = true)]
+    input: Option<String>,
+
+    /// Output file path
+    #[arg(short, long)]
+    output: Option<String>,
+
+    /// Kind of output to emit
+    #[arg(long, value_enum, default_value_t = compiler::Emit::LlvmIr)]
+    emit: compiler::Emit,
+
+    #[command(flatten)]
+    options: OptionArgs,
+}
+
+#[derive(Args, Debug)]
+struct BuildArgs {
     /// Input file path
     input: String,
 
     /// Output file path
-    #[arg(short, long, default_value = "./a.ll")]
+    #[arg(short, long, default_value = "./a.out")]
     output: String,
+
+    #[command(flatten)]
+    options: OptionArgs,
+}
+
+#[derive(Args, Debug)]
+struct RunArgs {
+    /// Input file path
+    input: String,
+
+    /// Function to call
+    #[arg(long, default_value = "main")]
+    entry: String,
+
+    /// Arguments passed to the function
+    #[arg(long, num_args = 0.., allow_hyphen_values = true)]
+    args: Vec<String>,
+
+    #[command(flatten)]
+    options: OptionArgs,
+}
+
+#[derive(Args, Debug)]
+struct ReplArgs {
+    #[command(flatten)]
+    options: OptionArgs,
+}
+
+#[derive(Args, Debug)]
+struct InterpretArgs {
+    /// Input file path
+    input: String,
+
+    /// Function to call
+    #[arg(long, default_value = "main")]
+    entry: String,
+
+    /// Arguments passed to the function
+    #[arg(long, num_args = 0.., allow_hyphen_values = true)]
+    args: Vec<String>,
+
+    /// Directory to search for imported modules
+    #[arg(short = 'I', long = "include")]
+    include: Vec<String>,
+
+    /// Depth of calls past which evaluation fails with a stack overflow
+    #[arg(long, default_value_t = 10_000)]
+    max_depth: usize,
+}
+
+#[derive(Args, Debug)]
+struct FmtArgs {
+    /// Input file paths
+    #[arg(required = true)]
+    inputs: Vec<String>,
+
+    /// Only report files that are not formatted, failing if there are any
+    #[arg(long)]
+    check: bool,
+
+    /// Maximum line width
+    #[arg(long, default_value_t = formatter::DEFAULT_WIDTH)]
+    width: usize,
+}
+
+#[derive(Args, Debug)]
+struct OptionArgs {
+    /// Directory to search for imported modules
+    #[arg(short = 'I', long = "include")]
+    include: Vec<String>,
+
+    /// Optimization level
+    #[arg(short = 'O', value_enum, default_value_t = compiler::OptLevel::O0)]
+    opt_level: compiler::OptLevel,
+
+    /// Custom LLVM pass pipeline, overriding the optimization level
+    #[arg(long)]
+    passes: Option<String>,
+
+    /// Verify the module after every codegen step and pass
+    #[arg(long)]
+    verify_each: bool,
+
+    /// Lints not to report
+    #[arg(short = 'A', long = "allow", value_enum, value_name = "LINT")]
+    allow: Vec<Lint>,
+
+    /// Lints to report as warnings, the default
+    #[arg(short = 'W', long = "warn", value_enum, value_name = "LINT")]
+    warn: Vec<Lint>,
+
+    /// Lints to report as errors, taking precedence over the other levels
+    #[arg(short = 'D', long = "deny", value_enum, value_name = "LINT")]
+    deny: Vec<Lint>,
+}
+
+impl OptionArgs {
+    fn into_options(self, emit: compiler::Emit) -> compiler::Options {
+        let mut lints = Levels::default();
+        for (lints_at_level, level) in [(&self.allow, Level::Allow), (&self.warn, Level::Warn), (&self.deny, Level::Deny)] {
+            for lint in lints_at_level {
+                lints.set(*lint, level);
+            }
+        }
+        compiler::Options {
+            search_paths: self.include,
+            emit,
+            opt_level: self.opt_level,
+            passes: self.passes,
+            verify_each: self.verify_each,
+            lints,
+        }
+    }
 }
 
 fn main() -> Result<()> {
-    let args = Args::parse();
-    compiler::compile(&args.input, &args.output)?;
+    let cli = Cli::parse();
+    match cli.command {
+        Some(Command::Build(args)) => {
+            let options = args.options.into_options(compiler::Emit::Obj);
+            let warnings = compiler::build(&args.input, &args.output, &options)?;
+            print_warnings(&args.input, &warnings);
+        },
+        Some(Command::Run(args)) => {
+            let options = args.options.into_options(compiler::Emit::Obj);
+            let (mut jit, warnings) = compiler::jit(&args.input, &options)?;
+            print_warnings(&args.input, &warnings);
+            let values = parse_args(&args.entry, &jit.fn_ty(&args.entry)?, &args.args)?;
+            exit_or_print(&args.entry, jit.call(&args.entry, &values)?);
+        },
+        Some(Command::Repl(args)) => {
+            let options = args.options.into_options(compiler::Emit::Obj);
+            repl(Session::new(options)?)?;
+        },
+        Some(Command::Interpret(args)) =>
+            interpret(args)?,
+        Some(Command::Fmt(args)) => {
+            let options = formatter::Options { width: args.width };
+            let mut is_formatted = true;
+            for input in &args.inputs {
+                if !formatter::format_file(input, &options, args.check)? && args.check {
+                    eprintln!("{} is not formatted", input);
+                    is_formatted = false;
+                }
+            }
+            if !is_formatted {
+                process::exit(1);
+            }
+        },
+        None => {
+            let args = cli.compile;
+            let input = args.input.unwrap();
+            let output = args.output.unwrap_or_else(|| args.emit.default_output().to_owned());
+            let options = args.options.into_options(args.emit);
+            let warnings = compiler::compile_with_options(&input, &output, &options)?;
+            print_warnings(&input, &warnings);
+        },
+    }
     Ok(())
 }
+
+/// Prints each warning as `path:line:col: warning: message`, or without a position if the input cannot be read again.
+fn print_warnings(in_path: &str, warnings: &[Diagnostic]) {
+    if warnings.is_empty() {
+        return;
+    }
+    let code = fs::read_to_string(in_path).ok();
+    for warning in warnings {
+        match code.as_deref().filter(|code| code.is_char_boundary(warning.span.start)) {
+            Some(code) => {
+                let (line, col) = compiler::line_col(code, warning.span.start);
+                eprintln!("{}:{}:{}: warning: {}", in_path, line, col, warning.message);
+            },
+            None => eprintln!("{}: warning: {}", in_path, warning.message),
+        }
+    }
+}
+
+fn parse_args(entry: &str, fn_ty: &FnTy, args: &[String]) -> Result<Vec<Value>> {
+    if args.len() != fn_ty.args.len() {
+        bail!("{} takes {} arguments.", entry, fn_ty.args.len());
+    }
+    let mut values = Vec::new();
+    for (arg, ty) in args.iter().zip(&fn_ty.args) {
+        values.push(Value::parse(arg, ty)?);
+    }
+    Ok(values)
+}
+
+fn exit_or_print(entry: &str, value: Value) {
+    match value {
+        Value::I32(code) if entry == "main" => process::exit(code),
+        value => println!("{}", value),
+    }
+}
+
+/// The stack a call of the interpreter takes at most, in debug builds.
+const STACK_PER_CALL: usize = 16 * 1024;
+
+/// Runs on a thread with a stack deep enough for `--max-depth` calls.
+fn interpret(args: InterpretArgs) -> Result<()> {
+    let stack_size = (args.max_depth + 1) * STACK_PER_CALL;
+    let handle = thread::Builder::new().stack_size(stack_size).spawn(move || -> Result<()> {
+        let module_ast = loader::load(&args.input, &args.include)?;
+        let mut interp = Interp::new(&module_ast)?;
+        interp.set_max_depth(args.max_depth);
+        let values = parse_args(&args.entry, &interp.fn_ty(&args.entry)?, &args.args)?;
+        exit_or_print(&args.entry, interp.call(&args.entry, &values)?);
+        Ok(())
+    })?;
+    match handle.join() {
+        Ok(result) => result,
+        Err(e) => panic::resume_unwind(e),
+    }
+}
+
+fn repl(mut session: Session) -> Result<()> {
+    let stdin = io::stdin();
+    let mut stdout = io::stdout();
+    let mut line = String::new();
+    loop {
+        print!("> ");
+        stdout.flush()?;
+        line.clear();
+        if stdin.lock().read_line(&mut line)? == 0 {
+            println!();
+            return Ok(());
+        }
+        if matches!(line.trim(), ":quit" | ":q") {
+            return Ok(());
+        }
+        match session.eval_line(&line) {
+            Ok(output) if output.is_empty() => (),
+            Ok(output) => println!("{}", output),
+            Err(e) => eprintln!("Error: {}", e),
+        }
+    }
+}
@@ -1,18 +1,24 @@
 #[cfg(test)]
 mod tests;
 
-use std::{
-    iter::Peekable,
-    rc::Rc,
-};
+use std::rc::Rc;
 use anyhow::{
     bail,
     Result,
 };
 use crate::data::{
-    token::Token,
+    diagnostic::Diagnostic,
+    span::Span,
+    token::{
+        Token,
+        SpannedToken,
+    },
     ast::{
+        ModuleAst,
+        ImportAst,
+        ExternFnAst,
         FnDefAst,
+        AttrAst,
         LeftDefAst,
         ExprAst,
         FnAst,
@@ -20,25 +26,123 @@ use crate::data::{
         InfixOpAst,
         IdentAst,
         NumAst,
+        StrAst,
+        CharAst,
+        ListAst,
+        TyAst,
+        FnTyAst,
+        ListTyAst,
     },
 };
 
-pub fn parse(input: Vec<Token>) -> Result<Vec<FnDefAst>> {
-    let mut asts = Vec::new();
-    let mut tokens = input.into_iter().peekable();
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Assoc {
+    Left,
+    Right,
+}
+
+struct Tokens {
+    tokens: Vec<SpannedToken>,
+    pos: usize,
+}
+
+impl Tokens {
+    fn new(tokens: Vec<SpannedToken>) -> Self {
+        Self { tokens, pos: 0 }
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|spanned| &spanned.token)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos)?.token.clone();
+        self.pos += 1;
+        Some(token)
+    }
+
+    /// Where the next token starts.
+    fn start(&self) -> usize {
+        self.tokens.get(self.pos).or(self.tokens.last()).map_or(0, |spanned| spanned.span.start)
+    }
+
+    /// Locates a syntax error at the token where parsing stopped.
+    fn error(&self, e: anyhow::Error) -> anyhow::Error {
+        let span = self.tokens.get(self.pos).or(self.tokens.last()).map_or(Span::default(), |spanned| spanned.span);
+        Diagnostic::error(e.to_string(), span).into()
+    }
+
+    /// The span from `start` to the end of the last consumed token.
+    fn span_from(&self, start: usize) -> Span {
+        let end = if self.pos == 0 { start } else { self.tokens[self.pos - 1].span.end };
+        Span::new(start, end.max(start))
+    }
+}
+
+pub fn infix_op_prec(op_code: &str) -> (usize, Assoc) {
+    match op_code {
+        "||" => (2, Assoc::Right),
+        "&&" => (3, Assoc::Right),
+        "==" | "!=" | "<" | "<=" | ">" | ">=" => (4, Assoc::Left),
+        "++" | "::" => (5, Assoc::Right),
+        "+" | "-" => (6, Assoc::Left),
+        "*" | "/" | "%" => (7, Assoc::Left),
+        _ => (9, Assoc::Left),
+    }
+}
+
+pub fn parse(input: Vec<SpannedToken>) -> Result<ModuleAst> {
+    let mut tokens = Tokens::new(input);
+    assume_module(&mut tokens).map_err(|e| tokens.error(e))
+}
+
+pub fn parse_expr(input: Vec<SpannedToken>) -> Result<ExprAst> {
+    let mut tokens = Tokens::new(input);
+    assume_whole_expr(&mut tokens).map_err(|e| tokens.error(e))
+}
+
+fn assume_module(tokens: &mut Tokens) -> Result<ModuleAst> {
+    let name = assume_module_decl(tokens)?;
+    let mut imports = Vec::new();
+    let mut extern_fns = Vec::new();
+    let mut fn_defs = Vec::new();
     loop {
-        if let Some(_) = assume_eof(&mut tokens)? {
-            return Ok(asts);
+        if assume_eof(tokens)?.is_some() {
+            return Ok(ModuleAst { name, imports, extern_fns, fn_defs });
         }
-        if let Some(ast) = assume_fn_def(&mut tokens)? {
-            asts.push(ast);
+        if let Some(import) = assume_import(tokens)? {
+            if !extern_fns.is_empty() || !fn_defs.is_empty() {
+                bail!("Imports must precede function definitions.");
+            }
+            imports.push(import);
+            continue;
+        }
+        if let Some(extern_fn) = assume_extern_fn(tokens)? {
+            extern_fns.push(extern_fn);
             continue;
         }
+        if let Some(ast) = assume_fn_def(tokens)? {
+            fn_defs.push(ast);
+            continue;
+        }
+        if let Some(Token::Module) = tokens.peek() {
+            bail!("Module declaration must come first.");
+        }
         bail!("Invalid function definition.");
     }
 }
 
-fn assume_eof(tokens: &mut Peekable<impl Iterator<Item = Token>>) -> Result<Option<()>> {
+fn assume_whole_expr(tokens: &mut Tokens) -> Result<ExprAst> {
+    if let Some(expr) = assume_expr(tokens)? {
+        if assume_semicolon(tokens)?.is_some() && assume_eof(tokens)?.is_some() {
+            return Ok(expr);
+        }
+        bail!("Invalid expression.");
+    }
+    bail!("Expression required.");
+}
+
+fn assume_eof(tokens: &mut Tokens) -> Result<Option<()>> {
     if let Some(Token::Eof) = tokens.peek() {
         tokens.next();
         Ok(Some(()))
@@ -48,40 +152,242 @@ fn assume_eof(tokens: &mut Peekable<impl Iterator<Item = Token>>) -> Result<Opti
     }
 }
 
-fn assume_fn_def(tokens: &mut Peekable<impl Iterator<Item = Token>>) -> Result<Option<FnDefAst>> {
-    if let Some(left_def) = assume_left_def(tokens)? {
-        if let Some(_) = assume_equal(tokens)? {
-            if let Some(expr) = assume_expr(tokens)? {
-                if let Some(_) = assume_semicolon(tokens)? {
-                    return Ok(Some(FnDefAst { left_def, expr }));
+fn assume_module_decl(tokens: &mut Tokens) -> Result<Option<IdentAst>> {
+    if let Some(Token::Module) = tokens.peek() {
+        tokens.next();
+        if let Some(name) = assume_ident(tokens)? {
+            if assume_semicolon(tokens)?.is_some() {
+                return Ok(Some(name));
+            }
+            bail!("Semicolon required.");
+        }
+        bail!("Module name required.");
+    }
+    else {
+        Ok(None)
+    }
+}
+
+fn assume_import(tokens: &mut Tokens) -> Result<Option<ImportAst>> {
+    let start = tokens.start();
+    if let Some(Token::Import) = tokens.peek() {
+        tokens.next();
+        if let Some(module) = assume_ident(tokens)? {
+            if assume_semicolon(tokens)?.is_some() {
+                return Ok(Some(ImportAst { module, span: tokens.span_from(start) }));
+            }
+            bail!("Semicolon required.");
+        }
+        bail!("Module name required.");
+    }
+    else {
+        Ok(None)
+    }
+}
+
+fn assume_extern_fn(tokens: &mut Tokens) -> Result<Option<ExternFnAst>> {
+    let start = tokens.start();
+    if let Some(Token::Extern) = tokens.peek() {
+        tokens.next();
+        if let Some(abi) = assume_str(tokens)? {
+            if abi.value != "C" {
+                bail!("Unsupported ABI: {}", abi.value);
+            }
+            if let Some(ident) = assume_ident(tokens)? {
+                if let Some(ty) = assume_ty_annot(tokens)? {
+                    return Ok(Some(ExternFnAst { abi, ident, ty, span: tokens.span_from(start) }));
                 }
+                bail!("Type annotation required.");
             }
-            bail!("Expression required.");
+            bail!("Function name required.");
         }
-        bail!("Equal required.");
+        bail!("ABI required.");
     }
     else {
         Ok(None)
     }
 }
 
-fn assume_left_def(tokens: &mut Peekable<impl Iterator<Item = Token>>) -> Result<Option<LeftDefAst>> {
+fn assume_fn_def(tokens: &mut Tokens) -> Result<Option<FnDefAst>> {
+    let start = tokens.start();
+    let mut attrs = Vec::new();
+    while let Some(attr) = assume_attr(tokens)? {
+        attrs.push(attr);
+    }
+    let is_pub = assume_pub(tokens)?.is_some();
     if let Some(ident) = assume_ident(tokens)? {
-        let mut args = Vec::new();
+        if let Some(ty_annot) = assume_ty_annot(tokens)? {
+            if let Some(left_def) = assume_left_def(tokens)? {
+                if left_def.ident.name != ident.name {
+                    bail!("Function definition must follow its type annotation.");
+                }
+                return Ok(Some(assume_fn_def_rhs(tokens, start, attrs, is_pub, Some(ty_annot), left_def)?));
+            }
+            bail!("Function definition required.");
+        }
+        let left_def = assume_left_def_args(tokens, ident)?;
+        Ok(Some(assume_fn_def_rhs(tokens, start, attrs, is_pub, None, left_def)?))
+    }
+    else if is_pub || !attrs.is_empty() {
+        bail!("Function definition required.");
+    }
+    else {
+        Ok(None)
+    }
+}
+
+fn assume_fn_def_rhs(tokens: &mut Tokens, start: usize, attrs: Vec<AttrAst>, is_pub: bool, ty_annot: Option<TyAst>, left_def: LeftDefAst) -> Result<FnDefAst> {
+    if assume_equal(tokens)?.is_some() {
+        if let Some(expr) = assume_expr(tokens)? {
+            if assume_semicolon(tokens)?.is_some() {
+                return Ok(FnDefAst { attrs, is_pub, ty_annot, left_def, expr, span: tokens.span_from(start) });
+            }
+        }
+        bail!("Expression required.");
+    }
+    bail!("Equal required.");
+}
+
+fn assume_attr(tokens: &mut Tokens) -> Result<Option<AttrAst>> {
+    let start = tokens.start();
+    if let Some(Token::OpCode(op_code)) = tokens.peek() {
+        if op_code != "#" {
+            return Ok(None);
+        }
+        tokens.next();
+        if let Some(Token::LBracket) = tokens.peek() {
+            tokens.next();
+            if let Some(name) = assume_ident(tokens)? {
+                let args = assume_attr_args(tokens)?;
+                if let Some(Token::RBracket) = tokens.peek() {
+                    tokens.next();
+                    return Ok(Some(AttrAst { name, args, span: tokens.span_from(start) }));
+                }
+                bail!("Right bracket required.");
+            }
+            bail!("Attribute name required.");
+        }
+        bail!("Left bracket required.");
+    }
+    else {
+        Ok(None)
+    }
+}
+
+fn assume_attr_args(tokens: &mut Tokens) -> Result<Vec<IdentAst>> {
+    let mut args = Vec::new();
+    if let Some(Token::LParen) = tokens.peek() {
+        tokens.next();
         loop {
             if let Some(arg) = assume_ident(tokens)? {
                 args.push(arg);
-                continue;
+                match tokens.next() {
+                    Some(Token::Comma) => continue,
+                    Some(Token::RParen) => break,
+                    _ => bail!("Right paren required."),
+                }
             }
-            return Ok(Some(LeftDefAst { ident, args }));
+            bail!("Identifier required.");
         }
     }
+    Ok(args)
+}
+
+fn assume_pub(tokens: &mut Tokens) -> Result<Option<()>> {
+    if let Some(Token::Pub) = tokens.peek() {
+        tokens.next();
+        Ok(Some(()))
+    }
     else {
         Ok(None)
     }
 }
 
-fn assume_equal(tokens: &mut Peekable<impl Iterator<Item = Token>>) -> Result<Option<()>> {
+fn assume_ty_annot(tokens: &mut Tokens) -> Result<Option<TyAst>> {
+    if assume_colon(tokens)?.is_some() {
+        if let Some(ty) = assume_ty(tokens)? {
+            if assume_semicolon(tokens)?.is_some() {
+                return Ok(Some(ty));
+            }
+            bail!("Semicolon required.");
+        }
+        bail!("Type required.");
+    }
+    else {
+        Ok(None)
+    }
+}
+
+fn assume_left_def(tokens: &mut Tokens) -> Result<Option<LeftDefAst>> {
+    if let Some(ident) = assume_ident(tokens)? {
+        Ok(Some(assume_left_def_args(tokens, ident)?))
+    }
+    else {
+        Ok(None)
+    }
+}
+
+fn assume_left_def_args(tokens: &mut Tokens, ident: IdentAst) -> Result<LeftDefAst> {
+    let start = ident.span.start;
+    let mut args = Vec::new();
+    while let Some(arg) = assume_ident(tokens)? {
+        args.push(arg);
+    }
+    Ok(LeftDefAst { ident, args, span: tokens.span_from(start) })
+}
+
+fn assume_ty(tokens: &mut Tokens) -> Result<Option<TyAst>> {
+    let start = tokens.start();
+    if let Some(lhs) = assume_ty_factor(tokens)? {
+        if let Some(Token::OpCode(op_code)) = tokens.peek() {
+            if op_code == "->" {
+                tokens.next();
+                if let Some(rhs) = assume_ty(tokens)? {
+                    return Ok(Some(TyAst::Fn(FnTyAst { lhs: Rc::new(lhs), rhs: Rc::new(rhs), span: tokens.span_from(start) })));
+                }
+                bail!("Type required.");
+            }
+        }
+        Ok(Some(lhs))
+    }
+    else {
+        Ok(None)
+    }
+}
+
+fn assume_ty_factor(tokens: &mut Tokens) -> Result<Option<TyAst>> {
+    let start = tokens.start();
+    if let Some(Token::LParen) = tokens.peek() {
+        tokens.next();
+        if let Some(ty) = assume_ty(tokens)? {
+            if let Some(Token::RParen) = tokens.peek() {
+                tokens.next();
+                return Ok(Some(ty));
+            }
+            bail!("Right paren required.");
+        }
+        bail!("Type required.");
+    }
+    else if let Some(Token::LBracket) = tokens.peek() {
+        tokens.next();
+        if let Some(elem) = assume_ty(tokens)? {
+            if let Some(Token::RBracket) = tokens.peek() {
+                tokens.next();
+                return Ok(Some(TyAst::List(ListTyAst { elem: Rc::new(elem), span: tokens.span_from(start) })));
+            }
+            bail!("Right bracket required.");
+        }
+        bail!("Type required.");
+    }
+    else if let Some(ident) = assume_ident(tokens)? {
+        Ok(Some(TyAst::Ident(ident)))
+    }
+    else {
+        Ok(None)
+    }
+}
+
+fn assume_equal(tokens: &mut Tokens) -> Result<Option<()>> {
     if let Some(Token::Equal) = tokens.peek() {
         tokens.next();
         Ok(Some(()))
@@ -91,24 +397,22 @@ fn assume_equal(tokens: &mut Peekable<impl Iterator<Item = Token>>) -> Result<Op
     }
 }
 
-fn assume_expr(tokens: &mut Peekable<impl Iterator<Item = Token>>) -> Result<Option<ExprAst>> {
+fn assume_expr(tokens: &mut Tokens) -> Result<Option<ExprAst>> {
+    let start = tokens.start();
     if let Some(lhs) = assume_prefix_op_lhs(tokens)? {
-        let mut lhs = lhs;
-        while let Some((op_code, rhs)) = assume_infix_op_rhs(tokens)? {
-            lhs = ExprAst::InfixOp(InfixOpAst { op_code, lhs: Rc::new(lhs), rhs: Rc::new(rhs) });
-        }
-        Ok(Some(lhs))
+        Ok(Some(assume_infix_op(tokens, start, lhs, 0)?))
     }
     else {
         Ok(None)
     }
 }
 
-fn assume_term(tokens: &mut Peekable<impl Iterator<Item = Token>>) -> Result<Option<ExprAst>> {
+fn assume_term(tokens: &mut Tokens) -> Result<Option<ExprAst>> {
+    let start = tokens.start();
     if let Some(factor) = assume_factor(tokens)? {
         let mut term = factor;
         while let Some(f) = assume_factor(tokens)? {
-            term = ExprAst::Fn(FnAst { fn_expr: Rc::new(term), arg_expr: Rc::new(f) })
+            term = ExprAst::Fn(FnAst { fn_expr: Rc::new(term), arg_expr: Rc::new(f), span: tokens.span_from(start) })
         }
         Ok(Some(term))
     }
@@ -117,13 +421,14 @@ fn assume_term(tokens: &mut Peekable<impl Iterator<Item = Token>>) -> Result<Opt
     }
 }
 
-fn assume_prefix_op_lhs(tokens: &mut Peekable<impl Iterator<Item = Token>>) -> Result<Option<ExprAst>> {
+fn assume_prefix_op_lhs(tokens: &mut Tokens) -> Result<Option<ExprAst>> {
+    let start = tokens.start();
     if let Some(Token::OpCode(op_code)) = tokens.peek() {
         let op_code = op_code.to_owned();
         if op_code == "-" {
             tokens.next();
             if let Some(term) = assume_term(tokens)? {
-                return Ok(Some(ExprAst::PrefixOp(PrefixOpAst { op_code, rhs: Rc::new(term) })))
+                return Ok(Some(ExprAst::PrefixOp(PrefixOpAst { op_code, rhs: Rc::new(term), span: tokens.span_from(start) })))
             }
             bail!("Term required.");
         }
@@ -137,21 +442,51 @@ fn assume_prefix_op_lhs(tokens: &mut Peekable<impl Iterator<Item = Token>>) -> R
     }
 }
 
-fn assume_infix_op_rhs(tokens: &mut Peekable<impl Iterator<Item = Token>>) -> Result<Option<(String, ExprAst)>> {
+fn assume_infix_op(tokens: &mut Tokens, start: usize, lhs: ExprAst, min_prec: usize) -> Result<ExprAst> {
+    let mut lhs = lhs;
+    while let Some((prec, _)) = peek_infix_op(tokens) {
+        if prec < min_prec {
+            break;
+        }
+        let (op_code, rhs_start, rhs) = assume_infix_op_rhs(tokens)?;
+        let mut rhs = rhs;
+        while let Some((next_prec, next_assoc)) = peek_infix_op(tokens) {
+            if next_prec > prec {
+                rhs = assume_infix_op(tokens, rhs_start, rhs, prec + 1)?;
+            }
+            else if next_prec == prec && next_assoc == Assoc::Right {
+                rhs = assume_infix_op(tokens, rhs_start, rhs, prec)?;
+            }
+            else {
+                break;
+            }
+        }
+        lhs = ExprAst::InfixOp(InfixOpAst { op_code, lhs: Rc::new(lhs), rhs: Rc::new(rhs), span: tokens.span_from(start) });
+    }
+    Ok(lhs)
+}
+
+fn peek_infix_op(tokens: &mut Tokens) -> Option<(usize, Assoc)> {
     if let Some(Token::OpCode(op_code)) = tokens.peek() {
-        let op_code = op_code.to_owned();
-        tokens.next();
+        Some(infix_op_prec(op_code))
+    }
+    else {
+        None
+    }
+}
+
+fn assume_infix_op_rhs(tokens: &mut Tokens) -> Result<(String, usize, ExprAst)> {
+    if let Some(Token::OpCode(op_code)) = tokens.next() {
+        let start = tokens.start();
         if let Some(term) = assume_term(tokens)? {
-            return Ok(Some((op_code, term)));
+            return Ok((op_code, start, term));
         }
         bail!("Term required.");
     }
-    else {
-        Ok(None)
-    }
+    bail!("Operator required.");
 }
 
-fn assume_factor(tokens: &mut Peekable<impl Iterator<Item = Token>>) -> Result<Option<ExprAst>> {
+fn assume_factor(tokens: &mut Tokens) -> Result<Option<ExprAst>> {
     if let Some(expr) = assume_paren(tokens)? {
         Ok(Some(expr))
     }
@@ -161,12 +496,21 @@ fn assume_factor(tokens: &mut Peekable<impl Iterator<Item = Token>>) -> Result<O
     else if let Some(num) = assume_num(tokens)? {
         Ok(Some(ExprAst::Num(num)))
     }
+    else if let Some(str) = assume_str(tokens)? {
+        Ok(Some(ExprAst::Str(str)))
+    }
+    else if let Some(char) = assume_char(tokens)? {
+        Ok(Some(ExprAst::Char(char)))
+    }
+    else if let Some(list) = assume_list(tokens)? {
+        Ok(Some(ExprAst::List(list)))
+    }
     else {
         Ok(None)
     }
 }
 
-fn assume_paren(tokens: &mut Peekable<impl Iterator<Item = Token>>) -> Result<Option<ExprAst>>  {
+fn assume_paren(tokens: &mut Tokens) -> Result<Option<ExprAst>>  {
     if let Some(Token::LParen) = tokens.peek() {
         tokens.next();
         if let Some(expr) = assume_expr(tokens)? {
@@ -183,29 +527,91 @@ fn assume_paren(tokens: &mut Peekable<impl Iterator<Item = Token>>) -> Result<Op
     }
 }
 
-fn assume_ident(tokens: &mut Peekable<impl Iterator<Item = Token>>) -> Result<Option<IdentAst>> {
+fn assume_list(tokens: &mut Tokens) -> Result<Option<ListAst>> {
+    let start = tokens.start();
+    if let Some(Token::LBracket) = tokens.peek() {
+        tokens.next();
+        let mut elems = Vec::new();
+        if let Some(Token::RBracket) = tokens.peek() {
+            tokens.next();
+            return Ok(Some(ListAst { elems, span: tokens.span_from(start) }));
+        }
+        loop {
+            if let Some(expr) = assume_expr(tokens)? {
+                elems.push(expr);
+                match tokens.next() {
+                    Some(Token::Comma) => continue,
+                    Some(Token::RBracket) => return Ok(Some(ListAst { elems, span: tokens.span_from(start) })),
+                    _ => bail!("Right bracket required."),
+                }
+            }
+            bail!("Expression required.");
+        }
+    }
+    else {
+        Ok(None)
+    }
+}
+
+fn assume_ident(tokens: &mut Tokens) -> Result<Option<IdentAst>> {
     if let Some(Token::Ident(name)) = tokens.peek() {
         let name = name.to_owned();
+        let start = tokens.start();
         tokens.next();
-        Ok(Some(IdentAst { name }))
+        Ok(Some(IdentAst { name, span: tokens.span_from(start) }))
     }
     else {
         Ok(None)
     }
 }
 
-fn assume_num(tokens: &mut Peekable<impl Iterator<Item = Token>>) -> Result<Option<NumAst>> {
+fn assume_num(tokens: &mut Tokens) -> Result<Option<NumAst>> {
     if let Some(Token::Num(value)) = tokens.peek() {
         let value = value.to_owned();
+        let start = tokens.start();
         tokens.next();
-        Ok(Some(NumAst { value }))
+        Ok(Some(NumAst { value, span: tokens.span_from(start) }))
+    }
+    else {
+        Ok(None)
+    }
+}
+
+fn assume_str(tokens: &mut Tokens) -> Result<Option<StrAst>> {
+    if let Some(Token::Str(value)) = tokens.peek() {
+        let value = value.to_owned();
+        let start = tokens.start();
+        tokens.next();
+        Ok(Some(StrAst { value, span: tokens.span_from(start) }))
+    }
+    else {
+        Ok(None)
+    }
+}
+
+fn assume_char(tokens: &mut Tokens) -> Result<Option<CharAst>> {
+    if let Some(Token::Char(value)) = tokens.peek() {
+        let value = *value;
+        let start = tokens.start();
+        tokens.next();
+        Ok(Some(CharAst { value, span: tokens.span_from(start) }))
+    }
+    else {
+        Ok(None)
+    }
+}
+
+fn assume_colon(tokens: &mut Tokens) -> Result<Option<()>> {
+    if let Some(Token::Colon) = tokens.peek() {
+        tokens.next();
+        Ok(Some(()))
     }
     else {
         Ok(None)
     }
 }
 
-fn assume_semicolon(tokens: &mut Peekable<impl Iterator<Item = Token>>) -> Result<Option<()>> {
+fn assume_semicolon(tokens: &mut Tokens) -> Result<Option<()>> {
     if let Some(Token::Semicolon) = tokens.peek() {
         tokens.next();
         Ok(Some(()))
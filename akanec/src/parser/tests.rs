@@ -1,25 +1,62 @@
 use std::rc::Rc;
-use crate::data::ast::{
-    FnDefAst,
-    LeftDefAst,
-    ExprAst,
-    FnAst,
-    PrefixOpAst,
-    InfixOpAst,
-    IdentAst,
-    NumAst,
+use crate::data::{
+    span::Span,
+    ast::{
+        ModuleAst,
+        ImportAst,
+        ExternFnAst,
+        FnDefAst,
+        AttrAst,
+        LeftDefAst,
+        ExprAst,
+        FnAst,
+        PrefixOpAst,
+        InfixOpAst,
+        IdentAst,
+        NumAst,
+        StrAst,
+        CharAst,
+        ListAst,
+        TyAst,
+        FnTyAst,
+        ListTyAst,
+    },
 };
 
 fn parse(s: &str) -> Vec<FnDefAst> {
+    parse_module(s).without_spans().fn_defs
+}
+
+fn parse_module(s: &str) -> ModuleAst {
     super::parse(crate::lexer::lex(s.to_owned()).unwrap()).unwrap()
 }
 
+fn module_ast(name: Option<IdentAst>, imports: Vec<ImportAst>, fn_defs: Vec<FnDefAst>) -> ModuleAst {
+    ModuleAst { name, imports, extern_fns: vec![], fn_defs }
+}
+
+fn import_ast(module: IdentAst) -> ImportAst {
+    ImportAst { module, span: Span::default() }
+}
+
+fn extern_fn_ast(ident: IdentAst, ty: TyAst) -> ExternFnAst {
+    ExternFnAst { abi: str_ast("C"), ident, ty, span: Span::default() }
+}
+
 fn fn_def_ast(left_def: LeftDefAst, expr: ExprAst) -> FnDefAst {
-    FnDefAst { left_def, expr }
+    FnDefAst { attrs: Vec::new(), is_pub: false, ty_annot: None, left_def, expr, span: Span::default() }
+}
+
+fn annotated_fn_def_ast(ty_annot: TyAst, left_def: LeftDefAst, expr: ExprAst) -> FnDefAst {
+    FnDefAst { attrs: Vec::new(), is_pub: false, ty_annot: Some(ty_annot), left_def, expr, span: Span::default() }
+}
+
+fn pub_fn_def_ast(fn_def: FnDefAst) -> FnDefAst {
+    FnDefAst { is_pub: true, ..fn_def }
 }
 
 fn left_def_ast(ident: IdentAst, args: Vec<IdentAst>) -> LeftDefAst {
-    LeftDefAst { ident, args }
+    LeftDefAst { ident, args, span: Span::default() }
 }
 
 fn fn_expr_ast(fn_ast: FnAst) -> ExprAst {
@@ -42,24 +79,60 @@ fn num_expr_ast(num_ast: NumAst) -> ExprAst {
     ExprAst::Num(num_ast)
 }
 
+fn str_expr_ast(str_ast: StrAst) -> ExprAst {
+    ExprAst::Str(str_ast)
+}
+
+fn char_expr_ast(char_ast: CharAst) -> ExprAst {
+    ExprAst::Char(char_ast)
+}
+
+fn list_expr_ast(list_ast: ListAst) -> ExprAst {
+    ExprAst::List(list_ast)
+}
+
 fn fn_ast(fn_expr: ExprAst, arg_expr: ExprAst) -> FnAst {
-    FnAst { fn_expr: Rc::new(fn_expr), arg_expr: Rc::new(arg_expr) }
+    FnAst { fn_expr: Rc::new(fn_expr), arg_expr: Rc::new(arg_expr), span: Span::default() }
 }
 
 fn prefix_op_ast(op_code: &str, rhs: ExprAst) -> PrefixOpAst {
-    PrefixOpAst { op_code: op_code.to_owned(), rhs: Rc::new(rhs) }
+    PrefixOpAst { op_code: op_code.to_owned(), rhs: Rc::new(rhs), span: Span::default() }
 }
 
 fn infix_op_ast(op_code: &str, lhs: ExprAst, rhs: ExprAst) -> InfixOpAst {
-    InfixOpAst { op_code: op_code.to_owned(), lhs: Rc::new(lhs), rhs: Rc::new(rhs) }
+    InfixOpAst { op_code: op_code.to_owned(), lhs: Rc::new(lhs), rhs: Rc::new(rhs), span: Span::default() }
 }
 
 fn ident_ast(name: &str) -> IdentAst {
-    IdentAst { name: name.to_owned() }
+    IdentAst { name: name.to_owned(), span: Span::default() }
 }
 
 fn num_ast(value: &str) -> NumAst {
-    NumAst { value: value.to_owned() }
+    NumAst { value: value.to_owned(), span: Span::default() }
+}
+
+fn str_ast(value: &str) -> StrAst {
+    StrAst { value: value.to_owned(), span: Span::default() }
+}
+
+fn char_ast(value: char) -> CharAst {
+    CharAst { value, span: Span::default() }
+}
+
+fn list_ast(elems: Vec<ExprAst>) -> ListAst {
+    ListAst { elems, span: Span::default() }
+}
+
+fn fn_ty_ast(lhs: TyAst, rhs: TyAst) -> TyAst {
+    TyAst::Fn(FnTyAst { lhs: Rc::new(lhs), rhs: Rc::new(rhs), span: Span::default() })
+}
+
+fn list_ty_ast(elem: TyAst) -> TyAst {
+    TyAst::List(ListTyAst { elem: Rc::new(elem), span: Span::default() })
+}
+
+fn ident_ty_ast(name: &str) -> TyAst {
+    TyAst::Ident(ident_ast(name))
 }
 
 #[test]
@@ -228,3 +301,329 @@ fn parse_prefix_op() {
         )]
     );
 }
+
+#[test]
+fn parse_str() {
+    assert_eq!(
+        parse(r#"f = "abc""#),
+        &[fn_def_ast(
+            left_def_ast(ident_ast("f"), vec![]),
+            str_expr_ast(str_ast("abc"))
+        )]
+    );
+    assert_eq!(
+        parse(r#"f a = a ++ "\n""#),
+        &[fn_def_ast(
+            left_def_ast(ident_ast("f"), vec![ident_ast("a")]),
+            infix_op_expr_ast(infix_op_ast("++", ident_expr_ast(ident_ast("a")), str_expr_ast(str_ast("\n"))))
+        )]
+    );
+}
+
+#[test]
+fn parse_char() {
+    assert_eq!(
+        parse("f = 'a'"),
+        &[fn_def_ast(
+            left_def_ast(ident_ast("f"), vec![]),
+            char_expr_ast(char_ast('a'))
+        )]
+    );
+}
+
+#[test]
+fn parse_ty_annot() {
+    assert_eq!(
+        parse("f : String; f = g"),
+        &[annotated_fn_def_ast(
+            ident_ty_ast("String"),
+            left_def_ast(ident_ast("f"), vec![]),
+            ident_expr_ast(ident_ast("g"))
+        )]
+    );
+    assert_eq!(
+        parse("f : (i32 -> i32) -> String -> i32; f g s = g 0"),
+        &[annotated_fn_def_ast(
+            fn_ty_ast(
+                fn_ty_ast(ident_ty_ast("i32"), ident_ty_ast("i32")),
+                fn_ty_ast(ident_ty_ast("String"), ident_ty_ast("i32"))
+            ),
+            left_def_ast(ident_ast("f"), vec![ident_ast("g"), ident_ast("s")]),
+            fn_expr_ast(fn_ast(ident_expr_ast(ident_ast("g")), num_expr_ast(num_ast("0"))))
+        )]
+    );
+}
+
+#[test]
+fn parse_ty_annot_error() {
+    assert!(super::parse(crate::lexer::lex("f : String;".to_owned()).unwrap()).is_err());
+    assert!(super::parse(crate::lexer::lex("f : String; g = 0".to_owned()).unwrap()).is_err());
+    assert!(super::parse(crate::lexer::lex("f : ; f = 0".to_owned()).unwrap()).is_err());
+    assert!(super::parse(crate::lexer::lex("f : String f = 0".to_owned()).unwrap()).is_err());
+}
+
+#[test]
+fn parse_infix_op_prec() {
+    assert_eq!(
+        parse("f = a == b + c"),
+        &[fn_def_ast(
+            left_def_ast(ident_ast("f"), vec![]),
+            infix_op_expr_ast(infix_op_ast(
+                "==",
+                ident_expr_ast(ident_ast("a")),
+                infix_op_expr_ast(infix_op_ast(
+                    "+",
+                    ident_expr_ast(ident_ast("b")),
+                    ident_expr_ast(ident_ast("c"))
+                ))
+            ))
+        )]
+    );
+    assert_eq!(
+        parse("f = a ++ b ++ c == d"),
+        &[fn_def_ast(
+            left_def_ast(ident_ast("f"), vec![]),
+            infix_op_expr_ast(infix_op_ast(
+                "==",
+                infix_op_expr_ast(infix_op_ast(
+                    "++",
+                    ident_expr_ast(ident_ast("a")),
+                    infix_op_expr_ast(infix_op_ast(
+                        "++",
+                        ident_expr_ast(ident_ast("b")),
+                        ident_expr_ast(ident_ast("c"))
+                    ))
+                )),
+                ident_expr_ast(ident_ast("d"))
+            ))
+        )]
+    );
+}
+
+#[test]
+fn parse_list() {
+    assert_eq!(
+        parse("f = []"),
+        &[fn_def_ast(
+            left_def_ast(ident_ast("f"), vec![]),
+            list_expr_ast(list_ast(vec![]))
+        )]
+    );
+    assert_eq!(
+        parse("f a = [1, a + 1, g a]"),
+        &[fn_def_ast(
+            left_def_ast(ident_ast("f"), vec![ident_ast("a")]),
+            list_expr_ast(list_ast(vec![
+                num_expr_ast(num_ast("1")),
+                infix_op_expr_ast(infix_op_ast("+", ident_expr_ast(ident_ast("a")), num_expr_ast(num_ast("1")))),
+                fn_expr_ast(fn_ast(ident_expr_ast(ident_ast("g")), ident_expr_ast(ident_ast("a")))),
+            ]))
+        )]
+    );
+    assert_eq!(
+        parse("f = 1 :: 2 :: [] ++ xs !! 0 :: []"),
+        &[fn_def_ast(
+            left_def_ast(ident_ast("f"), vec![]),
+            infix_op_expr_ast(infix_op_ast(
+                "::",
+                num_expr_ast(num_ast("1")),
+                infix_op_expr_ast(infix_op_ast(
+                    "::",
+                    num_expr_ast(num_ast("2")),
+                    infix_op_expr_ast(infix_op_ast(
+                        "++",
+                        list_expr_ast(list_ast(vec![])),
+                        infix_op_expr_ast(infix_op_ast(
+                            "::",
+                            infix_op_expr_ast(infix_op_ast(
+                                "!!",
+                                ident_expr_ast(ident_ast("xs")),
+                                num_expr_ast(num_ast("0"))
+                            )),
+                            list_expr_ast(list_ast(vec![]))
+                        ))
+                    ))
+                ))
+            ))
+        )]
+    );
+}
+
+#[test]
+fn parse_list_error() {
+    assert!(super::parse(crate::lexer::lex("f = [1, 2".to_owned()).unwrap()).is_err());
+    assert!(super::parse(crate::lexer::lex("f = [1,]".to_owned()).unwrap()).is_err());
+    assert!(super::parse(crate::lexer::lex("f = [1; 2]".to_owned()).unwrap()).is_err());
+}
+
+#[test]
+fn parse_list_ty() {
+    assert_eq!(
+        parse("f : [i32] -> i32; f xs = length xs"),
+        &[annotated_fn_def_ast(
+            fn_ty_ast(list_ty_ast(ident_ty_ast("i32")), ident_ty_ast("i32")),
+            left_def_ast(ident_ast("f"), vec![ident_ast("xs")]),
+            fn_expr_ast(fn_ast(ident_expr_ast(ident_ast("length")), ident_expr_ast(ident_ast("xs"))))
+        )]
+    );
+}
+
+#[test]
+fn parse_module_decl() {
+    assert_eq!(
+        parse_module("module Math; square x = x").without_spans(),
+        module_ast(
+            Some(ident_ast("Math")),
+            vec![],
+            vec![fn_def_ast(
+                left_def_ast(ident_ast("square"), vec![ident_ast("x")]),
+                ident_expr_ast(ident_ast("x"))
+            )]
+        )
+    );
+    assert_eq!(
+        parse_module("module Data.List;").without_spans(),
+        module_ast(Some(ident_ast("Data.List")), vec![], vec![])
+    );
+}
+
+#[test]
+fn parse_import() {
+    assert_eq!(
+        parse_module("import Math; import Data.List; f x = Math.square x").without_spans(),
+        module_ast(
+            None,
+            vec![import_ast(ident_ast("Math")), import_ast(ident_ast("Data.List"))],
+            vec![fn_def_ast(
+                left_def_ast(ident_ast("f"), vec![ident_ast("x")]),
+                fn_expr_ast(fn_ast(ident_expr_ast(ident_ast("Math.square")), ident_expr_ast(ident_ast("x"))))
+            )]
+        )
+    );
+}
+
+#[test]
+fn parse_module_error() {
+    assert!(super::parse(crate::lexer::lex("f = 0; module Math;".to_owned()).unwrap()).is_err());
+    assert!(super::parse(crate::lexer::lex("module Math; module Math;".to_owned()).unwrap()).is_err());
+    assert!(super::parse(crate::lexer::lex("f = 0; import Math;".to_owned()).unwrap()).is_err());
+    assert!(super::parse(crate::lexer::lex("import;".to_owned()).unwrap()).is_err());
+}
+
+#[test]
+fn parse_pub() {
+    assert_eq!(
+        parse("pub f a = a; g = 0"),
+        &[
+            pub_fn_def_ast(fn_def_ast(
+                left_def_ast(ident_ast("f"), vec![ident_ast("a")]),
+                ident_expr_ast(ident_ast("a"))
+            )),
+            fn_def_ast(
+                left_def_ast(ident_ast("g"), vec![]),
+                num_expr_ast(num_ast("0"))
+            ),
+        ]
+    );
+    assert_eq!(
+        parse("pub f : String; f = g"),
+        &[pub_fn_def_ast(annotated_fn_def_ast(
+            ident_ty_ast("String"),
+            left_def_ast(ident_ast("f"), vec![]),
+            ident_expr_ast(ident_ast("g"))
+        ))]
+    );
+    assert!(super::parse(crate::lexer::lex("pub;".to_owned()).unwrap()).is_err());
+    assert!(super::parse(crate::lexer::lex("pub f : String; pub f = g".to_owned()).unwrap()).is_err());
+}
+
+#[test]
+fn parse_attrs() {
+    let attr_ast = |name: &str, args: &[&str]| AttrAst { name: ident_ast(name), args: args.iter().map(|arg| ident_ast(arg)).collect(), span: Span::default() };
+    assert_eq!(
+        parse("#[allow(unused_fn, unused_param)]\n#[inline]\npub f : i32 -> i32;\nf x = 0;"),
+        &[FnDefAst {
+            attrs: vec![attr_ast("allow", &["unused_fn", "unused_param"]), attr_ast("inline", &[])],
+            ..pub_fn_def_ast(annotated_fn_def_ast(
+                fn_ty_ast(ident_ty_ast("i32"), ident_ty_ast("i32")),
+                left_def_ast(ident_ast("f"), vec![ident_ast("x")]),
+                num_expr_ast(num_ast("0"))
+            ))
+        }]
+    );
+    let module_ast = parse_module("g = 0;\n#[deny(unused_fn)] f = 0;");
+    assert_eq!((module_ast.fn_defs[1].span.start, module_ast.fn_defs[1].attrs[0].span.end), (7, 25));
+    assert!(super::parse(crate::lexer::lex("#[allow(unused_fn)];".to_owned()).unwrap()).is_err());
+    assert!(super::parse(crate::lexer::lex("#[allow(unused_fn] f = 0;".to_owned()).unwrap()).is_err());
+    assert!(super::parse(crate::lexer::lex("#[allow()] f = 0;".to_owned()).unwrap()).is_err());
+    assert!(super::parse(crate::lexer::lex("#allow f = 0;".to_owned()).unwrap()).is_err());
+}
+
+#[test]
+fn parse_extern_fn() {
+    assert_eq!(
+        parse_module(r#"extern "C" puts : ptr -> i32; extern "C" rand : i32; f = rand"#).without_spans(),
+        ModuleAst {
+            name: None,
+            imports: vec![],
+            extern_fns: vec![
+                extern_fn_ast(ident_ast("puts"), fn_ty_ast(ident_ty_ast("ptr"), ident_ty_ast("i32"))),
+                extern_fn_ast(ident_ast("rand"), ident_ty_ast("i32")),
+            ],
+            fn_defs: vec![fn_def_ast(
+                left_def_ast(ident_ast("f"), vec![]),
+                ident_expr_ast(ident_ast("rand"))
+            )],
+        }
+    );
+}
+
+#[test]
+fn parse_extern_fn_error() {
+    assert!(super::parse(crate::lexer::lex(r#"extern puts : ptr -> i32;"#.to_owned()).unwrap()).is_err());
+    assert!(super::parse(crate::lexer::lex(r#"extern "Rust" puts : ptr -> i32;"#.to_owned()).unwrap()).is_err());
+    assert!(super::parse(crate::lexer::lex(r#"extern "C" puts;"#.to_owned()).unwrap()).is_err());
+    assert!(super::parse(crate::lexer::lex(r#"extern "C" puts : ptr -> i32; import Math;"#.to_owned()).unwrap()).is_err());
+}
+
+#[test]
+fn parse_expr() {
+    assert_eq!(
+        super::parse_expr(crate::lexer::lex("f 1 + 2".to_owned()).unwrap()).unwrap().without_spans(),
+        infix_op_expr_ast(infix_op_ast(
+            "+",
+            fn_expr_ast(fn_ast(ident_expr_ast(ident_ast("f")), num_expr_ast(num_ast("1")))),
+            num_expr_ast(num_ast("2"))
+        ))
+    );
+    assert!(super::parse_expr(crate::lexer::lex("".to_owned()).unwrap()).is_err());
+    assert!(super::parse_expr(crate::lexer::lex("f = 1".to_owned()).unwrap()).is_err());
+    assert!(super::parse_expr(crate::lexer::lex("1; 2".to_owned()).unwrap()).is_err());
+}
+
+#[test]
+fn parse_spans() {
+    let module_ast = parse_module("import Math;\npub f : i32 -> i32;\nf x = -x + [1, 2];");
+    let span = |span: Span| (span.start, span.end);
+    assert_eq!(span(module_ast.imports[0].span), (0, 12));
+    let fn_def_ast = &module_ast.fn_defs[0];
+    assert_eq!(span(fn_def_ast.span), (13, 51));
+    assert_eq!(span(fn_def_ast.ty_annot.as_ref().unwrap().span()), (21, 31));
+    assert_eq!(span(fn_def_ast.left_def.span), (33, 36));
+    assert_eq!(span(fn_def_ast.expr.span()), (39, 50));
+    let ExprAst::InfixOp(infix_op_ast) = &fn_def_ast.expr else { panic!() };
+    assert_eq!(span(infix_op_ast.lhs.span()), (39, 41));
+    assert_eq!(span(infix_op_ast.rhs.span()), (44, 50));
+}
+
+#[test]
+fn parse_error_spans() {
+    let span = |s: &str| {
+        let e = super::parse(crate::lexer::lex(s.to_owned()).unwrap()).unwrap_err();
+        let diagnostic = e.downcast_ref::<crate::data::diagnostic::Diagnostic>().unwrap();
+        (diagnostic.message.clone(), diagnostic.span.start, diagnostic.span.end)
+    };
+    assert_eq!(span("f x = x +;"), ("Term required.".to_owned(), 9, 10));
+    assert_eq!(span("f = 0;\nmodule Math;"), ("Module declaration must come first.".to_owned(), 7, 13));
+    assert_eq!(span("f x = (x"), ("Right paren required.".to_owned(), 8, 8));
+}
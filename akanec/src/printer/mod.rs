@@ -0,0 +1,316 @@
+#[cfg(test)]
+mod tests;
+
+use crate::{
+    data::ast::{
+        ModuleAst,
+        ExternFnAst,
+        FnDefAst,
+        AttrAst,
+        ExprAst,
+        TyAst,
+    },
+    parser::{
+        self,
+        Assoc,
+    },
+};
+
+pub const INDENT: usize = 4;
+
+/// Where an expression is printed, which decides whether it needs parentheses.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Pos {
+    /// The start of a whole expression: a function body, a list element or the inside of parentheses.
+    Head,
+    /// The left operand of an infix operator; `true` if the operand begins a whole expression.
+    Lhs(usize, Assoc, bool),
+    Rhs(usize),
+    Callee,
+    Arg,
+}
+
+pub fn module(module_ast: &ModuleAst) -> String {
+    let mut sections = Vec::new();
+    if let Some(name) = &module_ast.name {
+        sections.push(format!("module {};\n", name.name));
+    }
+    if !module_ast.imports.is_empty() {
+        sections.push(module_ast.imports.iter().map(|import_ast| format!("import {};\n", import_ast.module.name)).collect());
+    }
+    if !module_ast.extern_fns.is_empty() {
+        sections.push(module_ast.extern_fns.iter().map(extern_fn).collect());
+    }
+    if !module_ast.fn_defs.is_empty() {
+        sections.push(module_ast.fn_defs.iter().map(fn_def).collect());
+    }
+    sections.join("\n")
+}
+
+pub fn extern_fn(extern_fn_ast: &ExternFnAst) -> String {
+    format!("extern {} {} : {};\n", str_lit(&extern_fn_ast.abi.value), extern_fn_ast.ident.name, ty(&extern_fn_ast.ty))
+}
+
+pub fn fn_def(fn_def_ast: &FnDefAst) -> String {
+    fn_def_within(fn_def_ast, usize::MAX)
+}
+
+/// Like `fn_def`, but an expression that does not fit in `width` columns moves to its own line
+/// and is broken at its applications, operators and list elements.
+pub fn fn_def_within(fn_def_ast: &FnDefAst, width: usize) -> String {
+    let mut s = fn_def_ast.attrs.iter().map(|attr_ast| format!("{}\n", attr(attr_ast))).collect::<String>();
+    let left_def = &fn_def_ast.left_def;
+    let visibility = if fn_def_ast.is_pub { "pub " } else { "" };
+    let head = [&left_def.ident].into_iter().chain(&left_def.args).map(|ident| ident.name.as_str()).collect::<Vec<_>>().join(" ");
+    let lhs = match &fn_def_ast.ty_annot {
+        Some(ty_annot) => {
+            s += &format!("{}{} : {};\n", visibility, left_def.ident.name, ty(ty_annot));
+            format!("{} =", head)
+        },
+        None => format!("{}{} =", visibility, head),
+    };
+    let flat = format!("{} {};", lhs, expr(&fn_def_ast.expr));
+    if flat.chars().count() <= width {
+        s += &flat;
+        s += "\n";
+    }
+    else {
+        let layout = Layout { width };
+        s += &format!("{}\n{}{};\n", lhs, " ".repeat(INDENT), layout.expr(&fn_def_ast.expr, Pos::Head, INDENT, INDENT, 1));
+    }
+    s
+}
+
+/// Prints `expr_ast` with only the parentheses the parser needs to rebuild the same tree.
+pub fn expr(expr_ast: &ExprAst) -> String {
+    expr_at(expr_ast, Pos::Head)
+}
+
+pub fn attr(attr_ast: &AttrAst) -> String {
+    if attr_ast.args.is_empty() {
+        return format!("#[{}]", attr_ast.name.name);
+    }
+    let args = attr_ast.args.iter().map(|arg| arg.name.as_str()).collect::<Vec<_>>().join(", ");
+    format!("#[{}({})]", attr_ast.name.name, args)
+}
+
+/// The subexpressions of `expr_ast` written in parentheses the parser does not need,
+/// where `depth` tells how many pairs each subexpression is written in.
+/// Where removing the outer pair would make an inner one necessary, only the outer one is reported.
+pub fn redundant_parens<'a>(expr_ast: &'a ExprAst, depth: &dyn Fn(&ExprAst) -> usize) -> Vec<&'a ExprAst> {
+    let mut redundant = Vec::new();
+    collect_redundant_parens(expr_ast, Pos::Head, depth, &mut redundant);
+    redundant
+}
+
+pub fn ty(ty_ast: &TyAst) -> String {
+    match ty_ast {
+        TyAst::Fn(fn_ty_ast) => {
+            let lhs = ty(&fn_ty_ast.lhs);
+            match fn_ty_ast.lhs.as_ref() {
+                TyAst::Fn(_) => format!("({}) -> {}", lhs, ty(&fn_ty_ast.rhs)),
+                _ => format!("{} -> {}", lhs, ty(&fn_ty_ast.rhs)),
+            }
+        },
+        TyAst::List(list_ty_ast) => format!("[{}]", ty(&list_ty_ast.elem)),
+        TyAst::Ident(ident_ast) => ident_ast.name.clone(),
+    }
+}
+
+pub fn str_lit(value: &str) -> String {
+    format!("\"{}\"", value.chars().map(|c| escape(c, '"')).collect::<String>())
+}
+
+pub fn char_lit(value: char) -> String {
+    format!("'{}'", escape(value, '\''))
+}
+
+fn expr_at(expr_ast: &ExprAst, pos: Pos) -> String {
+    let parens = needs_parens(expr_ast, pos);
+    let is_head = parens || matches!(pos, Pos::Head | Pos::Lhs(_, _, true));
+    let s = match expr_ast {
+        ExprAst::Fn(fn_ast) =>
+            format!("{} {}", expr_at(&fn_ast.fn_expr, Pos::Callee), expr_at(&fn_ast.arg_expr, Pos::Arg)),
+        ExprAst::PrefixOp(prefix_op_ast) =>
+            format!("{}{}", prefix_op_ast.op_code, expr_at(&prefix_op_ast.rhs, Pos::Callee)),
+        ExprAst::InfixOp(infix_op_ast) => {
+            let (prec, assoc) = parser::infix_op_prec(&infix_op_ast.op_code);
+            let lhs = expr_at(&infix_op_ast.lhs, Pos::Lhs(prec, assoc, is_head));
+            let rhs = expr_at(&infix_op_ast.rhs, Pos::Rhs(prec));
+            format!("{} {} {}", lhs, infix_op_ast.op_code, rhs)
+        },
+        ExprAst::Ident(ident_ast) => ident_ast.name.clone(),
+        ExprAst::Num(num_ast) => num_ast.value.clone(),
+        ExprAst::Str(str_ast) => str_lit(&str_ast.value),
+        ExprAst::Char(char_ast) => char_lit(char_ast.value),
+        ExprAst::List(list_ast) =>
+            format!("[{}]", list_ast.elems.iter().map(expr).collect::<Vec<_>>().join(", ")),
+    };
+    if parens { format!("({})", s) } else { s }
+}
+
+/// A prefix operator only parses at the head of an expression, an infix operand must be a term
+/// unless precedence and associativity group it the right way, and an argument must be a factor.
+fn needs_parens(expr_ast: &ExprAst, pos: Pos) -> bool {
+    match (expr_ast, pos) {
+        (_, Pos::Head) => false,
+        (ExprAst::InfixOp(infix_op_ast), Pos::Lhs(prec, assoc, _)) => {
+            let (lhs_prec, _) = parser::infix_op_prec(&infix_op_ast.op_code);
+            lhs_prec < prec || lhs_prec == prec && assoc == Assoc::Right
+        },
+        (ExprAst::InfixOp(infix_op_ast), Pos::Rhs(prec)) => {
+            let (rhs_prec, rhs_assoc) = parser::infix_op_prec(&infix_op_ast.op_code);
+            rhs_prec < prec || rhs_prec == prec && rhs_assoc == Assoc::Left
+        },
+        (ExprAst::PrefixOp(_), Pos::Lhs(_, _, is_head)) => !is_head,
+        (ExprAst::InfixOp(_) | ExprAst::PrefixOp(_), _) => true,
+        (ExprAst::Fn(_), Pos::Arg) => true,
+        _ => false,
+    }
+}
+
+fn collect_redundant_parens<'a>(expr_ast: &'a ExprAst, pos: Pos, depth: &dyn Fn(&ExprAst) -> usize, redundant: &mut Vec<&'a ExprAst>) {
+    let pairs = depth(expr_ast);
+    let is_head_pos = matches!(pos, Pos::Head | Pos::Lhs(_, _, true));
+    let is_redundant = pairs > 1 || pairs == 1 && !needs_parens(expr_ast, pos) && (is_head_pos || !starts_with_prefix_op(expr_ast, depth));
+    if is_redundant {
+        redundant.push(expr_ast);
+    }
+    let is_head = pairs > 0 && !is_redundant || is_head_pos;
+    match expr_ast {
+        ExprAst::Fn(fn_ast) => {
+            collect_redundant_parens(&fn_ast.fn_expr, Pos::Callee, depth, redundant);
+            collect_redundant_parens(&fn_ast.arg_expr, Pos::Arg, depth, redundant);
+        },
+        ExprAst::PrefixOp(prefix_op_ast) =>
+            collect_redundant_parens(&prefix_op_ast.rhs, Pos::Callee, depth, redundant),
+        ExprAst::InfixOp(infix_op_ast) => {
+            let (prec, assoc) = parser::infix_op_prec(&infix_op_ast.op_code);
+            collect_redundant_parens(&infix_op_ast.lhs, Pos::Lhs(prec, assoc, is_head), depth, redundant);
+            collect_redundant_parens(&infix_op_ast.rhs, Pos::Rhs(prec), depth, redundant);
+        },
+        ExprAst::List(list_ast) =>
+            for elem in &list_ast.elems {
+                collect_redundant_parens(elem, Pos::Head, depth, redundant);
+            },
+        ExprAst::Ident(_) | ExprAst::Num(_) | ExprAst::Str(_) | ExprAst::Char(_) => (),
+    }
+}
+
+/// Whether a prefix operator would begin `expr_ast` without its parentheses,
+/// which is only allowed at the head of an expression.
+fn starts_with_prefix_op(expr_ast: &ExprAst, depth: &dyn Fn(&ExprAst) -> usize) -> bool {
+    match expr_ast {
+        ExprAst::PrefixOp(_) => true,
+        ExprAst::InfixOp(infix_op_ast) => depth(&infix_op_ast.lhs) == 0 && starts_with_prefix_op(&infix_op_ast.lhs, depth),
+        _ => false,
+    }
+}
+
+struct Layout {
+    width: usize,
+}
+
+impl Layout {
+    /// `col` is where the expression starts, `indent` the indentation of its line
+    /// and `trail` the width of what follows it on its last line.
+    fn expr(&self, expr_ast: &ExprAst, pos: Pos, col: usize, indent: usize, trail: usize) -> String {
+        let flat = expr_at(expr_ast, pos);
+        if col + flat.chars().count() + trail <= self.width || !is_breakable(expr_ast) {
+            return flat;
+        }
+        if needs_parens(expr_ast, pos) {
+            return format!("({})", self.expr(expr_ast, Pos::Head, col + 1, indent, trail + 1));
+        }
+        let next = indent + INDENT;
+        match expr_ast {
+            ExprAst::Fn(_) => {
+                let mut args = Vec::new();
+                let callee = application(expr_ast, &mut args);
+                let mut s = self.expr(callee, Pos::Callee, col, indent, 0);
+                for (i, arg) in args.iter().enumerate() {
+                    let arg_trail = if i + 1 == args.len() { trail } else { 0 };
+                    s += &format!("\n{}{}", " ".repeat(next), self.expr(arg, Pos::Arg, next, next, arg_trail));
+                }
+                s
+            },
+            ExprAst::PrefixOp(prefix_op_ast) => {
+                let rhs_col = col + prefix_op_ast.op_code.chars().count();
+                format!("{}{}", prefix_op_ast.op_code, self.expr(&prefix_op_ast.rhs, Pos::Callee, rhs_col, indent, trail))
+            },
+            ExprAst::InfixOp(infix_op_ast) => {
+                let (prec, _) = parser::infix_op_prec(&infix_op_ast.op_code);
+                let mut operands = Vec::new();
+                operator_chain(expr_ast, pos, prec, None, &mut operands);
+                let mut s = String::new();
+                for (i, (op_code, operand, pos)) in operands.iter().enumerate() {
+                    let operand_trail = if i + 1 == operands.len() { trail } else { 0 };
+                    match op_code {
+                        None => s += &self.expr(operand, *pos, col, indent, operand_trail),
+                        Some(op_code) => {
+                            let operand_col = next + op_code.chars().count() + 1;
+                            s += &format!("\n{}{} {}", " ".repeat(next), op_code, self.expr(operand, *pos, operand_col, next, operand_trail));
+                        },
+                    }
+                }
+                s
+            },
+            ExprAst::List(list_ast) => {
+                let elems = list_ast.elems.iter().enumerate().map(|(i, elem)| {
+                    let comma = if i + 1 == list_ast.elems.len() { 0 } else { 1 };
+                    format!("{}{}", " ".repeat(next), self.expr(elem, Pos::Head, next, next, comma))
+                });
+                format!("[\n{}\n{}]", elems.collect::<Vec<_>>().join(",\n"), " ".repeat(indent))
+            },
+            _ => flat,
+        }
+    }
+}
+
+fn is_breakable(expr_ast: &ExprAst) -> bool {
+    match expr_ast {
+        ExprAst::Fn(_) | ExprAst::InfixOp(_) => true,
+        ExprAst::PrefixOp(prefix_op_ast) => is_breakable(&prefix_op_ast.rhs),
+        ExprAst::List(list_ast) => !list_ast.elems.is_empty(),
+        _ => false,
+    }
+}
+
+/// Collects the arguments of `f a b c` and returns `f`.
+fn application<'a>(expr_ast: &'a ExprAst, args: &mut Vec<&'a ExprAst>) -> &'a ExprAst {
+    match expr_ast {
+        ExprAst::Fn(fn_ast) => {
+            let callee = application(&fn_ast.fn_expr, args);
+            args.push(&fn_ast.arg_expr);
+            callee
+        },
+        _ => expr_ast,
+    }
+}
+
+/// Collects the operands of consecutive operators of precedence `prec`,
+/// each with the operator before it and its position in the tree.
+fn operator_chain<'a>(expr_ast: &'a ExprAst, pos: Pos, prec: usize, op_code: Option<&'a str>, operands: &mut Vec<(Option<&'a str>, &'a ExprAst, Pos)>) {
+    match expr_ast {
+        ExprAst::InfixOp(infix_op_ast) if parser::infix_op_prec(&infix_op_ast.op_code).0 == prec && !needs_parens(expr_ast, pos) => {
+            let (_, assoc) = parser::infix_op_prec(&infix_op_ast.op_code);
+            let is_head = matches!(pos, Pos::Head | Pos::Lhs(_, _, true));
+            operator_chain(&infix_op_ast.lhs, Pos::Lhs(prec, assoc, is_head), prec, op_code, operands);
+            operator_chain(&infix_op_ast.rhs, Pos::Rhs(prec), prec, Some(&infix_op_ast.op_code), operands);
+        },
+        _ => operands.push((op_code, expr_ast, pos)),
+    }
+}
+
+fn escape(c: char, quote: char) -> String {
+    match c {
+        '\n' => "\\n".to_owned(),
+        '\r' => "\\r".to_owned(),
+        '\t' => "\\t".to_owned(),
+        '\0' => "\\0".to_owned(),
+        '\\' => "\\\\".to_owned(),
+        c if c == quote => format!("\\{}", c),
+        c if c.is_control() => format!("\\u{{{:x}}}", c as u32),
+        c => c.to_string(),
+    }
+}
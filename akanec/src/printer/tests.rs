@@ -0,0 +1,209 @@
+use std::rc::Rc;
+use proptest::prelude::*;
+use crate::{
+    data::{
+        span::Span,
+        ast::{
+            ModuleAst,
+            ImportAst,
+            ExternFnAst,
+            FnDefAst,
+            AttrAst,
+            LeftDefAst,
+            ExprAst,
+            FnAst,
+            PrefixOpAst,
+            InfixOpAst,
+            IdentAst,
+            NumAst,
+            StrAst,
+            CharAst,
+            ListAst,
+            TyAst,
+            FnTyAst,
+            ListTyAst,
+        },
+        token::Token,
+    },
+    lexer,
+    parser,
+};
+
+const KEYWORDS: [&str; 4] = ["module", "import", "pub", "extern"];
+const INFIX_OPS: [&str; 16] = ["||", "&&", "==", "!=", "<", "<=", ">", ">=", "++", "::", "+", "-", "*", "/", "%", "<>"];
+
+fn parse_expr(s: &str) -> ExprAst {
+    parser::parse_expr(lexer::lex(s.to_owned()).unwrap()).unwrap()
+}
+
+fn parse_module(s: &str) -> ModuleAst {
+    parser::parse(lexer::lex(s.to_owned()).unwrap()).unwrap()
+}
+
+fn reprint_expr(s: &str) -> String {
+    super::expr(&parse_expr(s))
+}
+
+fn ident_ast(name: String) -> IdentAst {
+    IdentAst { name, span: Span::default() }
+}
+
+fn ident_strategy() -> impl Strategy<Value = IdentAst> {
+    prop_oneof![
+        "[a-z_][a-z0-9_]{0,5}",
+        "[A-Z][a-z]{0,3}\\.[a-z][a-z0-9]{0,3}",
+    ]
+    .prop_filter("keyword", |name| !KEYWORDS.contains(&name.as_str()))
+    .prop_map(ident_ast)
+}
+
+fn expr_strategy() -> impl Strategy<Value = ExprAst> {
+    let leaf = prop_oneof![
+        ident_strategy().prop_map(ExprAst::Ident),
+        "[0-9]{1,6}".prop_map(|value| ExprAst::Num(NumAst { value, span: Span::default() })),
+        any::<String>().prop_map(|value| ExprAst::Str(StrAst { value, span: Span::default() })),
+        any::<char>().prop_map(|value| ExprAst::Char(CharAst { value, span: Span::default() })),
+    ];
+    leaf.prop_recursive(6, 48, 4, |inner| prop_oneof![
+        (inner.clone(), inner.clone()).prop_map(|(fn_expr, arg_expr)|
+            ExprAst::Fn(FnAst { fn_expr: Rc::new(fn_expr), arg_expr: Rc::new(arg_expr), span: Span::default() })
+        ),
+        inner.clone().prop_map(|rhs|
+            ExprAst::PrefixOp(PrefixOpAst { op_code: "-".to_owned(), rhs: Rc::new(rhs), span: Span::default() })
+        ),
+        (prop::sample::select(&INFIX_OPS[..]), inner.clone(), inner.clone()).prop_map(|(op_code, lhs, rhs)|
+            ExprAst::InfixOp(InfixOpAst { op_code: op_code.to_owned(), lhs: Rc::new(lhs), rhs: Rc::new(rhs), span: Span::default() })
+        ),
+        prop::collection::vec(inner, 0..4).prop_map(|elems| ExprAst::List(ListAst { elems, span: Span::default() })),
+    ])
+}
+
+fn ty_strategy() -> impl Strategy<Value = TyAst> {
+    let leaf = prop_oneof![Just("i32"), Just("ptr"), Just("str")].prop_map(|name| TyAst::Ident(ident_ast(name.to_owned())));
+    leaf.prop_recursive(4, 16, 2, |inner| prop_oneof![
+        (inner.clone(), inner.clone()).prop_map(|(lhs, rhs)|
+            TyAst::Fn(FnTyAst { lhs: Rc::new(lhs), rhs: Rc::new(rhs), span: Span::default() })
+        ),
+        inner.prop_map(|elem| TyAst::List(ListTyAst { elem: Rc::new(elem), span: Span::default() })),
+    ])
+}
+
+fn attr_strategy() -> impl Strategy<Value = AttrAst> {
+    (ident_strategy(), prop::collection::vec(ident_strategy(), 0..3)).prop_map(|(name, args)| AttrAst { name, args, span: Span::default() })
+}
+
+fn fn_def_strategy() -> impl Strategy<Value = FnDefAst> {
+    let attrs = prop::collection::vec(attr_strategy(), 0..2);
+    (attrs, any::<bool>(), prop::option::of(ty_strategy()), ident_strategy(), prop::collection::vec(ident_strategy(), 0..3), expr_strategy())
+        .prop_map(|(attrs, is_pub, ty_annot, ident, args, expr)| FnDefAst {
+            attrs,
+            is_pub,
+            ty_annot,
+            left_def: LeftDefAst { ident, args, span: Span::default() },
+            expr,
+            span: Span::default(),
+        })
+}
+
+fn module_strategy() -> impl Strategy<Value = ModuleAst> {
+    let name = prop::option::of("[A-Z][a-z]{0,5}".prop_map(ident_ast));
+    let imports = prop::collection::vec("[A-Z][a-z]{0,5}".prop_map(|name| ImportAst { module: ident_ast(name), span: Span::default() }), 0..3);
+    let extern_fns = prop::collection::vec((ident_strategy(), ty_strategy()), 0..3).prop_map(|extern_fns|
+        extern_fns.into_iter().map(|(ident, ty)| ExternFnAst {
+            abi: StrAst { value: "C".to_owned(), span: Span::default() },
+            ident,
+            ty,
+            span: Span::default(),
+        })
+        .collect()
+    );
+    let fn_defs = prop::collection::vec(fn_def_strategy(), 0..4);
+    (name, imports, extern_fns, fn_defs).prop_map(|(name, imports, extern_fns, fn_defs)| ModuleAst { name, imports, extern_fns, fn_defs })
+}
+
+#[test]
+fn print_minimal_parens() {
+    assert_eq!(reprint_expr("(a + b) + c"), "a + b + c");
+    assert_eq!(reprint_expr("a + (b + c)"), "a + (b + c)");
+    assert_eq!(reprint_expr("(a :: b) :: c"), "(a :: b) :: c");
+    assert_eq!(reprint_expr("a :: (b :: c)"), "a :: b :: c");
+    assert_eq!(reprint_expr("(a * b) + (c * d)"), "a * b + c * d");
+    assert_eq!(reprint_expr("(a + b) * (c - d)"), "(a + b) * (c - d)");
+    assert_eq!(reprint_expr("a == b || (c && d)"), "a == b || c && d");
+    assert_eq!(reprint_expr("((f x) (g y)) z"), "f x (g y) z");
+    assert_eq!(reprint_expr("(f) (x)"), "f x");
+}
+
+#[test]
+fn print_prefix_op() {
+    assert_eq!(reprint_expr("(-x) * y"), "-x * y");
+    assert_eq!(reprint_expr("-(x * y)"), "-(x * y)");
+    assert_eq!(reprint_expr("x * (-y)"), "x * (-y)");
+    assert_eq!(reprint_expr("-(-x)"), "-(-x)");
+    assert_eq!(reprint_expr("-f x"), "-f x");
+    assert_eq!(reprint_expr("f (-x)"), "f (-x)");
+    assert_eq!(reprint_expr("((-x) + y) * z"), "(-x + y) * z");
+    assert_eq!(reprint_expr("[-x, (-y) - 1]"), "[-x, -y - 1]");
+}
+
+#[test]
+fn print_literals() {
+    assert_eq!(reprint_expr(r#"f "a\"b\\c\n" 'x'"#), r#"f "a\"b\\c\n" 'x'"#);
+    assert_eq!(reprint_expr(r#"['\'', '"', "'", '\u{7}']"#), r#"['\'', '"', "'", '\u{7}']"#);
+    assert_eq!(reprint_expr("[ ]"), "[]");
+}
+
+#[test]
+fn print_module() {
+    let code = "module Shapes; import Math; pub area w h = w*h; extern \"C\" abs : i32 -> i32; pub apply : (i32 -> i32) -> [i32] -> i32; apply f xs = f (Math.sum xs);";
+    assert_eq!(
+        super::module(&parse_module(code)),
+        "module Shapes;\n\nimport Math;\n\nextern \"C\" abs : i32 -> i32;\n\npub area w h = w * h;\npub apply : (i32 -> i32) -> [i32] -> i32;\napply f xs = f (Math.sum xs);\n"
+    );
+    assert_eq!(super::module(&parse_module("f = 1;")), "f = 1;\n");
+    assert_eq!(
+        super::module(&parse_module("#[allow( unused_fn,unused_param )] #[inline] pub f : i32 -> i32; f x = 1;")),
+        "#[allow(unused_fn, unused_param)]\n#[inline]\npub f : i32 -> i32;\nf x = 1;\n"
+    );
+    assert_eq!(super::module(&parse_module("")), "");
+}
+
+#[test]
+fn redundant_parens() {
+    let redundant = |s: &str| {
+        let tokens = lexer::lex(s.to_owned()).unwrap();
+        let expr_ast = parser::parse_expr(tokens.clone()).unwrap();
+        let depth = |expr_ast: &ExprAst| {
+            let span = expr_ast.span();
+            let before = tokens.iter().rev().filter(|spanned| spanned.span.end <= span.start).take_while(|spanned| spanned.token == Token::LParen).count();
+            let after = tokens.iter().filter(|spanned| spanned.span.start >= span.end).take_while(|spanned| spanned.token == Token::RParen).count();
+            before.min(after)
+        };
+        super::redundant_parens(&expr_ast, &depth).into_iter().map(super::expr).collect::<Vec<_>>()
+    };
+    assert_eq!(redundant("(a + b) + (c * d)"), vec!["a + b", "c * d"]);
+    assert_eq!(redundant("a + (b + c) * (d)"), vec!["d"]);
+    assert_eq!(redundant("(f x) (g y) ((z))"), vec!["f x", "z"]);
+    assert_eq!(redundant("(a :: b) :: (c :: d)"), vec!["c :: d"]);
+    assert_eq!(redundant("[(x), -(y)] ++ (-z)"), vec!["x", "y"]);
+    assert_eq!(redundant("(-a * b) + c"), vec!["-a * b"]);
+    assert_eq!(redundant("c + (-a * b)"), Vec::<String>::new());
+    assert_eq!(redundant("c + ((-a) * b)"), vec!["-a * b"]);
+    assert_eq!(redundant("((-a) + b) * c"), vec!["-a"]);
+}
+
+proptest! {
+    #[test]
+    fn expr_round_trip(expr_ast in expr_strategy()) {
+        let code = super::expr(&expr_ast);
+        let parsed = parser::parse_expr(lexer::lex(code.clone()).unwrap());
+        prop_assert_eq!(parsed.ok().map(|parsed| parsed.without_spans()), Some(expr_ast), "{}", code);
+    }
+
+    #[test]
+    fn module_round_trip(module_ast in module_strategy()) {
+        let code = super::module(&module_ast);
+        let parsed = parser::parse(lexer::lex(code.clone()).unwrap());
+        prop_assert_eq!(parsed.ok().map(|parsed| parsed.without_spans()), Some(module_ast), "{}", code);
+    }
+}
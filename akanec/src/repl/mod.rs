@@ -0,0 +1,177 @@
+#[cfg(test)]
+mod tests;
+
+use anyhow::{
+    bail,
+    Result,
+};
+use crate::{
+    compiler::{
+        self,
+        Options,
+    },
+    data::{
+        ast::{
+            ModuleAst,
+            ExternFnAst,
+            FnDefAst,
+            ExprAst,
+        },
+        token::{
+            Token,
+            SpannedToken,
+        },
+        ty::Ty,
+    },
+    dump,
+    jit::Jit,
+    lexer,
+    llvm::LLVM,
+    loader,
+    parser,
+};
+
+const MODULE_ID: &str = "repl";
+const IT: &str = "repl.it";
+
+pub const HELP: &str = "\
+<definition>       Add or replace a definition
+<expression>       Evaluate an expression
+:type <expression> Show the type of an expression
+:ast <input>       Show the AST of a definition or an expression
+:ir [expression]   Show the LLVM IR of the definitions and the expression
+:load <path>       Load the definitions of a file
+:help              Show this help
+:quit              Exit";
+
+/// Definitions are compiled once into the JIT as they are entered, each line into a module of its own.
+pub struct Session {
+    options: Options,
+    jit: Jit,
+    /// The definitions as entered, for `:ir`.
+    module_ast: ModuleAst,
+}
+
+impl Session {
+    pub fn new(options: Options) -> Result<Self> {
+        let mut llvm = LLVM::new(MODULE_ID);
+        llvm.set_verify_each(options.verify_each);
+        let jit = Jit::new(llvm, options.opt_level.codegen_level())?;
+        Ok(Self {
+            options,
+            jit,
+            module_ast: ModuleAst { name: None, imports: Vec::new(), extern_fns: Vec::new(), fn_defs: Vec::new() },
+        })
+    }
+
+    pub fn eval_line(&mut self, line: &str) -> Result<String> {
+        let line = line.trim();
+        if line.is_empty() {
+            return Ok(String::new());
+        }
+        if let Some(command) = line.strip_prefix(':') {
+            let (command, arg) = command.split_once(char::is_whitespace).unwrap_or((command, ""));
+            return self.eval_command(command, arg.trim());
+        }
+        match parse_line(line)? {
+            Input::Defs(module_ast) => self.add_defs(module_ast.extern_fns, module_ast.fn_defs),
+            Input::Expr(expr_ast) => self.eval_expr(&expr_ast),
+        }
+    }
+
+    fn eval_command(&mut self, command: &str, arg: &str) -> Result<String> {
+        match command {
+            "type" | "t" => {
+                let expr_ast = parser::parse_expr(lexer::lex(arg.to_owned())?)?;
+                let ty = self.jit.infer_ty(MODULE_ID, &expr_ast)?;
+                Ok(format!("{} : {}", arg, show_ty(&ty)))
+            },
+            "ast" =>
+                match parse_line(arg)? {
+                    Input::Defs(module_ast) => Ok(dump::ast(&module_ast).trim_end().to_owned()),
+                    Input::Expr(expr_ast) => Ok(dump::expr(&expr_ast).trim_end().to_owned()),
+                },
+            "ir" => {
+                let mut llvm =
+                    if arg.is_empty() {
+                        compiler::gen_module(MODULE_ID, &self.module_ast, &self.options)?
+                    }
+                    else {
+                        let expr_ast = parser::parse_expr(lexer::lex(arg.to_owned())?)?;
+                        compiler::gen_expr(MODULE_ID, &self.module_ast, IT, &expr_ast, &self.options)?
+                    };
+                compiler::optimize(&mut llvm, &self.options)?;
+                Ok(llvm.print_module_to_string().trim_end().to_owned())
+            },
+            "load" | "l" => {
+                let module_ast = loader::load(arg, &self.options.search_paths)?;
+                self.add_defs(module_ast.extern_fns, module_ast.fn_defs)
+            },
+            "help" | "h" =>
+                Ok(HELP.to_owned()),
+            _ => bail!("Unknown command: :{}", command),
+        }
+    }
+
+    fn add_defs(&mut self, extern_fns: Vec<ExternFnAst>, fn_defs: Vec<FnDefAst>) -> Result<String> {
+        let defs = ModuleAst { name: None, imports: Vec::new(), extern_fns: extern_fns.clone(), fn_defs: fn_defs.clone() };
+        compiler::jit_add_module(&mut self.jit, MODULE_ID, &defs, &self.options)?;
+        let mut module_ast = self.module_ast.clone();
+        for extern_fn in &extern_fns {
+            module_ast.extern_fns.retain(|defined| defined.ident.name != extern_fn.ident.name);
+        }
+        for fn_def in &fn_defs {
+            module_ast.fn_defs.retain(|defined| defined.left_def.ident.name != fn_def.left_def.ident.name);
+        }
+        module_ast.extern_fns.extend(extern_fns.iter().cloned());
+        module_ast.fn_defs.extend(fn_defs.iter().cloned());
+        let names = extern_fns.iter().map(|extern_fn| &extern_fn.ident).chain(fn_defs.iter().map(|fn_def| &fn_def.left_def.ident));
+        let mut lines = Vec::new();
+        for ident in names {
+            lines.push(format!("{} : {}", ident.name, self.jit.fn_ty(&ident.name)?));
+        }
+        self.module_ast = module_ast;
+        Ok(lines.join("\n"))
+    }
+
+    fn eval_expr(&mut self, expr_ast: &ExprAst) -> Result<String> {
+        compiler::jit_add_expr(&mut self.jit, MODULE_ID, IT, expr_ast, &self.options)?;
+        let ty = self.jit.fn_ty(IT)?.ret.as_ref().clone();
+        if let Ty::Fn(fn_ty) = ty {
+            return Ok(format!("<function> : {}", fn_ty));
+        }
+        let value = self.jit.call(IT, &[])?;
+        Ok(format!("{} : {}", value, ty))
+    }
+}
+
+enum Input {
+    Defs(ModuleAst),
+    Expr(ExprAst),
+}
+
+fn parse_line(line: &str) -> Result<Input> {
+    let tokens = lexer::lex(line.to_owned())?;
+    if !is_definition(&tokens) {
+        return Ok(Input::Expr(parser::parse_expr(tokens)?));
+    }
+    let module_ast = parser::parse(tokens)?;
+    if module_ast.name.is_some() || !module_ast.imports.is_empty() {
+        bail!("Use :load to load modules.");
+    }
+    Ok(Input::Defs(module_ast))
+}
+
+fn is_definition(tokens: &[SpannedToken]) -> bool {
+    let token = |i: usize| tokens.get(i).map(|spanned| &spanned.token);
+    matches!(token(0), Some(Token::Pub | Token::Extern | Token::Module | Token::Import))
+        || matches!(token(1), Some(Token::Colon))
+        || tokens.iter().any(|spanned| spanned.token == Token::Equal)
+}
+
+fn show_ty(ty: &Ty) -> String {
+    match ty {
+        Ty::Fn(fn_ty) => fn_ty.to_string(),
+        _ => ty.to_string(),
+    }
+}
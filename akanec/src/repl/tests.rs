@@ -0,0 +1,89 @@
+use std::fs;
+use crate::compiler::Options;
+use super::Session;
+
+fn session() -> Session {
+    Session::new(Options::default()).unwrap()
+}
+
+#[test]
+fn eval_expr() {
+    let mut session = session();
+    assert_eq!(session.eval_line("1 + 2").unwrap(), "3 : i32");
+    assert_eq!(session.eval_line(r#""Hello, " ++ "world""#).unwrap(), r#""Hello, world" : String"#);
+    assert_eq!(session.eval_line("[1, 2] ++ [3]").unwrap(), "[1, 2, 3] : [i32]");
+    assert_eq!(session.eval_line("").unwrap(), "");
+}
+
+#[test]
+fn define() {
+    let mut session = session();
+    assert_eq!(session.eval_line("add a b = a + b").unwrap(), "add : i32 -> i32 -> i32");
+    assert_eq!(session.eval_line("two = add 1 1;").unwrap(), "two : i32");
+    assert_eq!(session.eval_line("add two 3").unwrap(), "5 : i32");
+    assert_eq!(session.eval_line("add").unwrap(), "<function> : i32 -> i32 -> i32");
+}
+
+#[test]
+fn redefine() {
+    let mut session = session();
+    session.eval_line("f x = x + 1").unwrap();
+    session.eval_line("g x = f x").unwrap();
+    assert_eq!(session.eval_line("f 1").unwrap(), "2 : i32");
+    session.eval_line("f x = x + 10").unwrap();
+    assert_eq!(session.eval_line("f 1").unwrap(), "11 : i32");
+    assert_eq!(session.eval_line("g 1").unwrap(), "2 : i32");
+    session.eval_line("f x = x + 100").unwrap();
+    assert_eq!(session.eval_line("f 1").unwrap(), "101 : i32");
+}
+
+#[test]
+fn invalid_definition_is_discarded() {
+    let mut session = session();
+    session.eval_line("f x = x + 1").unwrap();
+    assert!(session.eval_line(r#"f x = x ++ "a""#).is_err());
+    assert_eq!(session.eval_line("f 1").unwrap(), "2 : i32");
+    assert!(session.eval_line("g 1").is_err());
+}
+
+#[test]
+fn command_type() {
+    let mut session = session();
+    session.eval_line("add a b = a + b").unwrap();
+    assert_eq!(session.eval_line(":type add").unwrap(), "add : i32 -> i32 -> i32");
+    assert_eq!(session.eval_line(":type add 1 2").unwrap(), "add 1 2 : i32");
+    assert_eq!(session.eval_line(r#":t "a""#).unwrap(), r#""a" : String"#);
+}
+
+#[test]
+fn command_ast() {
+    let mut session = session();
+    assert_eq!(session.eval_line(":ast 1 + 2").unwrap(), "InfixOp +\n  Num 1\n  Num 2");
+    assert_eq!(session.eval_line(":ast f x = x").unwrap(), "Module\n  FnDef f\n    Arg x\n    Ident x");
+}
+
+#[test]
+fn command_ir() {
+    let mut session = session();
+    session.eval_line("add a b = a + b").unwrap();
+    assert!(session.eval_line(":ir").unwrap().contains("@add"));
+    assert!(session.eval_line(":ir add 1 2").unwrap().contains("@repl.it"));
+}
+
+#[test]
+fn command_load() {
+    let path = std::env::temp_dir().join("akanec_repl_load.akane");
+    fs::write(&path, "pub double x = x + x;").unwrap();
+    let mut session = session();
+    assert_eq!(session.eval_line(&format!(":load {}", path.display())).unwrap(), "double : i32 -> i32");
+    assert_eq!(session.eval_line("double 4").unwrap(), "8 : i32");
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn errors() {
+    let mut session = session();
+    assert_eq!(session.eval_line(":foo").unwrap_err().to_string(), "Unknown command: :foo");
+    assert_eq!(session.eval_line("import Math;").unwrap_err().to_string(), "Use :load to load modules.");
+    assert!(session.eval_line("1 +").is_err());
+}
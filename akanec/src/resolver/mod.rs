@@ -0,0 +1,214 @@
+#[cfg(test)]
+mod tests;
+
+use std::collections::HashMap;
+use anyhow::Result;
+use crate::{
+    data::{
+        ast::{
+            ModuleAst,
+            FnDefAst,
+            ExprAst,
+            IdentAst,
+        },
+        builtins::{
+            BUILTINS,
+            builtin_arity,
+        },
+        diagnostic::{
+            Diagnostic,
+            Diagnostics,
+            Severity,
+        },
+    },
+};
+
+/// Where a name is defined.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Def {
+    /// An index into the module's `fn_defs`.
+    Fn(usize),
+    /// An index into the module's `extern_fns`.
+    ExternFn(usize),
+    /// The indices of the function and of the parameter.
+    Param(usize, usize),
+    /// An index into the names imported into `resolve_with`.
+    Imported(usize),
+    Builtin,
+}
+
+/// An identifier in an expression and the definition it refers to.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Use {
+    pub ident: IdentAst,
+    pub def: Def,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SymbolTable {
+    /// The top-level functions by name, imported ones under their qualified names.
+    pub globals: HashMap<String, Def>,
+    own_prefix: Option<String>,
+    /// Every resolved identifier, in the order the functions are defined.
+    pub uses: Vec<Use>,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl SymbolTable {
+    /// The use whose identifier contains `offset`.
+    pub fn use_at(&self, offset: usize) -> Option<&Use> {
+        self.uses.iter().find(|use_| use_.ident.span.contains(offset))
+    }
+
+    /// Looks `name` up in the body of the function at `index`: parameters come first,
+    /// then the top-level functions, then the builtins, the order codegen looks names up in.
+    pub fn lookup(&self, index: usize, fn_def_ast: &FnDefAst, name: &str) -> Option<Def> {
+        if let Some(i) = fn_def_ast.left_def.args.iter().position(|arg| arg.name == name) {
+            return Some(Def::Param(index, i));
+        }
+        self.global(name).or_else(|| builtin_arity(name).map(|_| Def::Builtin))
+    }
+
+    pub fn global(&self, name: &str) -> Option<Def> {
+        let name = self.own_prefix.as_ref().and_then(|prefix| name.strip_prefix(prefix.as_str())).unwrap_or(name);
+        self.globals.get(name).copied()
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.diagnostics.iter().any(|diagnostic| diagnostic.severity == Severity::Error)
+    }
+}
+
+/// Resolves a module whose imports have been linked by the loader,
+/// failing with every error found if there are any.
+pub fn check(module_ast: &ModuleAst) -> Result<SymbolTable> {
+    let symbol_table = resolve(module_ast);
+    Diagnostics::check(&symbol_table.diagnostics)?;
+    Ok(symbol_table)
+}
+
+pub fn resolve(module_ast: &ModuleAst) -> SymbolTable {
+    resolve_with(module_ast, &[])
+}
+
+/// `imported` are the qualified names of functions of other modules, for a module resolved on its own.
+pub fn resolve_with(module_ast: &ModuleAst, imported: &[String]) -> SymbolTable {
+    let mut globals = HashMap::new();
+    for (i, name) in imported.iter().enumerate().rev() {
+        globals.insert(name.clone(), Def::Imported(i));
+    }
+    for (i, extern_fn_ast) in module_ast.extern_fns.iter().enumerate().rev() {
+        globals.insert(extern_fn_ast.ident.name.clone(), Def::ExternFn(i));
+    }
+    for (i, fn_def_ast) in module_ast.fn_defs.iter().enumerate().rev() {
+        globals.insert(fn_def_ast.left_def.ident.name.clone(), Def::Fn(i));
+    }
+    let symbol_table = SymbolTable {
+        globals,
+        own_prefix: module_ast.name.as_ref().map(|name| format!("{}.", name.name)),
+        uses: Vec::new(),
+        diagnostics: Vec::new(),
+    };
+    let mut resolver = Resolver { module_ast, imported, symbol_table };
+    for (i, fn_def_ast) in module_ast.fn_defs.iter().enumerate() {
+        resolver.resolve_fn_def(i, fn_def_ast);
+    }
+    resolver.symbol_table
+}
+
+struct Resolver<'a> {
+    module_ast: &'a ModuleAst,
+    imported: &'a [String],
+    symbol_table: SymbolTable,
+}
+
+impl Resolver<'_> {
+    fn resolve_fn_def(&mut self, index: usize, fn_def_ast: &FnDefAst) {
+        let args = &fn_def_ast.left_def.args;
+        for (i, arg) in args.iter().enumerate() {
+            if args[..i].iter().any(|prev| prev.name == arg.name) {
+                let message = format!("Duplicate parameter: {}", arg.name);
+                self.symbol_table.diagnostics.push(Diagnostic::error(message, arg.span));
+            }
+        }
+        self.resolve_expr(index, fn_def_ast, &fn_def_ast.expr);
+    }
+
+    fn resolve_expr(&mut self, index: usize, fn_def_ast: &FnDefAst, expr_ast: &ExprAst) {
+        match expr_ast {
+            ExprAst::Fn(fn_ast) => {
+                match fn_ast.fn_expr.as_ref() {
+                    ExprAst::Ident(ident_ast) => self.resolve_ident(index, fn_def_ast, ident_ast, true),
+                    fn_expr => self.resolve_expr(index, fn_def_ast, fn_expr),
+                }
+                self.resolve_expr(index, fn_def_ast, &fn_ast.arg_expr);
+            },
+            ExprAst::PrefixOp(prefix_op_ast) =>
+                self.resolve_expr(index, fn_def_ast, &prefix_op_ast.rhs),
+            ExprAst::InfixOp(infix_op_ast) => {
+                self.resolve_expr(index, fn_def_ast, &infix_op_ast.lhs);
+                self.resolve_expr(index, fn_def_ast, &infix_op_ast.rhs);
+            },
+            ExprAst::Ident(ident_ast) => self.resolve_ident(index, fn_def_ast, ident_ast, false),
+            ExprAst::List(list_ast) =>
+                for elem in &list_ast.elems {
+                    self.resolve_expr(index, fn_def_ast, elem);
+                },
+            ExprAst::Num(_) | ExprAst::Str(_) | ExprAst::Char(_) => (),
+        }
+    }
+
+    fn resolve_ident(&mut self, index: usize, fn_def_ast: &FnDefAst, ident_ast: &IdentAst, is_callee: bool) {
+        if let Some(def) = self.symbol_table.lookup(index, fn_def_ast, &ident_ast.name) {
+            self.symbol_table.uses.push(Use { ident: ident_ast.clone(), def });
+            return;
+        }
+        let what = if is_callee { "Undefined function" } else { "Unknown identifier" };
+        let message = match self.suggest(fn_def_ast, &ident_ast.name) {
+            Some(suggestion) => format!("{}: {} (did you mean {}?)", what, ident_ast.name, suggestion),
+            None => format!("{}: {}", what, ident_ast.name),
+        };
+        self.symbol_table.diagnostics.push(Diagnostic::error(message, ident_ast.span));
+    }
+
+    /// The closest visible name, if it is close enough to be a likely typo.
+    fn suggest<'b>(&'b self, fn_def_ast: &'b FnDefAst, name: &str) -> Option<&'b str> {
+        let len = name.chars().count();
+        let max_distance = (len / 3).max(1);
+        let candidates = fn_def_ast.left_def.args.iter().map(|arg| arg.name.as_str())
+            .chain(self.module_ast.fn_defs.iter().map(|fn_def_ast| fn_def_ast.left_def.ident.name.as_str()))
+            .chain(self.module_ast.extern_fns.iter().map(|extern_fn_ast| extern_fn_ast.ident.name.as_str()))
+            .chain(self.imported.iter().map(String::as_str))
+            .chain(BUILTINS);
+        let mut best = None;
+        for candidate in candidates {
+            let distance = edit_distance(name, candidate);
+            if distance <= max_distance && distance < len && best.is_none_or(|(_, best_distance)| distance < best_distance) {
+                best = Some((candidate, distance));
+            }
+        }
+        best.map(|(candidate, _)| candidate)
+    }
+}
+
+/// The number of inserted, removed, replaced or swapped adjacent characters turning `a` into `b`,
+/// so that transposed letters, a common typo, count as one edit.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a = a.chars().collect::<Vec<_>>();
+    let b = b.chars().collect::<Vec<_>>();
+    let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    d[0] = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j - 1] + cost).min(d[i - 1][j] + 1).min(d[i][j - 1] + 1);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
@@ -0,0 +1,90 @@
+use crate::{
+    data::{
+        ast::ModuleAst,
+        diagnostic::{
+            Diagnostics,
+            Severity,
+        },
+    },
+    lexer,
+    parser,
+};
+use super::Def;
+
+fn parse_module(code: &str) -> ModuleAst {
+    parser::parse(lexer::lex(code.to_owned()).unwrap()).unwrap()
+}
+
+fn diagnostics(code: &str) -> Vec<(Severity, String, usize, usize)> {
+    super::resolve(&parse_module(code)).diagnostics.into_iter()
+        .map(|diagnostic| (diagnostic.severity, diagnostic.message, diagnostic.span.start, diagnostic.span.end))
+        .collect()
+}
+
+#[test]
+fn resolve_uses() {
+    let code = "extern \"C\" abs : i32 -> i32;\ndouble x = x + x;\nf x = double (abs x) + length [g];\ng = 1;";
+    let symbol_table = super::resolve(&parse_module(code));
+    assert_eq!(symbol_table.diagnostics, vec![]);
+    let uses = symbol_table.uses.iter().map(|use_| (use_.ident.name.as_str(), use_.def)).collect::<Vec<_>>();
+    assert_eq!(
+        uses,
+        vec![
+            ("x", Def::Param(0, 0)),
+            ("x", Def::Param(0, 0)),
+            ("double", Def::Fn(0)),
+            ("abs", Def::ExternFn(0)),
+            ("x", Def::Param(1, 0)),
+            ("length", Def::Builtin),
+            ("g", Def::Fn(2)),
+        ]
+    );
+    let use_ = symbol_table.use_at(code.find("abs x").unwrap() + 1).unwrap();
+    assert_eq!(use_.def, Def::ExternFn(0));
+    assert_eq!(symbol_table.use_at(code.find("f x").unwrap()), None);
+}
+
+#[test]
+fn resolve_scopes() {
+    let module_ast = parse_module("module M;\nf length = length + M.g + Math.h;\ng = 0;");
+    let uses = super::resolve_with(&module_ast, &["Math.h".to_owned()]).uses.into_iter().map(|use_| use_.def).collect::<Vec<_>>();
+    assert_eq!(uses, vec![Def::Param(0, 0), Def::Fn(1), Def::Imported(0)]);
+}
+
+#[test]
+fn resolve_unbound() {
+    assert_eq!(
+        diagnostics("double x = x + x;\nf value = doubel valeu + lenght [y];"),
+        vec![
+            (Severity::Error, "Undefined function: doubel (did you mean double?)".to_owned(), 28, 34),
+            (Severity::Error, "Unknown identifier: valeu (did you mean value?)".to_owned(), 35, 40),
+            (Severity::Error, "Undefined function: lenght (did you mean length?)".to_owned(), 43, 49),
+            (Severity::Error, "Unknown identifier: y".to_owned(), 51, 52),
+        ]
+    );
+    assert_eq!(diagnostics("f xs = xz;")[0].1, "Unknown identifier: xz (did you mean xs?)");
+    assert_eq!(diagnostics("f = something;")[0].1, "Unknown identifier: something");
+}
+
+#[test]
+fn resolve_duplicates() {
+    assert_eq!(diagnostics("f x y x = x;\ng f = f;"), vec![(Severity::Error, "Duplicate parameter: x".to_owned(), 6, 7)]);
+}
+
+#[test]
+fn check_errors() {
+    assert!(super::check(&parse_module("g f = f;")).is_ok());
+    let e = super::check(&parse_module("f = a;\ng x x = b;")).unwrap_err();
+    assert_eq!(e.to_string(), "Unknown identifier: a\nDuplicate parameter: x\nUnknown identifier: b");
+    assert_eq!(e.downcast_ref::<Diagnostics>().unwrap().0.len(), 3);
+}
+
+#[test]
+fn edit_distance() {
+    assert_eq!(super::edit_distance("", "abc"), 3);
+    assert_eq!(super::edit_distance("kitten", "sitting"), 3);
+    assert_eq!(super::edit_distance("lenght", "length"), 1);
+    assert_eq!(super::edit_distance("ab", "bca"), 3);
+    assert_eq!(super::edit_distance("あい", "あう"), 1);
+    assert_eq!(super::edit_distance("same", "same"), 0);
+}
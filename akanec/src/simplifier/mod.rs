@@ -0,0 +1,241 @@
+#[cfg(test)]
+mod tests;
+
+use std::rc::Rc;
+use anyhow::Result;
+use crate::{
+    data::{
+        ast::{
+            ModuleAst,
+            FnDefAst,
+            ExprAst,
+            FnAst,
+            PrefixOpAst,
+            InfixOpAst,
+            IdentAst,
+            NumAst,
+            ListAst,
+        },
+        diagnostic::{
+            Diagnostic,
+            Diagnostics,
+        },
+        span::Span,
+        ty::{
+            Ty,
+            FnTy,
+        },
+        value::{
+            num_value,
+            neg_num_value,
+            checked_div,
+        },
+    },
+    resolver::{
+        Def,
+        SymbolTable,
+    },
+};
+
+/// Simplifies every function body, failing with the errors found in constant expressions if there are any.
+pub fn simplify(module_ast: &ModuleAst, symbol_table: &SymbolTable) -> Result<ModuleAst> {
+    let (module_ast, diagnostics) = run(module_ast, symbol_table);
+    Diagnostics::check(&diagnostics)?;
+    Ok(module_ast)
+}
+
+/// Reports the constant expressions the interpreter would fail to evaluate.
+pub fn diagnose(module_ast: &ModuleAst, symbol_table: &SymbolTable) -> Vec<Diagnostic> {
+    run(module_ast, symbol_table).1
+}
+
+fn run(module_ast: &ModuleAst, symbol_table: &SymbolTable) -> (ModuleAst, Vec<Diagnostic>) {
+    let bodies = module_ast.fn_defs.iter().map(|_| Body::Unfolded).collect();
+    let mut simplifier = Simplifier { module_ast, symbol_table, bodies, is_conditional: false, diagnostics: Vec::new() };
+    for i in 0..module_ast.fn_defs.len() {
+        simplifier.fold_body(i);
+    }
+    let mut module_ast = module_ast.clone();
+    for (fn_def_ast, body) in module_ast.fn_defs.iter_mut().zip(simplifier.bodies) {
+        if let Body::Folded(expr_ast) = body {
+            fn_def_ast.expr = expr_ast;
+        }
+    }
+    (module_ast, simplifier.diagnostics)
+}
+
+enum Body {
+    Unfolded,
+    /// Being folded, so a constant naming it refers to itself.
+    Folding,
+    Folded(ExprAst),
+}
+
+struct Simplifier<'a> {
+    module_ast: &'a ModuleAst,
+    symbol_table: &'a SymbolTable,
+    bodies: Vec<Body>,
+    /// Whether the expression being folded is the right-hand side of a `&&` or `||`
+    /// whose left-hand side is not constant, so that it may never be evaluated.
+    is_conditional: bool,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Simplifier<'_> {
+    /// Folds the body of the function at `index` once, however many constants name it.
+    fn fold_body(&mut self, index: usize) {
+        if !matches!(self.bodies[index], Body::Unfolded) {
+            return;
+        }
+        self.bodies[index] = Body::Folding;
+        let is_conditional = std::mem::replace(&mut self.is_conditional, false);
+        let fn_def_ast = &self.module_ast.fn_defs[index];
+        let expr_ast = self.fold_expr(index, fn_def_ast, &fn_def_ast.expr);
+        self.is_conditional = is_conditional;
+        self.bodies[index] = Body::Folded(expr_ast);
+    }
+
+    /// Folds the operators whose operands are constant the way the interpreter evaluates them,
+    /// leaving an expression that fails to evaluate as it is once it has been reported.
+    fn fold_expr(&mut self, index: usize, fn_def_ast: &FnDefAst, expr_ast: &ExprAst) -> ExprAst {
+        match expr_ast {
+            ExprAst::Fn(fn_ast) => {
+                let fn_expr = Rc::new(self.fold_expr(index, fn_def_ast, &fn_ast.fn_expr));
+                let arg_expr = Rc::new(self.fold_expr(index, fn_def_ast, &fn_ast.arg_expr));
+                ExprAst::Fn(FnAst { fn_expr, arg_expr, span: fn_ast.span })
+            },
+            ExprAst::PrefixOp(prefix_op_ast) => {
+                if let ("-", ExprAst::Num(num_ast)) = (prefix_op_ast.op_code.as_str(), prefix_op_ast.rhs.as_ref()) {
+                    return match neg_num_value(num_ast) {
+                        Ok(n) => constant(n, prefix_op_ast.span),
+                        Err(e) => {
+                            self.diagnostics.push(Diagnostic::error(e.to_string(), num_ast.span));
+                            expr_ast.clone()
+                        },
+                    };
+                }
+                let rhs = self.fold_expr(index, fn_def_ast, &prefix_op_ast.rhs);
+                match (prefix_op_ast.op_code.as_str(), value(&rhs)) {
+                    ("-", Some(n)) => constant(n.wrapping_neg(), prefix_op_ast.span),
+                    _ => ExprAst::PrefixOp(PrefixOpAst { op_code: prefix_op_ast.op_code.clone(), rhs: Rc::new(rhs), span: prefix_op_ast.span }),
+                }
+            },
+            ExprAst::InfixOp(infix_op_ast) => {
+                let op_code = infix_op_ast.op_code.as_str();
+                let lhs = self.fold_expr(index, fn_def_ast, &infix_op_ast.lhs);
+                let rhs = match (op_code, value(&lhs)) {
+                    ("&&" | "||", Some(lhs)) if (lhs != 0) == (op_code == "||") =>
+                        return constant((lhs != 0) as i32, infix_op_ast.span),
+                    ("&&" | "||", None) =>
+                        self.fold_conditional(index, fn_def_ast, &infix_op_ast.rhs),
+                    _ => self.fold_expr(index, fn_def_ast, &infix_op_ast.rhs),
+                };
+                if let (Some(lhs), Some(rhs)) = (value(&lhs), value(&rhs)) {
+                    match eval_infix_op(op_code, lhs, rhs) {
+                        Some(Ok(n)) => return constant(n, infix_op_ast.span),
+                        Some(Err(e)) => self.report(e.to_string(), infix_op_ast.span),
+                        None => (),
+                    }
+                }
+                ExprAst::InfixOp(InfixOpAst { op_code: infix_op_ast.op_code.clone(), lhs: Rc::new(lhs), rhs: Rc::new(rhs), span: infix_op_ast.span })
+            },
+            ExprAst::Ident(ident_ast) =>
+                match self.inline(index, fn_def_ast, ident_ast) {
+                    Some(n) => constant(n, ident_ast.span),
+                    None => expr_ast.clone(),
+                },
+            ExprAst::List(list_ast) => {
+                let elems = list_ast.elems.iter().map(|elem| self.fold_expr(index, fn_def_ast, elem)).collect();
+                ExprAst::List(ListAst { elems, span: list_ast.span })
+            },
+            ExprAst::Num(num_ast) => {
+                // Out of range wherever it is, unlike a fault that depends on evaluation.
+                if let Err(e) = num_value(num_ast) {
+                    self.diagnostics.push(Diagnostic::error(e.to_string(), num_ast.span));
+                }
+                expr_ast.clone()
+            },
+            ExprAst::Str(_) | ExprAst::Char(_) => expr_ast.clone(),
+        }
+    }
+
+    /// Folds an expression that may not be evaluated, reporting nothing that fails in it.
+    fn fold_conditional(&mut self, index: usize, fn_def_ast: &FnDefAst, expr_ast: &ExprAst) -> ExprAst {
+        let is_conditional = std::mem::replace(&mut self.is_conditional, true);
+        let expr_ast = self.fold_expr(index, fn_def_ast, expr_ast);
+        self.is_conditional = is_conditional;
+        expr_ast
+    }
+
+    /// The value of the function `ident_ast` names if it takes no arguments, returns an `i32`
+    /// and its body folds to a constant. The definition itself is kept, since it may be public.
+    fn inline(&mut self, index: usize, fn_def_ast: &FnDefAst, ident_ast: &IdentAst) -> Option<i32> {
+        let Some(Def::Fn(i)) = self.symbol_table.lookup(index, fn_def_ast, &ident_ast.name) else {
+            return None;
+        };
+        let def_ast = &self.module_ast.fn_defs[i];
+        let fn_ty = FnTy::from_ty_annot(def_ast.ty_annot.as_ref(), def_ast.left_def.args.len()).ok()?;
+        if !fn_ty.args.is_empty() || *fn_ty.ret != Ty::I32 {
+            return None;
+        }
+        self.fold_body(i);
+        match &self.bodies[i] {
+            Body::Folded(expr_ast) => value(expr_ast),
+            _ => None,
+        }
+    }
+
+    /// Faults in an expression that may never be evaluated are left for the runtime to report.
+    fn report(&mut self, message: String, span: Span) {
+        if !self.is_conditional {
+            self.diagnostics.push(Diagnostic::error(message, span));
+        }
+    }
+}
+
+/// `None` if `op_code` is not an operator on two `i32`s.
+fn eval_infix_op(op_code: &str, lhs: i32, rhs: i32) -> Option<Result<i32>> {
+    let n = match op_code {
+        "+" => lhs.wrapping_add(rhs),
+        "-" => lhs.wrapping_sub(rhs),
+        "*" => lhs.wrapping_mul(rhs),
+        "/" => return Some(checked_div(lhs, rhs, i32::checked_div)),
+        "%" => return Some(checked_div(lhs, rhs, i32::checked_rem)),
+        "==" => (lhs == rhs) as i32,
+        "!=" => (lhs != rhs) as i32,
+        "<" => (lhs < rhs) as i32,
+        "<=" => (lhs <= rhs) as i32,
+        ">" => (lhs > rhs) as i32,
+        ">=" => (lhs >= rhs) as i32,
+        "&&" => (lhs != 0 && rhs != 0) as i32,
+        "||" => (lhs != 0 || rhs != 0) as i32,
+        _ => return None,
+    };
+    Some(Ok(n))
+}
+
+/// The value of a literal, or of one negated as `constant` writes negative values.
+fn value(expr_ast: &ExprAst) -> Option<i32> {
+    match expr_ast {
+        ExprAst::Num(num_ast) => num_value(num_ast).ok(),
+        ExprAst::Char(char_ast) => Some(char_ast.value as i32),
+        ExprAst::PrefixOp(prefix_op_ast) if prefix_op_ast.op_code == "-" =>
+            match prefix_op_ast.rhs.as_ref() {
+                ExprAst::Num(num_ast) => neg_num_value(num_ast).ok(),
+                _ => None,
+            },
+        _ => None,
+    }
+}
+
+/// Numeric literals are unsigned, so a negative value is written as a negated one,
+/// `i32::MIN` as `-2147483648`, the one literal valid only negated.
+fn constant(n: i32, span: Span) -> ExprAst {
+    let num = ExprAst::Num(NumAst { value: n.unsigned_abs().to_string(), span });
+    if n < 0 {
+        ExprAst::PrefixOp(PrefixOpAst { op_code: "-".to_owned(), rhs: Rc::new(num), span })
+    }
+    else {
+        num
+    }
+}
@@ -0,0 +1,109 @@
+use crate::{
+    data::diagnostic::Severity,
+    lexer,
+    parser,
+    printer,
+    resolver,
+};
+
+fn simplify(code: &str) -> String {
+    let module_ast = parser::parse(lexer::lex(code.to_owned()).unwrap()).unwrap();
+    let symbol_table = resolver::resolve(&module_ast);
+    printer::module(&super::simplify(&module_ast, &symbol_table).unwrap())
+}
+
+fn diagnostics(code: &str) -> Vec<(Severity, String, usize, usize)> {
+    let module_ast = parser::parse(lexer::lex(code.to_owned()).unwrap()).unwrap();
+    let symbol_table = resolver::resolve(&module_ast);
+    super::diagnose(&module_ast, &symbol_table).into_iter()
+        .map(|diagnostic| (diagnostic.severity, diagnostic.message, diagnostic.span.start, diagnostic.span.end))
+        .collect()
+}
+
+fn messages(code: &str) -> Vec<String> {
+    diagnostics(code).into_iter().map(|(_, message, _, _)| message).collect()
+}
+
+#[test]
+fn simplify_arithmetic() {
+    assert_eq!(simplify("f = 1 + 2 * 3;"), "f = 7;\n");
+    assert_eq!(simplify("f x = x + (2 - 5) * 4;"), "f x = x + (-12);\n");
+    assert_eq!(simplify("f = 7 / (0 - 2) + 7 % (0 - 2) - (-3);"), "f = 1;\n");
+    assert_eq!(simplify("f = 'a' + 1;"), "f = 98;\n");
+    assert_eq!(simplify("f x = (1 < 2) :: (3 == 4) :: [2 >= 2 && 0 || 5, x * 1];"), "f x = 1 :: 0 :: [1, x * 1];\n");
+    assert_eq!(simplify("f x = length \"ab\" + (x + 1) + 2;"), "f x = length \"ab\" + (x + 1) + 2;\n");
+}
+
+#[test]
+fn simplify_short_circuits() {
+    assert_eq!(simplify("f = 1 || 1 % 0;"), "f = 1;\n");
+    assert_eq!(simplify("f = 0 && 1 / 0;"), "f = 0;\n");
+    assert_eq!(simplify("f x = x > 0 || 1 / 0;"), "f x = x > 0 || 1 / 0;\n");
+    assert_eq!(simplify("f x = x > 0 || 1 + 1;"), "f x = x > 0 || 2;\n");
+    assert_eq!(simplify("f x = 1 && x;\ng = 1 && 2 - 2;"), "f x = 1 && x;\ng = 0;\n");
+}
+
+#[test]
+fn simplify_overflow() {
+    assert_eq!(simplify("f = 2147483647 + 1;"), "f = -2147483648;\n");
+    assert_eq!(simplify("f = -2147483648 - 1;"), "f = 2147483647;\n");
+    assert_eq!(simplify("f = 65536 * 65536 + 1;"), "f = 1;\n");
+    assert_eq!(simplify("f = -2147483648 + 0;"), "f = -2147483648;\n");
+    assert_eq!(simplify("f = -(-2147483648);"), "f = -2147483648;\n");
+}
+
+#[test]
+fn simplify_consts() {
+    let code = "\
+pub n = 5;
+m = n * 2;
+f x = x + m - n;
+g n = n + m;
+s : String;
+s = \"a\";
+a = b + 1;
+b = a + 1;
+h x = a + s;
+";
+    let expected = "\
+pub n = 5;
+m = 10;
+f x = x + 10 - 5;
+g n = n + 10;
+s : String;
+s = \"a\";
+a = b + 1;
+b = a + 1;
+h x = a + s;
+";
+    assert_eq!(simplify(code), expected);
+}
+
+#[test]
+fn simplify_errors() {
+    assert_eq!(
+        diagnostics("f x = x + 1 / 0;\ng = (3 - 3) + 1 % (2 - 2);\nh = -2147483648 / (-1);\ni x = x / 0;"),
+        vec![
+            (Severity::Error, "Division by zero.".to_owned(), 10, 15),
+            (Severity::Error, "Division by zero.".to_owned(), 31, 42),
+            (Severity::Error, "Integer overflow.".to_owned(), 48, 66),
+        ]
+    );
+    assert_eq!(diagnostics("f = 1 || 1 % 0;\ng = 0 && 1 / 0;\nh x = x > 0 || 1 / 0;\ni x = x && 1 + 2;"), vec![]);
+    assert_eq!(messages("n = 1 / 0;\nf x = x && n;"), vec!["Division by zero."]);
+    assert_eq!(messages("f x = 1 && x + 1 / 0;"), vec!["Division by zero."]);
+    assert_eq!(
+        diagnostics("f = 99999999999;\ng x = x > 0 || 4294967296;\nh = -2147483649;"),
+        vec![
+            (Severity::Error, "Integer literal out of range.".to_owned(), 4, 15),
+            (Severity::Error, "Integer literal out of range.".to_owned(), 32, 42),
+            (Severity::Error, "Integer literal out of range.".to_owned(), 49, 59),
+        ]
+    );
+    assert_eq!(messages("f = 2147483648;\ng = 2147483648 + 1;"), vec!["Integer literal out of range.", "Integer literal out of range."]);
+    let code = "n = 0;\nf = 1 / n + 2;";
+    assert_eq!(diagnostics(code), vec![(Severity::Error, "Division by zero.".to_owned(), 11, 16)]);
+    let module_ast = parser::parse(lexer::lex(code.to_owned()).unwrap()).unwrap();
+    let e = super::simplify(&module_ast, &resolver::resolve(&module_ast)).unwrap_err();
+    assert_eq!(e.to_string(), "Division by zero.");
+}
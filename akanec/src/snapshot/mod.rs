@@ -0,0 +1,166 @@
+#[cfg(test)]
+mod tests;
+
+use std::{
+    env,
+    fmt::{
+        self,
+        Display,
+        Formatter,
+    },
+    fs,
+    path::{
+        Path,
+        PathBuf,
+    },
+};
+use anyhow::{
+    bail,
+    Result,
+};
+use crate::{
+    compiler::{
+        self,
+        Options,
+    },
+    dump,
+    lexer,
+    loader,
+    parser,
+    resolver,
+    arity,
+    simplifier,
+};
+
+pub const BLESS_VAR: &str = "AKANE_BLESS";
+pub const EXTS: [&str; 4] = ["tokens", "ast", "ll", "err"];
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Snapshot {
+    pub ext: &'static str,
+    pub content: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Mismatch {
+    pub path: PathBuf,
+    pub expected: Option<String>,
+    pub actual: Option<String>,
+}
+
+impl Display for Mismatch {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let path = self.path.display();
+        match (&self.expected, &self.actual) {
+            (None, _) => write!(f, "{}: snapshot missing; rerun with {}=1 to create it", path, BLESS_VAR),
+            (_, None) => write!(f, "{}: snapshot no longer produced; rerun with {}=1 to remove it", path, BLESS_VAR),
+            (Some(expected), Some(actual)) => {
+                let mut expected_lines = expected.lines();
+                let mut actual_lines = actual.lines();
+                let mut line = 1;
+                loop {
+                    match (expected_lines.next(), actual_lines.next()) {
+                        (Some(e), Some(a)) if e == a => line += 1,
+                        (e, a) => {
+                            writeln!(f, "{}:{}: snapshot differs; rerun with {}=1 to update it", path, line, BLESS_VAR)?;
+                            writeln!(f, "  expected: {}", e.unwrap_or("<end of file>"))?;
+                            return write!(f, "  actual:   {}", a.unwrap_or("<end of file>"));
+                        },
+                    }
+                }
+            },
+        }
+    }
+}
+
+pub fn is_bless() -> bool {
+    env::var_os(BLESS_VAR).is_some_and(|value| !value.is_empty() && value != "0")
+}
+
+/// Every `.akane` file in `dir` is a fixture whose snapshots live beside it.
+pub fn check_dir(dir: &str, options: &Options, bless: bool) -> Result<Vec<Mismatch>> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) => bail!("Cannot read {}: {}", dir, e),
+    };
+    let mut paths = entries.map(|entry| entry.map(|entry| entry.path())).collect::<Result<Vec<_>, _>>()?;
+    paths.retain(|path| path.extension().is_some_and(|ext| ext == "akane"));
+    paths.sort();
+    let mut mismatches = Vec::new();
+    for path in paths {
+        mismatches.extend(check_file(path.to_str().unwrap(), options, bless)?);
+    }
+    Ok(mismatches)
+}
+
+pub fn check_file(in_path: &str, options: &Options, bless: bool) -> Result<Vec<Mismatch>> {
+    let snapshots = render(in_path, options)?;
+    let mut mismatches = Vec::new();
+    for ext in EXTS {
+        let path = Path::new(in_path).with_extension(ext);
+        let expected = fs::read_to_string(&path).ok();
+        let actual = snapshots.iter().find(|snapshot| snapshot.ext == ext).map(|snapshot| snapshot.content.clone());
+        if expected == actual {
+            continue;
+        }
+        if bless {
+            match &actual {
+                Some(content) => fs::write(&path, content)?,
+                None => fs::remove_file(&path)?,
+            }
+        }
+        else {
+            mismatches.push(Mismatch { path, expected, actual });
+        }
+    }
+    Ok(mismatches)
+}
+
+/// Runs the pipeline as far as it gets, producing a snapshot for each stage passed
+/// and an `err` snapshot naming the stage that failed.
+pub fn render(in_path: &str, options: &Options) -> Result<Vec<Snapshot>> {
+    let code = match fs::read_to_string(in_path) {
+        Ok(code) => code,
+        Err(e) => bail!("Cannot read {}: {}", in_path, e),
+    };
+    let mut snapshots = Vec::new();
+    let tokens = match lexer::lex(code) {
+        Ok(tokens) => tokens,
+        Err(e) => return Ok(with_error(snapshots, "lex", e)),
+    };
+    snapshots.push(Snapshot { ext: "tokens", content: dump::tokens(&tokens) });
+    let module_ast = match parser::parse(tokens) {
+        Ok(module_ast) => module_ast,
+        Err(e) => return Ok(with_error(snapshots, "parse", e)),
+    };
+    snapshots.push(Snapshot { ext: "ast", content: format!("{:#?}\n", module_ast) });
+    let module_ast = match loader::load(in_path, &options.search_paths) {
+        Ok(module_ast) => module_ast,
+        Err(e) => return Ok(with_error(snapshots, "load", e)),
+    };
+    let symbol_table = match resolver::check(&module_ast) {
+        Ok(symbol_table) => symbol_table,
+        Err(e) => return Ok(with_error(snapshots, "resolve", e)),
+    };
+    if let Err(e) = arity::check(&module_ast, &symbol_table) {
+        return Ok(with_error(snapshots, "arity", e));
+    }
+    if let Err(e) = simplifier::simplify(&module_ast, &symbol_table) {
+        return Ok(with_error(snapshots, "simplify", e));
+    }
+    let module_id = Path::new(in_path).file_name().unwrap().to_str().unwrap();
+    let mut llvm = match compiler::gen_module(module_id, &module_ast, options) {
+        Ok(llvm) => llvm,
+        Err(e) => return Ok(with_error(snapshots, "codegen", e)),
+    };
+    if let Err(e) = compiler::optimize(&mut llvm, options) {
+        return Ok(with_error(snapshots, "codegen", e));
+    }
+    snapshots.push(Snapshot { ext: "ll", content: llvm.print_module_to_string() });
+    Ok(snapshots)
+}
+
+fn with_error(mut snapshots: Vec<Snapshot>, stage: &str, e: anyhow::Error) -> Vec<Snapshot> {
+    snapshots.push(Snapshot { ext: "err", content: format!("{}: {}\n", stage, e) });
+    snapshots
+}
@@ -0,0 +1,86 @@
+use std::{
+    env,
+    fs,
+    path::{
+        Path,
+        PathBuf,
+    },
+    process,
+};
+use super::{
+    Mismatch,
+    Options,
+};
+
+fn write_fixture(test_name: &str, code: &str) -> (PathBuf, PathBuf) {
+    let dir = env::temp_dir().join(format!("akanec_snapshot_{}_{}", test_name, process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let in_path = dir.join("main.akane");
+    fs::write(&in_path, code).unwrap();
+    (dir, in_path)
+}
+
+fn exts(in_path: &Path) -> Vec<&'static str> {
+    super::render(in_path.to_str().unwrap(), &Options::default()).unwrap().into_iter().map(|snapshot| snapshot.ext).collect()
+}
+
+#[test]
+fn render_stages() {
+    let (dir, in_path) = write_fixture("stages", "pub f x = x + 1;");
+    assert_eq!(exts(&in_path), vec!["tokens", "ast", "ll"]);
+    fs::write(&in_path, "f x = \"x;").unwrap();
+    assert_eq!(exts(&in_path), vec!["err"]);
+    fs::write(&in_path, "f x = ;").unwrap();
+    assert_eq!(exts(&in_path), vec!["tokens", "err"]);
+    fs::write(&in_path, "f x = g x;").unwrap();
+    assert_eq!(exts(&in_path), vec!["tokens", "ast", "err"]);
+    let snapshots = super::render(in_path.to_str().unwrap(), &Options::default()).unwrap();
+    assert!(snapshots[2].content.starts_with("resolve: "));
+    fs::write(&in_path, "f x = x x;").unwrap();
+    let snapshots = super::render(in_path.to_str().unwrap(), &Options::default()).unwrap();
+    assert!(snapshots[2].content.starts_with("arity: "));
+    fs::write(&in_path, "f x = x + \"x\";").unwrap();
+    let snapshots = super::render(in_path.to_str().unwrap(), &Options::default()).unwrap();
+    assert!(snapshots[2].content.starts_with("codegen: "));
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn check_and_bless() {
+    let (dir, in_path) = write_fixture("bless", "pub f x = x + 1;");
+    let in_path = in_path.to_str().unwrap();
+    let options = Options::default();
+
+    let mismatches = super::check_file(in_path, &options, false).unwrap();
+    assert_eq!(mismatches.len(), 3);
+    assert!(mismatches.iter().all(|mismatch| mismatch.expected.is_none()));
+
+    assert_eq!(super::check_file(in_path, &options, true).unwrap(), vec![]);
+    assert_eq!(super::check_file(in_path, &options, false).unwrap(), vec![]);
+
+    fs::write(in_path, "pub f x = x - 1;").unwrap();
+    let mismatches = super::check_file(in_path, &options, false).unwrap();
+    assert_eq!(mismatches.iter().map(|mismatch| mismatch.path.extension().unwrap().to_str().unwrap()).collect::<Vec<_>>(), vec!["tokens", "ast", "ll"]);
+
+    fs::write(in_path, "pub f x = ;").unwrap();
+    super::check_file(in_path, &options, true).unwrap();
+    assert!(!dir.join("main.ast").exists());
+    assert!(!dir.join("main.ll").exists());
+    assert_eq!(fs::read_to_string(dir.join("main.err")).unwrap(), "parse: Expression required.\n");
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn show_mismatch() {
+    let mismatch = Mismatch {
+        path: PathBuf::from("a.ll"),
+        expected: Some("x\ny\nz\n".to_owned()),
+        actual: Some("x\nw\nz\n".to_owned()),
+    };
+    assert_eq!(mismatch.to_string(), "a.ll:2: snapshot differs; rerun with AKANE_BLESS=1 to update it\n  expected: y\n  actual:   w");
+    let mismatch = Mismatch { path: PathBuf::from("a.ll"), expected: Some("x\n".to_owned()), actual: Some("x\ny\n".to_owned()) };
+    assert!(mismatch.to_string().contains("a.ll:2:") && mismatch.to_string().contains("expected: <end of file>"));
+    let mismatch = Mismatch { path: PathBuf::from("a.err"), expected: None, actual: Some(String::new()) };
+    assert_eq!(mismatch.to_string(), "a.err: snapshot missing; rerun with AKANE_BLESS=1 to create it");
+}
@@ -1,38 +1,175 @@
 use std::{
+    collections::{
+        HashMap,
+        HashSet,
+    },
     env,
+    fs,
+    path::{
+        Path,
+        PathBuf,
+    },
     process::Command,
-    path::Path,
 };
+use akanec::{
+    compiler::{
+        self,
+        Emit,
+        Options,
+    },
+    data::{
+        ast::ModuleAst,
+        ty::{
+            Ty,
+            FnTy,
+        },
+    },
+    lexer,
+    loader,
+    parser,
+};
+
+const TEST_DIR: &str = "./src/tests";
+
+const RUST_KEYWORDS: [&str; 38] = [
+    "as", "async", "await", "box", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern", "false",
+    "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return", "self",
+    "static", "struct", "super", "trait", "true", "type", "unsafe", "use", "where", "while",
+];
 
 fn main() {
     let out_dir = env::var("OUT_DIR").unwrap();
+    let mut paths = Vec::new();
+    find_akane_files(Path::new(TEST_DIR), &mut paths);
+    paths.sort();
+
+    let options = Options { emit: Emit::Obj, ..Default::default() };
+    let mut objs = Vec::new();
+    let mut decls = Vec::new();
+    let mut symbols = HashMap::new();
+    for path in roots(&paths) {
+        let path = path.to_str().unwrap();
+        let mut module_ast = loader::load(path, &options.search_paths).unwrap();
+        let exported = exported_names(&module_ast, path);
+        for fn_def_ast in &mut module_ast.fn_defs {
+            fn_def_ast.is_pub &= exported.contains(&fn_def_ast.left_def.ident.name);
+        }
+        for name in &exported {
+            if let Some(other) = symbols.insert(name.clone(), path.to_owned()) {
+                panic!("{} is exported by both {} and {}", name, other, path);
+            }
+        }
+        let obj = format!("{}/{}.o", out_dir, obj_name(path));
+        compiler::compile_module(path, &module_ast, &obj, &options).unwrap();
+        objs.push(obj);
+        decls.extend(gen_decls(&module_ast));
+    }
 
-    if Path::new("./src/tests/akane/test.akane").exists() {
-        akanec::compiler::compile(
-            "./src/tests/akane/test.akane",
-            &format!("{}/test.ll", out_dir)
-        ).unwrap();
-        Command::new("llc")
-            .args([
-                "--filetype",
-                "obj",
-                "-o",
-                &format!("{}/test.o", out_dir),
-                &format!("{}/test.ll", out_dir),
-            ])
-            .status()
-            .unwrap();
-        Command::new("ar")
-            .args([
-                "r",
-                &format!("{}/libakanectest.a", out_dir),
-                &format!("{}/test.o", out_dir),
-            ])
-            .status()
-            .unwrap();
+    let lib = format!("{}/libakanectest.a", out_dir);
+    let _ = fs::remove_file(&lib);
+    if !objs.is_empty() {
+        let status = Command::new("ar").arg("crs").arg(&lib).args(&objs).status().unwrap();
+        assert!(status.success(), "ar failed: {}", status);
     }
+    fs::write(format!("{}/akane_ffi.rs", out_dir), gen_ffi(&decls)).unwrap();
 
     println!("cargo:rustc-link-search=native={}", out_dir);
-    println!("cargo:rustc-link-lib=static=akanectest");
-    println!("cargo:rerun-if-changed={}/libakanectest.a", out_dir);
+    if !objs.is_empty() {
+        println!("cargo:rustc-link-lib=static=akanectest");
+    }
+    println!("cargo:rerun-if-changed={}", TEST_DIR);
+}
+
+fn find_akane_files(dir: &Path, paths: &mut Vec<PathBuf>) {
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            find_akane_files(&path, paths);
+        }
+        else if path.extension().map_or(false, |ext| ext == "akane") {
+            paths.push(path);
+        }
+    }
+}
+
+/// Modules imported by another test program are linked into their importers by the loader,
+/// so only the programs nobody imports are compiled on their own.
+fn roots(paths: &[PathBuf]) -> Vec<&PathBuf> {
+    let mut names = Vec::new();
+    let mut imported = HashSet::new();
+    for path in paths {
+        let code = fs::read_to_string(path).unwrap();
+        let module_ast = parser::parse(lexer::lex(code).unwrap()).unwrap();
+        names.push(module_ast.name.map(|name| name.name));
+        imported.extend(module_ast.imports.into_iter().map(|import| import.module.name));
+    }
+    paths.iter().zip(names).filter(|(_, name)| !name.as_ref().map_or(false, |name| imported.contains(name))).map(|(path, _)| path).collect()
+}
+
+/// Only the public functions of the program itself are exported;
+/// those of imported modules become internal so that two programs can share a module.
+fn exported_names(module_ast: &ModuleAst, path: &str) -> HashSet<String> {
+    let code = fs::read_to_string(path).unwrap();
+    let own = parser::parse(lexer::lex(code).unwrap()).unwrap();
+    let prefix = own.name.map(|name| format!("{}.", name.name)).unwrap_or_default();
+    let names = own.fn_defs.iter().map(|fn_def_ast| format!("{}{}", prefix, fn_def_ast.left_def.ident.name)).collect::<HashSet<_>>();
+    if names.contains("main") {
+        panic!("{} must not define main", path);
+    }
+    module_ast.fn_defs.iter()
+        .filter(|fn_def_ast| fn_def_ast.is_pub && names.contains(&fn_def_ast.left_def.ident.name))
+        .map(|fn_def_ast| fn_def_ast.left_def.ident.name.clone())
+        .collect()
+}
+
+fn obj_name(path: &str) -> String {
+    let relative = path.strip_prefix(TEST_DIR).unwrap().trim_start_matches('/').trim_end_matches(".akane");
+    relative.replace(['/', '.'], "_")
+}
+
+fn gen_decls(module_ast: &ModuleAst) -> Vec<String> {
+    let mut decls = Vec::new();
+    for fn_def_ast in module_ast.fn_defs.iter().filter(|fn_def_ast| fn_def_ast.is_pub) {
+        let name = &fn_def_ast.left_def.ident.name;
+        let fn_ty = FnTy::from_ty_annot(fn_def_ast.ty_annot.as_ref(), fn_def_ast.left_def.args.len()).unwrap();
+        let args = fn_def_ast.left_def.args.iter().zip(&fn_ty.args).enumerate()
+            .map(|(i, (arg, ty))| format!("{}: {}", arg_name(&arg.name, i), rust_ty(ty)))
+            .collect::<Vec<_>>();
+        let link_name = if name.contains('.') { format!("    #[link_name = \"{}\"]\n", name) } else { String::new() };
+        decls.push(format!("{}    pub fn {}({}) -> {};", link_name, name.replace('.', "_"), args.join(", "), rust_ty(&fn_ty.ret)));
+    }
+    decls
+}
+
+fn gen_ffi(decls: &[String]) -> String {
+    let mut s = String::from("// Generated by build.rs from the Akane test programs.\n\n");
+    s += "#[allow(dead_code, non_snake_case)]\nextern \"C\" {\n";
+    for decl in decls {
+        s += decl;
+        s += "\n";
+    }
+    s += "}\n";
+    s
+}
+
+fn arg_name(name: &str, index: usize) -> String {
+    if RUST_KEYWORDS.contains(&name) {
+        format!("arg{}", index)
+    }
+    else {
+        name.to_owned()
+    }
+}
+
+fn rust_ty(ty: &Ty) -> String {
+    match ty {
+        Ty::I32 => "::std::os::raw::c_int".to_owned(),
+        Ty::Ptr => "*const ::std::os::raw::c_void".to_owned(),
+        Ty::Str => "*const AkaneStr".to_owned(),
+        Ty::List => "*const AkaneList".to_owned(),
+        Ty::Fn(fn_ty) => {
+            let args = fn_ty.args.iter().map(rust_ty).collect::<Vec<_>>();
+            format!("extern \"C\" fn({}) -> {}", args.join(", "), rust_ty(&fn_ty.ret))
+        },
+    }
 }
@@ -0,0 +1,8 @@
+; ModuleID = 'Shapes.akane'
+source_filename = "Shapes.akane"
+
+define i32 @Shapes.area(i32 %w, i32 %h) {
+entry:
+  %multmp = mul i32 %w, %h
+  ret i32 %multmp
+}
@@ -0,0 +1,60 @@
+; ModuleID = 'arithmetic.akane'
+source_filename = "arithmetic.akane"
+
+define i32 @poly(i32 %x) {
+entry:
+  %multmp = mul i32 %x, %x
+  %multmp1 = mul i32 %multmp, 3
+  %multmp2 = mul i32 %x, 2
+  %subtmp = sub i32 %multmp1, %multmp2
+  %addtmp = add i32 %subtmp, 1
+  ret i32 %addtmp
+}
+
+define i32 @neg(i32 %x) {
+entry:
+  %negtmp = sub i32 0, %x
+  ret i32 %negtmp
+}
+
+define i32 @mean(i32 %a, i32 %b) {
+entry:
+  %addtmp = add i32 %a, %b
+  %divtmp = sdiv i32 %addtmp, 2
+  ret i32 %divtmp
+}
+
+define i32 @in_range(i32 %x) {
+entry:
+  %cmptmp = icmp sge i32 %x, 0
+  %booltmp = zext i1 %cmptmp to i32
+  %cmptmp1 = icmp ne i32 %booltmp, 0
+  %booltmp2 = zext i1 %cmptmp1 to i32
+  %cond = icmp ne i32 %booltmp2, 0
+  br i1 %cond, label %rhs, label %end
+
+rhs:                                              ; preds = %entry
+  %cmptmp3 = icmp slt i32 %x, 100
+  %booltmp4 = zext i1 %cmptmp3 to i32
+  %cmptmp5 = icmp ne i32 %booltmp4, 0
+  %booltmp6 = zext i1 %cmptmp5 to i32
+  br label %end
+
+end:                                              ; preds = %rhs, %entry
+  %logictmp = phi i32 [ %booltmp2, %entry ], [ %booltmp6, %rhs ]
+  %cmptmp7 = icmp ne i32 %logictmp, 0
+  %booltmp8 = zext i1 %cmptmp7 to i32
+  %cond11 = icmp ne i32 %booltmp8, 0
+  br i1 %cond11, label %end10, label %rhs9
+
+rhs9:                                             ; preds = %end
+  %cmptmp12 = icmp eq i32 %x, -1
+  %booltmp13 = zext i1 %cmptmp12 to i32
+  %cmptmp14 = icmp ne i32 %booltmp13, 0
+  %booltmp15 = zext i1 %cmptmp14 to i32
+  br label %end10
+
+end10:                                            ; preds = %rhs9, %end
+  %logictmp16 = phi i32 [ %booltmp8, %end ], [ %booltmp15, %rhs9 ]
+  ret i32 %logictmp16
+}
@@ -0,0 +1,23 @@
+; ModuleID = 'constants.akane'
+source_filename = "constants.akane"
+
+define internal i32 @size() {
+entry:
+  ret i32 16
+}
+
+define i32 @area() {
+entry:
+  ret i32 256
+}
+
+define i32 @wrapped() {
+entry:
+  ret i32 -2147483648
+}
+
+define i32 @scaled(i32 %x) {
+entry:
+  %multmp = mul i32 %x, 3
+  ret i32 %multmp
+}
@@ -0,0 +1,23 @@
+; ModuleID = 'functions.akane'
+source_filename = "functions.akane"
+
+declare i32 @abs(i32)
+
+define internal i32 @inc(i32 %x) {
+entry:
+  %addtmp = add i32 %x, 1
+  ret i32 %addtmp
+}
+
+define i32 @apply(i32 (i32)* %f, i32 %x) {
+entry:
+  %calltmp = call i32 %f(i32 %x)
+  ret i32 %calltmp
+}
+
+define i32 @twice(i32 %x) {
+entry:
+  %calltmp = call i32 @abs(i32 %x)
+  %calltmp1 = call i32 @apply(i32 (i32)* @inc, i32 %calltmp)
+  ret i32 %calltmp1
+}
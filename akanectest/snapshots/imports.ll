@@ -0,0 +1,14 @@
+; ModuleID = 'imports.akane'
+source_filename = "imports.akane"
+
+define i32 @Shapes.area(i32 %w, i32 %h) {
+entry:
+  %multmp = mul i32 %w, %h
+  ret i32 %multmp
+}
+
+define i32 @square(i32 %x) {
+entry:
+  %calltmp = call i32 @Shapes.area(i32 %x, i32 %x)
+  ret i32 %calltmp
+}
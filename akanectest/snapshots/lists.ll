@@ -0,0 +1,114 @@
+; ModuleID = 'lists.akane'
+source_filename = "lists.akane"
+
+define i8* @push(i32 %x, i8* %xs) {
+entry:
+  %malloctmp = call i8* @malloc(i64 16)
+  %lenptr = bitcast i8* %malloctmp to i64*
+  store i64 2, i64* %lenptr, align 4
+  %offsetptr = getelementptr inbounds i8, i8* %malloctmp, i64 8
+  %dataptr = bitcast i8* %offsetptr to i32*
+  %elemptr = getelementptr inbounds i32, i32* %dataptr, i64 0
+  store i32 %x, i32* %elemptr, align 4
+  %offsetptr1 = getelementptr inbounds i8, i8* %malloctmp, i64 8
+  %dataptr2 = bitcast i8* %offsetptr1 to i32*
+  %elemptr3 = getelementptr inbounds i32, i32* %dataptr2, i64 1
+  store i32 2, i32* %elemptr3, align 4
+  %helpertmp = call i8* @akane.list.concat(i8* %xs, i8* %malloctmp)
+  %helpertmp4 = call i8* @akane.list.cons(i32 %x, i8* %helpertmp)
+  ret i8* %helpertmp4
+}
+
+define i32 @nth(i8* %xs, i32 %i) {
+entry:
+  %helpertmp = call i32 @akane.list.index(i8* %xs, i32 %i)
+  ret i32 %helpertmp
+}
+
+declare i8* @malloc(i64)
+
+define internal i8* @akane.list.concat(i8* %0, i8* %1) {
+entry:
+  %lenptr = bitcast i8* %0 to i64*
+  %len = load i64, i64* %lenptr, align 4
+  %lenptr1 = bitcast i8* %1 to i64*
+  %len2 = load i64, i64* %lenptr1, align 4
+  %len3 = add i64 %len, %len2
+  %datasize = mul i64 %len3, 4
+  %size = add i64 %datasize, 8
+  %malloctmp = call i8* @malloc(i64 %size)
+  %lenptr4 = bitcast i8* %malloctmp to i64*
+  store i64 %len3, i64* %lenptr4, align 4
+  %offsetptr = getelementptr inbounds i8, i8* %malloctmp, i64 8
+  %dataptr = bitcast i8* %offsetptr to i32*
+  %elemptr = getelementptr inbounds i32, i32* %dataptr, i64 0
+  %offsetptr5 = getelementptr inbounds i8, i8* %0, i64 8
+  %dataptr6 = bitcast i8* %offsetptr5 to i32*
+  %elemptr7 = getelementptr inbounds i32, i32* %dataptr6, i64 0
+  %offsetptr8 = getelementptr inbounds i8, i8* %1, i64 8
+  %dataptr9 = bitcast i8* %offsetptr8 to i32*
+  %elemptr10 = getelementptr inbounds i32, i32* %dataptr9, i64 0
+  %size11 = mul i64 %len, 4
+  %dst = bitcast i32* %elemptr to i8*
+  %src = bitcast i32* %elemptr7 to i8*
+  %memcpytmp = call i8* @memcpy(i8* %dst, i8* %src, i64 %size11)
+  %offsetptr12 = getelementptr inbounds i8, i8* %malloctmp, i64 8
+  %dataptr13 = bitcast i8* %offsetptr12 to i32*
+  %elemptr14 = getelementptr inbounds i32, i32* %dataptr13, i64 %len
+  %size15 = mul i64 %len2, 4
+  %dst16 = bitcast i32* %elemptr14 to i8*
+  %src17 = bitcast i32* %elemptr10 to i8*
+  %memcpytmp18 = call i8* @memcpy(i8* %dst16, i8* %src17, i64 %size15)
+  ret i8* %malloctmp
+}
+
+declare i8* @memcpy(i8*, i8*, i64)
+
+define internal i8* @akane.list.cons(i32 %0, i8* %1) {
+entry:
+  %lenptr = bitcast i8* %1 to i64*
+  %len = load i64, i64* %lenptr, align 4
+  %len1 = add i64 %len, 1
+  %datasize = mul i64 %len1, 4
+  %size = add i64 %datasize, 8
+  %malloctmp = call i8* @malloc(i64 %size)
+  %lenptr2 = bitcast i8* %malloctmp to i64*
+  store i64 %len1, i64* %lenptr2, align 4
+  %offsetptr = getelementptr inbounds i8, i8* %malloctmp, i64 8
+  %dataptr = bitcast i8* %offsetptr to i32*
+  %elemptr = getelementptr inbounds i32, i32* %dataptr, i64 0
+  store i32 %0, i32* %elemptr, align 4
+  %offsetptr3 = getelementptr inbounds i8, i8* %malloctmp, i64 8
+  %dataptr4 = bitcast i8* %offsetptr3 to i32*
+  %elemptr5 = getelementptr inbounds i32, i32* %dataptr4, i64 1
+  %offsetptr6 = getelementptr inbounds i8, i8* %1, i64 8
+  %dataptr7 = bitcast i8* %offsetptr6 to i32*
+  %elemptr8 = getelementptr inbounds i32, i32* %dataptr7, i64 0
+  %size9 = mul i64 %len, 4
+  %dst = bitcast i32* %elemptr5 to i8*
+  %src = bitcast i32* %elemptr8 to i8*
+  %memcpytmp = call i8* @memcpy(i8* %dst, i8* %src, i64 %size9)
+  ret i8* %malloctmp
+}
+
+define internal i32 @akane.list.index(i8* %0, i32 %1) {
+entry:
+  %lenptr = bitcast i8* %0 to i64*
+  %len = load i64, i64* %lenptr, align 4
+  %index = sext i32 %1 to i64
+  %inbounds1 = icmp ult i64 %index, %len
+  br i1 %inbounds1, label %inbounds, label %outofbounds
+
+inbounds:                                         ; preds = %entry
+  %offsetptr = getelementptr inbounds i8, i8* %0, i64 8
+  %dataptr = bitcast i8* %offsetptr to i32*
+  %elemptr = getelementptr inbounds i32, i32* %dataptr, i64 %index
+  %elem = load i32, i32* %elemptr, align 4
+  ret i32 %elem
+
+outofbounds:                                      ; preds = %entry
+  call void @abort()
+  unreachable
+}
+
+declare void @abort()
@@ -0,0 +1,73 @@
+; ModuleID = 'strings.akane'
+source_filename = "strings.akane"
+
+@.str = private unnamed_addr constant { i64, [8 x i8] } { i64 7, [8 x i8] c"Hello, \00" }
+@.str.1 = private unnamed_addr constant { i64, [3 x i8] } { i64 2, [3 x i8] c"!\0A\00" }
+
+define i8* @greet(i8* %name) {
+entry:
+  %helpertmp = call i8* @akane.str.concat(i8* %name, i8* bitcast ({ i64, [3 x i8] }* @.str.1 to i8*))
+  %helpertmp1 = call i8* @akane.str.concat(i8* bitcast ({ i64, [8 x i8] }* @.str to i8*), i8* %helpertmp)
+  ret i8* %helpertmp1
+}
+
+define i32 @same(i8* %a, i8* %b) {
+entry:
+  %helpertmp = call i32 @akane.str.eq(i8* %a, i8* %b)
+  ret i32 %helpertmp
+}
+
+define i32 @hiragana() {
+entry:
+  ret i32 12354
+}
+
+define internal i8* @akane.str.concat(i8* %0, i8* %1) {
+entry:
+  %lenptr = bitcast i8* %0 to i64*
+  %len = load i64, i64* %lenptr, align 4
+  %lenptr1 = bitcast i8* %1 to i64*
+  %len2 = load i64, i64* %lenptr1, align 4
+  %len3 = add i64 %len, %len2
+  %size = add i64 %len3, 9
+  %malloctmp = call i8* @malloc(i64 %size)
+  %lenptr4 = bitcast i8* %malloctmp to i64*
+  store i64 %len3, i64* %lenptr4, align 4
+  %offsetptr = getelementptr inbounds i8, i8* %malloctmp, i64 8
+  %offsetptr5 = getelementptr inbounds i8, i8* %0, i64 8
+  %offsetptr6 = getelementptr inbounds i8, i8* %1, i64 8
+  %memcpytmp = call i8* @memcpy(i8* %offsetptr, i8* %offsetptr5, i64 %len)
+  %offsetptr7 = getelementptr inbounds i8, i8* %offsetptr, i64 %len
+  %memcpytmp8 = call i8* @memcpy(i8* %offsetptr7, i8* %offsetptr6, i64 %len2)
+  %offsetptr9 = getelementptr inbounds i8, i8* %offsetptr, i64 %len3
+  store i8 0, i8* %offsetptr9, align 1
+  ret i8* %malloctmp
+}
+
+declare i8* @malloc(i64)
+
+declare i8* @memcpy(i8*, i8*, i64)
+
+define internal i32 @akane.str.eq(i8* %0, i8* %1) {
+entry:
+  %lenptr = bitcast i8* %0 to i64*
+  %len = load i64, i64* %lenptr, align 4
+  %lenptr1 = bitcast i8* %1 to i64*
+  %len2 = load i64, i64* %lenptr1, align 4
+  %leneq = icmp eq i64 %len, %len2
+  br i1 %leneq, label %cmp, label %end
+
+cmp:                                              ; preds = %entry
+  %offsetptr = getelementptr inbounds i8, i8* %0, i64 8
+  %offsetptr3 = getelementptr inbounds i8, i8* %1, i64 8
+  %memcmptmp = call i32 @memcmp(i8* %offsetptr, i8* %offsetptr3, i64 %len)
+  %dataeq = icmp eq i32 %memcmptmp, 0
+  %dataeq4 = zext i1 %dataeq to i32
+  br label %end
+
+end:                                              ; preds = %cmp, %entry
+  %eq = phi i32 [ 0, %entry ], [ %dataeq4, %cmp ]
+  ret i32 %eq
+}
+
+declare i32 @memcmp(i8*, i8*, i64)
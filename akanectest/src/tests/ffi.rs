@@ -1,7 +1,58 @@
-use std::os::raw::c_int;
+use std::{
+    ffi::CStr,
+    os::raw::{
+        c_char,
+        c_int,
+    },
+    slice,
+    str,
+};
 
-#[link(name = "akanectest")]
-extern "C" {
-    pub fn simple_add_one(a: c_int) -> c_int;
-    pub fn simple_add(a: c_int, b: c_int) -> c_int;
+#[repr(C)]
+pub struct AkaneStr {
+    len: i64,
+    data: [u8; 0],
+}
+
+#[repr(C)]
+pub struct AkaneList {
+    len: i64,
+    data: [i32; 0],
+}
+
+include!(concat!(env!("OUT_DIR"), "/akane_ffi.rs"));
+
+#[no_mangle]
+pub unsafe extern "C" fn akanetest_count_bytes(s: *const c_char) -> c_int {
+    CStr::from_ptr(s).to_bytes().len() as c_int
+}
+
+pub fn to_akane_str(s: &str) -> Vec<i64> {
+    let mut buf = vec![0i64; 1 + (s.len() + 8) / 8];
+    buf[0] = s.len() as i64;
+    unsafe {
+        let data = buf.as_mut_ptr().add(1) as *mut u8;
+        data.copy_from_nonoverlapping(s.as_ptr(), s.len());
+    }
+    buf
+}
+
+pub unsafe fn from_akane_str<'a>(s: *const AkaneStr) -> &'a str {
+    let bytes = slice::from_raw_parts((*s).data.as_ptr(), (*s).len as usize);
+    assert_eq!(*(*s).data.as_ptr().add((*s).len as usize), 0);
+    str::from_utf8(bytes).unwrap()
+}
+
+pub fn to_akane_list(xs: &[i32]) -> Vec<i64> {
+    let mut buf = vec![0i64; 1 + xs.len().div_ceil(2)];
+    buf[0] = xs.len() as i64;
+    unsafe {
+        let data = buf.as_mut_ptr().add(1) as *mut i32;
+        data.copy_from_nonoverlapping(xs.as_ptr(), xs.len());
+    }
+    buf
+}
+
+pub unsafe fn from_akane_list<'a>(xs: *const AkaneList) -> &'a [i32] {
+    slice::from_raw_parts((*xs).data.as_ptr(), (*xs).len as usize)
 }
@@ -1,5 +1,10 @@
 mod ffi;
 
+use std::{
+    fs,
+    thread,
+};
+
 #[test]
 fn simple_add_one() {
     unsafe {
@@ -15,3 +20,215 @@ fn simple_add() {
         assert_eq!(ffi::simple_add(3, 5), 8);
     }
 }
+
+#[test]
+fn str_literal() {
+    unsafe {
+        assert_eq!(ffi::from_akane_str(ffi::hello()), "Hello, world!");
+    }
+}
+
+#[test]
+fn str_concat() {
+    unsafe {
+        let name = ffi::to_akane_str("Akane");
+        assert_eq!(ffi::from_akane_str(ffi::greet(name.as_ptr() as *const _)), "Hello, Akane!");
+        let name = ffi::to_akane_str("");
+        assert_eq!(ffi::from_akane_str(ffi::greet(name.as_ptr() as *const _)), "Hello, !");
+    }
+}
+
+#[test]
+fn str_length() {
+    unsafe {
+        let s = ffi::to_akane_str("");
+        assert_eq!(ffi::str_length(s.as_ptr() as *const _), 0);
+        let s = ffi::to_akane_str("あかね");
+        assert_eq!(ffi::str_length(s.as_ptr() as *const _), 9);
+        assert_eq!(ffi::escaped_length(), 5);
+    }
+}
+
+#[test]
+fn str_eq() {
+    unsafe {
+        let a = ffi::to_akane_str("akane");
+        let b = ffi::to_akane_str("akane");
+        let c = ffi::to_akane_str("akan");
+        let d = ffi::to_akane_str("akano");
+        assert_eq!(ffi::str_eq(a.as_ptr() as *const _, b.as_ptr() as *const _), 1);
+        assert_eq!(ffi::str_eq(a.as_ptr() as *const _, c.as_ptr() as *const _), 0);
+        assert_eq!(ffi::str_eq(a.as_ptr() as *const _, d.as_ptr() as *const _), 0);
+        assert_eq!(ffi::str_ne(a.as_ptr() as *const _, b.as_ptr() as *const _), 0);
+        assert_eq!(ffi::str_ne(a.as_ptr() as *const _, d.as_ptr() as *const _), 1);
+    }
+}
+
+#[test]
+fn char_literal() {
+    unsafe {
+        assert_eq!(ffi::char_code(), 'あ' as i32);
+    }
+}
+
+#[test]
+fn list_literal() {
+    unsafe {
+        assert_eq!(ffi::from_akane_list(ffi::list_literal()), &[1, 2, 3]);
+        assert_eq!(ffi::from_akane_list(ffi::empty_list()), &[] as &[i32]);
+    }
+}
+
+#[test]
+fn list_cons() {
+    unsafe {
+        let xs = ffi::to_akane_list(&[2, 3]);
+        assert_eq!(ffi::from_akane_list(ffi::list_cons(1, xs.as_ptr() as *const _)), &[1, 2, 3]);
+        let xs = ffi::to_akane_list(&[]);
+        assert_eq!(ffi::from_akane_list(ffi::list_cons(1, xs.as_ptr() as *const _)), &[1]);
+    }
+}
+
+#[test]
+fn list_concat() {
+    unsafe {
+        let xs = ffi::to_akane_list(&[1, 2]);
+        let ys = ffi::to_akane_list(&[3]);
+        assert_eq!(ffi::from_akane_list(ffi::list_concat(xs.as_ptr() as *const _, ys.as_ptr() as *const _)), &[1, 2, 3]);
+        assert_eq!(ffi::from_akane_list(ffi::list_concat(ys.as_ptr() as *const _, xs.as_ptr() as *const _)), &[3, 1, 2]);
+    }
+}
+
+#[test]
+fn list_index() {
+    unsafe {
+        let xs = ffi::to_akane_list(&[10, 20, 30]);
+        assert_eq!(ffi::list_index(xs.as_ptr() as *const _, 0), 10);
+        assert_eq!(ffi::list_index(xs.as_ptr() as *const _, 2), 30);
+    }
+}
+
+#[test]
+fn list_length() {
+    unsafe {
+        let xs = ffi::to_akane_list(&[10, 20, 30]);
+        assert_eq!(ffi::list_length(xs.as_ptr() as *const _), 3);
+        assert_eq!(ffi::list_length(ffi::empty_list()), 0);
+    }
+}
+
+#[test]
+fn list_map() {
+    unsafe {
+        let xs = ffi::to_akane_list(&[1, 2, 3]);
+        assert_eq!(ffi::from_akane_list(ffi::list_map_add_one(xs.as_ptr() as *const _)), &[2, 3, 4]);
+        assert_eq!(ffi::from_akane_list(ffi::list_map_add_one(ffi::empty_list())), &[] as &[i32]);
+    }
+}
+
+#[test]
+fn list_filter() {
+    unsafe {
+        let xs = ffi::to_akane_list(&[1, 2, 3, 2, 4]);
+        assert_eq!(ffi::from_akane_list(ffi::list_filter_not_two(xs.as_ptr() as *const _)), &[1, 3, 4]);
+        assert_eq!(ffi::from_akane_list(ffi::list_filter_not_two(ffi::empty_list())), &[] as &[i32]);
+    }
+}
+
+#[test]
+fn list_fold() {
+    unsafe {
+        let xs = ffi::to_akane_list(&[1, 2, 3, 4]);
+        assert_eq!(ffi::list_sum(xs.as_ptr() as *const _), 10);
+        assert_eq!(ffi::list_sum(ffi::empty_list()), 0);
+    }
+}
+
+#[test]
+fn fn_value() {
+    unsafe {
+        assert_eq!(ffi::apply_add_one(1), 2);
+    }
+}
+
+#[test]
+fn import_module() {
+    unsafe {
+        assert_eq!(ffi::math_quad(1), 4);
+        assert_eq!(ffi::math_quad(5), 20);
+    }
+}
+
+#[test]
+fn extern_fn() {
+    unsafe {
+        assert_eq!(ffi::c_abs(-5), 5);
+        assert_eq!(ffi::c_abs(3), 3);
+        let s = ffi::to_akane_str("あいう");
+        assert_eq!(ffi::c_count_bytes(s.as_ptr() as *const _), 9);
+    }
+}
+
+#[test]
+fn shared_module() {
+    unsafe {
+        assert_eq!(ffi::shared_octo(1), 8);
+        assert_eq!(ffi::math_quad(1), 4);
+    }
+}
+
+#[test]
+fn differential() {
+    let checker = thread::Builder::new().stack_size(64 * 1024 * 1024).spawn(|| {
+        let options = akanec::compiler::Options::default();
+        let mut paths = fs::read_dir("./corpus").unwrap().map(|entry| entry.unwrap().path()).collect::<Vec<_>>();
+        paths.sort();
+        for path in paths {
+            let path = path.to_str().unwrap();
+            if let Some(mismatch) = akanec::difftest::check_file(path, &options).unwrap() {
+                panic!("{}\n{}", path, mismatch);
+            }
+        }
+    });
+    checker.unwrap().join().unwrap();
+}
+
+/// Run with `AKANE_BLESS=1` to regenerate the expected files under `./snapshots`.
+#[test]
+fn snapshots() {
+    let options = akanec::compiler::Options::default();
+    let mismatches = akanec::snapshot::check_dir("./snapshots", &options, akanec::snapshot::is_bless()).unwrap();
+    if !mismatches.is_empty() {
+        let report = mismatches.iter().map(|mismatch| mismatch.to_string()).collect::<Vec<_>>().join("\n");
+        panic!("{} snapshot(s) differ\n{}", mismatches.len(), report);
+    }
+}
+
+/// Formatting at any width must keep the program the same and be stable.
+#[test]
+fn format_programs() {
+    use akanec::{
+        formatter,
+        lexer,
+        parser,
+    };
+    let parse = |code: &str| parser::parse(lexer::lex(code.to_owned()).unwrap()).unwrap().without_spans();
+    for dir in ["./src/tests/akane", "./corpus", "./snapshots"] {
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().map_or(true, |ext| ext != "akane") {
+                continue;
+            }
+            let code = fs::read_to_string(&path).unwrap();
+            if lexer::lex(code.clone()).and_then(parser::parse).is_err() {
+                continue;
+            }
+            for width in [20, 40, formatter::DEFAULT_WIDTH] {
+                let options = formatter::Options { width };
+                let formatted = formatter::format(&code, &options).unwrap();
+                assert_eq!(parse(&formatted), parse(&code), "{} at width {}", path.display(), width);
+                assert_eq!(formatter::format(&formatted, &options).unwrap(), formatted, "{} at width {}", path.display(), width);
+            }
+        }
+    }
+}